@@ -0,0 +1,247 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::opcodes::{self, Opcode};
+use crate::script::witness_version_and_program;
+use crate::{BitcoinError, Hash160, PublicKey, Script, ScriptBuilder, ScriptType, base58, bech32};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+impl Network {
+    fn p2pkh_prefix(self) -> u8 {
+        match self {
+            Network::Mainnet => 0x00,
+            Network::Testnet | Network::Signet | Network::Regtest => 0x6F,
+        }
+    }
+
+    fn p2sh_prefix(self) -> u8 {
+        match self {
+            Network::Mainnet => 0x05,
+            Network::Testnet | Network::Signet | Network::Regtest => 0xC4,
+        }
+    }
+
+    pub(crate) fn wif_prefix(self) -> u8 {
+        match self {
+            Network::Mainnet => 0x80,
+            Network::Testnet | Network::Signet | Network::Regtest => 0xEF,
+        }
+    }
+
+    fn bech32_hrp(self) -> &'static str {
+        match self {
+            Network::Mainnet => "bc",
+            Network::Testnet | Network::Signet => "tb",
+            Network::Regtest => "bcrt",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum AddressType {
+    P2pkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+    /// A witness program of a version with no defined meaning yet.
+    WitnessUnknown,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+enum Payload {
+    PubkeyHash(Hash160),
+    ScriptHash(Hash160),
+    WitnessProgram { version: u8, program: Vec<u8> },
+}
+
+/// An address for a standard scriptPubKey on a given network.
+///
+/// Testnet, signet and regtest share base58 prefixes, and testnet and signet share the
+/// `tb` bech32 prefix, so parsing such an address reports [`Network::Testnet`].
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Address {
+    network: Network,
+    payload: Payload,
+}
+
+impl Address {
+    pub fn from_script(script: &Script, network: Network) -> Result<Address, BitcoinError> {
+        let hash = |range: std::ops::Range<usize>| {
+            Hash160::try_from(&script[range]).expect("template has a 20-byte hash")
+        };
+        let payload = match script.classify() {
+            ScriptType::P2PKH => Payload::PubkeyHash(hash(3..23)),
+            ScriptType::P2SH => Payload::ScriptHash(hash(2..22)),
+            ScriptType::P2WPKH
+            | ScriptType::P2WSH
+            | ScriptType::P2TR
+            | ScriptType::WitnessUnknown => {
+                let (version, program) =
+                    witness_version_and_program(script).expect("classified as witness program");
+                Payload::WitnessProgram {
+                    version,
+                    program: program.to_vec(),
+                }
+            }
+            _ => return Err(BitcoinError::UnsupportedScript),
+        };
+        Ok(Address { network, payload })
+    }
+
+    /// Parses `s` and fails with [`BitcoinError::NetworkMismatch`] unless it is valid for
+    /// `network`.
+    pub fn from_str_for_network(s: &str, network: Network) -> Result<Address, BitcoinError> {
+        s.parse::<Address>()?.require_network(network)
+    }
+
+    /// Checks the address against `network`, returning it tagged with that network.
+    pub fn require_network(self, network: Network) -> Result<Address, BitcoinError> {
+        if !self.is_valid_for_network(network) {
+            return Err(BitcoinError::NetworkMismatch {
+                expected: network,
+                found: self.network,
+            });
+        }
+        Ok(Address { network, ..self })
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Whether the encoding is the one `network` uses, e.g. a base58 testnet address is
+    /// also valid for signet and regtest.
+    pub fn is_valid_for_network(&self, network: Network) -> bool {
+        match self.payload {
+            Payload::PubkeyHash(_) => self.network.p2pkh_prefix() == network.p2pkh_prefix(),
+            Payload::ScriptHash(_) => self.network.p2sh_prefix() == network.p2sh_prefix(),
+            Payload::WitnessProgram { .. } => self.network.bech32_hrp() == network.bech32_hrp(),
+        }
+    }
+
+    pub fn address_type(&self) -> AddressType {
+        match &self.payload {
+            Payload::PubkeyHash(_) => AddressType::P2pkh,
+            Payload::ScriptHash(_) => AddressType::P2sh,
+            Payload::WitnessProgram { version, program } => match (version, program.len()) {
+                (0, 20) => AddressType::P2wpkh,
+                (0, _) => AddressType::P2wsh,
+                (1, 32) => AddressType::P2tr,
+                _ => AddressType::WitnessUnknown,
+            },
+        }
+    }
+
+    pub fn script_pubkey(&self) -> Script {
+        match &self.payload {
+            Payload::PubkeyHash(hash) => Script::new_p2pkh(hash),
+            Payload::ScriptHash(hash) => Script::new_p2sh(hash),
+            Payload::WitnessProgram { version, program } => {
+                let version = match version {
+                    0 => opcodes::OP_0,
+                    v => Opcode::from_u8(opcodes::OP_1.to_u8() + v - 1),
+                };
+                ScriptBuilder::new()
+                    .push_opcode(version)
+                    .push_slice(program)
+                    .into_script()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (prefix, hash) = match &self.payload {
+            Payload::PubkeyHash(hash) => (self.network.p2pkh_prefix(), hash),
+            Payload::ScriptHash(hash) => (self.network.p2sh_prefix(), hash),
+            Payload::WitnessProgram { version, program } => {
+                let encoded = bech32::encode_segwit(self.network.bech32_hrp(), *version, program)
+                    .expect("program was validated on construction");
+                return f.write_str(&encoded);
+            }
+        };
+        let mut data = [0u8; 21];
+        data[0] = prefix;
+        data[1..].copy_from_slice(hash.as_bytes());
+        f.write_str(&base58::encode_check(&data))
+    }
+}
+
+impl FromStr for Address {
+    type Err = BitcoinError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lowercase = s.to_ascii_lowercase();
+        if ["bc1", "tb1", "bcrt1"]
+            .iter()
+            .any(|prefix| lowercase.starts_with(prefix))
+        {
+            let (hrp, version, program) = bech32::decode_segwit(s)?;
+            let network = match hrp.as_str() {
+                "bc" => Network::Mainnet,
+                "tb" => Network::Testnet,
+                "bcrt" => Network::Regtest,
+                _ => return Err(BitcoinError::InvalidFormat),
+            };
+            let payload = Payload::WitnessProgram { version, program };
+            return Ok(Address { network, payload });
+        }
+
+        let data = base58::decode_check(s)?;
+        let (&prefix, hash) = data.split_first().ok_or(BitcoinError::InvalidFormat)?;
+        let hash = Hash160::try_from(hash)?;
+        let (network, payload) = match prefix {
+            0x00 => (Network::Mainnet, Payload::PubkeyHash(hash)),
+            0x05 => (Network::Mainnet, Payload::ScriptHash(hash)),
+            0x6F => (Network::Testnet, Payload::PubkeyHash(hash)),
+            0xC4 => (Network::Testnet, Payload::ScriptHash(hash)),
+            _ => return Err(BitcoinError::InvalidFormat),
+        };
+        Ok(Address { network, payload })
+    }
+}
+
+impl PublicKey {
+    pub fn p2pkh_address(&self, network: Network) -> Address {
+        let payload = Payload::PubkeyHash(self.pubkey_hash());
+        Address { network, payload }
+    }
+
+    /// Fails for uncompressed keys, which BIP143 does not allow in segwit outputs.
+    pub fn p2wpkh_address(&self, network: Network) -> Result<Address, BitcoinError> {
+        if !self.is_compressed() {
+            return Err(BitcoinError::UncompressedKey);
+        }
+        let payload = Payload::WitnessProgram {
+            version: 0,
+            program: self.pubkey_hash().to_vec(),
+        };
+        Ok(Address { network, payload })
+    }
+}
+
+impl Script {
+    /// The address of a P2SH output with this script as its redeem script.
+    pub fn p2sh_address(&self, network: Network) -> Address {
+        let payload = Payload::ScriptHash(self.script_hash());
+        Address { network, payload }
+    }
+
+    /// The address of a P2WSH output with this script as its witness script.
+    pub fn p2wsh_address(&self, network: Network) -> Address {
+        let payload = Payload::WitnessProgram {
+            version: 0,
+            program: self.wscript_hash().to_vec(),
+        };
+        Address { network, payload }
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::BitcoinError;
+
+/// A value in satoshis. Serializes as the integer count; with the `strict` feature,
+/// deserializing rejects counts above [`Amount::MAX_MONEY`].
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize)]
+pub struct Amount(pub u64);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+    pub const ONE_SAT: Amount = Amount(1);
+    pub const ONE_BTC: Amount = Amount(100_000_000);
+    /// Core's `MAX_MONEY`: no output, or sum of outputs, may exceed 21 million BTC.
+    pub const MAX_MONEY: Amount = Amount(21_000_000 * 100_000_000);
+
+    pub const fn from_sat(sats: u64) -> Self {
+        Amount(sats)
+    }
+
+    pub const fn to_sat(self) -> u64 {
+        self.0
+    }
+
+    /// Rejects NaN, infinities, negative values, values above [`Amount::MAX_MONEY`], and
+    /// values finer than one satoshi.
+    pub fn from_btc(btc: f64) -> Result<Self, BitcoinError> {
+        if !btc.is_finite() || btc < 0.0 {
+            return Err(BitcoinError::InvalidAmount);
+        }
+        let sats = (btc * 1e8).round();
+        // A decimal with at most 8 places comes back to the same f64 after scaling.
+        if sats / 1e8 != btc {
+            return Err(BitcoinError::InvalidAmount);
+        }
+        if sats > Amount::MAX_MONEY.0 as f64 {
+            return Err(BitcoinError::ValueTooLarge {
+                value: sats as u64,
+                max: Amount::MAX_MONEY.0,
+            });
+        }
+        Ok(Amount(sats as u64))
+    }
+
+    /// Parses a plain decimal number in `denomination`, such as `"0.015"` or `".5"`.
+    /// Trailing zeros past the denomination's precision are allowed.
+    pub fn from_str_in(s: &str, denomination: Denomination) -> Result<Self, ParseAmountError> {
+        if s.starts_with('-') {
+            return Err(ParseAmountError::Negative);
+        }
+        let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+        let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if (int.is_empty() && frac.is_empty()) || !is_digits(int) || !is_digits(frac) {
+            return Err(ParseAmountError::InvalidFormat);
+        }
+        let precision = denomination.precision();
+        let frac = frac.trim_end_matches('0');
+        if frac.len() > precision {
+            return Err(ParseAmountError::TooPrecise);
+        }
+
+        let digits = int
+            .bytes()
+            .chain(frac.bytes())
+            .chain(std::iter::repeat_n(b'0', precision - frac.len()));
+        let mut sats = 0u64;
+        for digit in digits {
+            sats = sats
+                .checked_mul(10)
+                .and_then(|sats| sats.checked_add(u64::from(digit - b'0')))
+                .ok_or(ParseAmountError::TooLarge)?;
+        }
+        let amount = Amount(sats);
+        if !amount.is_valid() {
+            return Err(ParseAmountError::TooLarge);
+        }
+        Ok(amount)
+    }
+
+    /// Formats in `denomination` with its unit, without trailing zeros: `0.015 BTC`.
+    pub fn display_in(self, denomination: Denomination) -> AmountDisplay {
+        AmountDisplay {
+            amount: self,
+            denomination,
+        }
+    }
+
+    pub fn to_btc(self) -> f64 {
+        self.0 as f64 / 1e8
+    }
+
+    pub fn is_valid(self) -> bool {
+        self <= Amount::MAX_MONEY
+    }
+
+    /// Errors on overflow, or if the sum exceeds [`Amount::MAX_MONEY`].
+    pub fn checked_add(self, rhs: Amount) -> Result<Amount, BitcoinError> {
+        let sum = self
+            .0
+            .checked_add(rhs.0)
+            .ok_or(BitcoinError::IntegerOverflow)?;
+        Amount(sum).check_max()
+    }
+
+    /// Errors if `rhs` is larger, or if either side exceeds [`Amount::MAX_MONEY`].
+    pub fn checked_sub(self, rhs: Amount) -> Result<Amount, BitcoinError> {
+        self.check_max()?;
+        rhs.check_max()?;
+        self.0
+            .checked_sub(rhs.0)
+            .map(Amount)
+            .ok_or(BitcoinError::IntegerOverflow)
+    }
+
+    fn check_max(self) -> Result<Amount, BitcoinError> {
+        if !self.is_valid() {
+            return Err(BitcoinError::ValueTooLarge {
+                value: self.0,
+                max: Amount::MAX_MONEY.0,
+            });
+        }
+        Ok(self)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} sat", self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let amount = Amount(u64::deserialize(deserializer)?);
+        if cfg!(feature = "strict") && !amount.is_valid() {
+            return Err(serde::de::Error::custom(format_args!(
+                "{amount} exceeds MAX_MONEY"
+            )));
+        }
+        Ok(amount)
+    }
+}
+
+/// A number and a unit separated by one space: `0.015 BTC`, `1500 sat`, `12.3 mBTC`.
+impl FromStr for Amount {
+    type Err = ParseAmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (number, unit) = s
+            .split_once(' ')
+            .ok_or(ParseAmountError::MissingDenomination)?;
+        Amount::from_str_in(number, unit.parse()?)
+    }
+}
+
+impl From<Amount> for u64 {
+    fn from(amount: Amount) -> Self {
+        amount.0
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Denomination {
+    Bitcoin,
+    MilliBitcoin,
+    MicroBitcoin,
+    Satoshi,
+}
+
+impl Denomination {
+    /// Decimal places below one unit, down to the satoshi.
+    pub fn precision(self) -> usize {
+        match self {
+            Denomination::Bitcoin => 8,
+            Denomination::MilliBitcoin => 5,
+            Denomination::MicroBitcoin => 2,
+            Denomination::Satoshi => 0,
+        }
+    }
+}
+
+impl fmt::Display for Denomination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Denomination::Bitcoin => "BTC",
+            Denomination::MilliBitcoin => "mBTC",
+            Denomination::MicroBitcoin => "uBTC",
+            Denomination::Satoshi => "sat",
+        })
+    }
+}
+
+/// Accepts the names produced by `Display`, plus `sats`.
+impl FromStr for Denomination {
+    type Err = ParseAmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "BTC" => Denomination::Bitcoin,
+            "mBTC" => Denomination::MilliBitcoin,
+            "uBTC" => Denomination::MicroBitcoin,
+            "sat" | "sats" => Denomination::Satoshi,
+            _ => return Err(ParseAmountError::UnknownDenomination),
+        })
+    }
+}
+
+/// Which rule a string broke in [`Amount::from_str_in`] or `Amount::from_str`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ParseAmountError {
+    /// Not digits with at most one decimal point and at least one digit.
+    InvalidFormat,
+    Negative,
+    /// More decimal places than the denomination can hold in whole satoshis.
+    TooPrecise,
+    /// Above [`Amount::MAX_MONEY`].
+    TooLarge,
+    MissingDenomination,
+    UnknownDenomination,
+}
+
+/// Returned by [`Amount::display_in`].
+#[derive(Debug, Clone, Copy)]
+pub struct AmountDisplay {
+    amount: Amount,
+    denomination: Denomination,
+}
+
+impl fmt::Display for AmountDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = self.denomination.precision();
+        let unit = 10u64.pow(precision as u32);
+        let (whole, frac) = (self.amount.0 / unit, self.amount.0 % unit);
+        if frac == 0 {
+            write!(f, "{whole} {}", self.denomination)
+        } else {
+            let frac = format!("{frac:0precision$}");
+            write!(
+                f,
+                "{whole}.{} {}",
+                frac.trim_end_matches('0'),
+                self.denomination
+            )
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{
+    Amount, BitcoinTransaction, CompactSize, OutPoint, Script, TransactionInput, TransactionOutput,
+    Txid, Witness,
+};
+
+// Long enough to exercise the 0xFD length prefix without making fuzz inputs huge.
+const MAX_SCRIPT_LEN: usize = 1_000;
+const MAX_INPUTS: usize = 8;
+const MAX_OUTPUTS: usize = 8;
+const MAX_WITNESS_ELEMENTS: usize = 4;
+
+impl<'a> Arbitrary<'a> for CompactSize {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(CompactSize::new(u64::arbitrary(u)?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Txid {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Txid(<[u8; 32]>::arbitrary(u)?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for OutPoint {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(OutPoint {
+            txid: Txid::arbitrary(u)?,
+            vout: u32::arbitrary(u)?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Script {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let len = u.int_in_range(0..=MAX_SCRIPT_LEN)?;
+        Ok(Script::new(u.bytes(len)?.to_vec()))
+    }
+}
+
+impl<'a> Arbitrary<'a> for TransactionInput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(TransactionInput::new(
+            OutPoint::arbitrary(u)?,
+            Script::arbitrary(u)?,
+            u32::arbitrary(u)?,
+        ))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Witness {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let element_count = u.int_in_range(0..=MAX_WITNESS_ELEMENTS)?;
+        (0..element_count)
+            .map(|_| {
+                let len = u.int_in_range(0..=MAX_SCRIPT_LEN)?;
+                u.bytes(len)
+            })
+            .collect()
+    }
+}
+
+// Any u64, so values above MAX_MONEY get exercised too.
+impl<'a> Arbitrary<'a> for Amount {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Amount::from_sat(u64::arbitrary(u)?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for TransactionOutput {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(TransactionOutput::new(
+            Amount::arbitrary(u)?,
+            Script::arbitrary(u)?,
+        ))
+    }
+}
+
+impl<'a> Arbitrary<'a> for BitcoinTransaction {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let input_count = u.int_in_range(0..=MAX_INPUTS)?;
+        let inputs = (0..input_count)
+            .map(|_| {
+                let mut input = TransactionInput::arbitrary(u)?;
+                input.witness = Witness::arbitrary(u)?;
+                Ok(input)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // Without inputs, an output count would read as the segwit flag.
+        let max_outputs = if inputs.is_empty() { 0 } else { MAX_OUTPUTS };
+        let output_count = u.int_in_range(0..=max_outputs)?;
+        let outputs = (0..output_count)
+            .map(|_| TransactionOutput::arbitrary(u))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(BitcoinTransaction::new_with_outputs(
+            u32::arbitrary(u)?,
+            inputs,
+            outputs,
+            u32::arbitrary(u)?,
+        ))
+    }
+}
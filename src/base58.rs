@@ -0,0 +1,75 @@
+use crate::{BitcoinError, sha256d};
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+pub fn encode(data: &[u8]) -> String {
+    let zeros = data.iter().take_while(|&&byte| byte == 0).count();
+    // Little-endian base58 digits of the big-endian number in `data`.
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in &data[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    std::iter::repeat_n('1', zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|&digit| ALPHABET[digit as usize] as char),
+        )
+        .collect()
+}
+
+pub fn decode(s: &str) -> Result<Vec<u8>, BitcoinError> {
+    let zeros = s.bytes().take_while(|&c| c == b'1').count();
+    // Little-endian bytes of the number, built up one base58 digit at a time.
+    let mut bytes: Vec<u8> = Vec::with_capacity(s.len() * 733 / 1000 + 1);
+    for c in s.chars().skip(zeros) {
+        let mut carry = ALPHABET
+            .iter()
+            .position(|&symbol| symbol as char == c)
+            .ok_or(BitcoinError::InvalidBase58Character(c))? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = (carry & 0xFF) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xFF) as u8);
+            carry >>= 8;
+        }
+    }
+    bytes.extend(std::iter::repeat_n(0, zeros));
+    bytes.reverse();
+    Ok(bytes)
+}
+
+/// Appends the first four bytes of `sha256d(data)` before encoding.
+pub fn encode_check(data: &[u8]) -> String {
+    let checksum = sha256d(data);
+    let mut payload = Vec::with_capacity(data.len() + 4);
+    payload.extend_from_slice(data);
+    payload.extend_from_slice(&checksum.as_bytes()[..4]);
+    encode(&payload)
+}
+
+/// Decodes and strips the four checksum bytes, which must match.
+pub fn decode_check(s: &str) -> Result<Vec<u8>, BitcoinError> {
+    let mut payload = decode(s)?;
+    if payload.len() < 4 {
+        return Err(BitcoinError::InsufficientBytes);
+    }
+    let checksum = payload.split_off(payload.len() - 4);
+    if checksum != sha256d(&payload).as_bytes()[..4] {
+        return Err(BitcoinError::InvalidChecksum);
+    }
+    Ok(payload)
+}
@@ -0,0 +1,167 @@
+use crate::BitcoinError;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+const MAX_LENGTH: usize = 90;
+
+/// Checksum flavour: BIP173 bech32 for v0 witness programs, BIP350 bech32m for v1+.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    const fn constant(self) -> u32 {
+        match self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => 0x2bc830a3,
+        }
+    }
+}
+
+fn polymod(values: impl IntoIterator<Item = u8>) -> u32 {
+    values.into_iter().fold(1, |chk, value| {
+        let top = chk >> 25;
+        let chk = ((chk & 0x1ffffff) << 5) ^ value as u32;
+        GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold(chk, |chk, (_, generator)| chk ^ generator)
+    })
+}
+
+fn hrp_expand(hrp: &str) -> impl Iterator<Item = u8> + '_ {
+    hrp.bytes()
+        .map(|c| c >> 5)
+        .chain([0])
+        .chain(hrp.bytes().map(|c| c & 31))
+}
+
+/// Regroups bits, e.g. 8-bit bytes into 5-bit values. Without `pad`, leftover bits must be
+/// fewer than `from` and all zero.
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, BitcoinError> {
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let max = (1 << to) - 1;
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for &value in data {
+        if (value as u32) >> from != 0 {
+            return Err(BitcoinError::InvalidFormat);
+        }
+        acc = (acc << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return Err(BitcoinError::InvalidFormat);
+    }
+    Ok(out)
+}
+
+/// Encodes 5-bit `data` under `hrp`, always in lowercase.
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> String {
+    let hrp = hrp.to_ascii_lowercase();
+    let checksum =
+        polymod(hrp_expand(&hrp).chain(data.iter().copied()).chain([0; 6])) ^ variant.constant();
+    let checksum = (0..6).map(|i| ((checksum >> (5 * (5 - i))) & 31) as u8);
+    let mut encoded = hrp;
+    encoded.push('1');
+    encoded.extend(
+        data.iter()
+            .copied()
+            .chain(checksum)
+            .map(|v| CHARSET[v as usize] as char),
+    );
+    encoded
+}
+
+/// Decodes a bech32 or bech32m string into its lowercase HRP and 5-bit data, without the
+/// checksum.
+pub fn decode(s: &str) -> Result<(String, Vec<u8>, Variant), BitcoinError> {
+    if let Some(c) = s.chars().find(|c| !(33..=126).contains(&(*c as u32))) {
+        return Err(BitcoinError::InvalidBech32Character(c));
+    }
+    if s.chars().any(|c| c.is_ascii_lowercase()) && s.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(BitcoinError::MixedCase);
+    }
+    let s = s.to_ascii_lowercase();
+    let separator = s.rfind('1').ok_or(BitcoinError::InvalidFormat)?;
+    if separator == 0 || separator + 7 > s.len() || s.len() > MAX_LENGTH {
+        return Err(BitcoinError::InvalidFormat);
+    }
+    let (hrp, data) = (&s[..separator], &s[separator + 1..]);
+    let data = data
+        .chars()
+        .map(|c| {
+            CHARSET
+                .iter()
+                .position(|&symbol| symbol as char == c)
+                .map(|v| v as u8)
+                .ok_or(BitcoinError::InvalidBech32Character(c))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let variant = match polymod(hrp_expand(hrp).chain(data.iter().copied())) {
+        c if c == Variant::Bech32.constant() => Variant::Bech32,
+        c if c == Variant::Bech32m.constant() => Variant::Bech32m,
+        _ => return Err(BitcoinError::InvalidChecksum),
+    };
+    let hrp = hrp.to_string();
+    let mut data = data;
+    data.truncate(data.len() - 6);
+    Ok((hrp, data, variant))
+}
+
+/// Encodes a witness program as a segwit address, choosing the variant from the version.
+pub fn encode_segwit(hrp: &str, version: u8, program: &[u8]) -> Result<String, BitcoinError> {
+    check_witness_program(version, program)?;
+    let variant = if version == 0 {
+        Variant::Bech32
+    } else {
+        Variant::Bech32m
+    };
+    let mut data = vec![version];
+    data.extend(convert_bits(program, 8, 5, true)?);
+    Ok(encode(hrp, &data, variant))
+}
+
+/// Decodes a segwit address into its HRP, witness version and program, enforcing the
+/// BIP141 program rules and the BIP350 choice of checksum variant.
+pub fn decode_segwit(s: &str) -> Result<(String, u8, Vec<u8>), BitcoinError> {
+    let (hrp, data, variant) = decode(s)?;
+    let (&version, data) = data.split_first().ok_or(BitcoinError::InvalidFormat)?;
+    let program = convert_bits(data, 5, 8, false)?;
+    check_witness_program(version, &program)?;
+    let expected = if version == 0 {
+        Variant::Bech32
+    } else {
+        Variant::Bech32m
+    };
+    if variant != expected {
+        return Err(BitcoinError::InvalidChecksum);
+    }
+    Ok((hrp, version, program))
+}
+
+fn check_witness_program(version: u8, program: &[u8]) -> Result<(), BitcoinError> {
+    if version > 16 {
+        return Err(BitcoinError::InvalidWitnessVersion(version));
+    }
+    let valid_len = match version {
+        0 => program.len() == 20 || program.len() == 32,
+        _ => (2..=40).contains(&program.len()),
+    };
+    if !valid_len {
+        return Err(BitcoinError::InvalidWitnessProgramLength(program.len()));
+    }
+    Ok(())
+}
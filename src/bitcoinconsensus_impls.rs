@@ -0,0 +1,67 @@
+use std::ffi::{c_int, c_uint};
+
+use crate::verify::{ScriptVerifier, VERIFY_TAPROOT, VerifyError};
+
+#[link(name = "bitcoinconsensus")]
+unsafe extern "C" {
+    fn bitcoinconsensus_verify_script_with_amount(
+        script_pubkey: *const u8,
+        script_pubkey_len: c_uint,
+        amount: i64,
+        tx_to: *const u8,
+        tx_to_len: c_uint,
+        n_in: c_uint,
+        flags: c_uint,
+        err: *mut c_int,
+    ) -> c_int;
+}
+
+/// A [`ScriptVerifier`] running Bitcoin Core's interpreter through the system
+/// libbitcoinconsensus.
+///
+/// The library needs every spent output to check taproot spends, which the trait does not
+/// pass, so `VERIFY_TAPROOT` is dropped: taproot outputs are accepted without evaluation,
+/// as before the soft fork.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct BitcoinConsensus;
+
+impl ScriptVerifier for BitcoinConsensus {
+    fn verify(
+        &self,
+        script_pubkey: &[u8],
+        amount: u64,
+        tx_bytes: &[u8],
+        input_index: usize,
+        flags: u32,
+    ) -> Result<(), VerifyError> {
+        let failed = VerifyError::ScriptFailed { index: input_index };
+        let (Ok(script_pubkey_len), Ok(amount), Ok(tx_len), Ok(n_in)) = (
+            c_uint::try_from(script_pubkey.len()),
+            i64::try_from(amount),
+            c_uint::try_from(tx_bytes.len()),
+            c_uint::try_from(input_index),
+        ) else {
+            return Err(failed);
+        };
+        let mut err: c_int = 0;
+        // SAFETY: the pointers and lengths come from live slices, which the library only
+        // reads during the call, and `err` outlives it.
+        let verified = unsafe {
+            bitcoinconsensus_verify_script_with_amount(
+                script_pubkey.as_ptr(),
+                script_pubkey_len,
+                amount,
+                tx_bytes.as_ptr(),
+                tx_len,
+                n_in,
+                flags & !VERIFY_TAPROOT,
+                &mut err,
+            )
+        };
+        match (verified, err) {
+            (1, _) => Ok(()),
+            (_, 0) => Err(failed),
+            (_, code) => Err(VerifyError::Library(code)),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{
+    Amount, BitcoinError, BitcoinTransaction, OutPoint, Script, Sequence, TransactionInput,
+    TransactionOutput, Witness,
+};
+
+/// One input for [`TransactionBuilder`]: an empty scriptSig and witness, and an
+/// RBF-signaling sequence unless set otherwise.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InputBuilder {
+    input: TransactionInput,
+}
+
+impl InputBuilder {
+    pub fn new(previous_output: OutPoint) -> Self {
+        InputBuilder {
+            input: TransactionInput {
+                previous_output,
+                script_sig: Script::new(vec![]),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::default(),
+            },
+        }
+    }
+
+    pub fn script_sig(mut self, script_sig: Script) -> Self {
+        self.input.script_sig = script_sig;
+        self
+    }
+
+    pub fn sequence(mut self, sequence: Sequence) -> Self {
+        self.input.sequence = sequence;
+        self
+    }
+
+    pub fn witness(mut self, witness: Witness) -> Self {
+        self.input.witness = witness;
+        self
+    }
+
+    pub fn into_input(self) -> TransactionInput {
+        self.input
+    }
+}
+
+impl From<OutPoint> for InputBuilder {
+    fn from(previous_output: OutPoint) -> Self {
+        InputBuilder::new(previous_output)
+    }
+}
+
+/// Builds a version 2 transaction with a zero lock time unless set otherwise.
+///
+/// ```
+/// use rust_week_3_exercises::{
+///     Amount, Hash160, InputBuilder, OutPoint, Script, Sequence, TransactionBuilder,
+/// };
+///
+/// let tx = TransactionBuilder::new()
+///     .lock_time(840_000)
+///     .add_input(OutPoint::new([0x11; 32], 0))
+///     .add_input(InputBuilder::new(OutPoint::new([0x22; 32], 1)).sequence(Sequence::MAX))
+///     .add_output(Amount::from_sat(50_000), Script::new_p2wpkh(&Hash160([0x33; 20])))
+///     .build()
+///     .unwrap();
+/// assert_eq!(tx.version, 2);
+/// assert_eq!(tx.inputs.len(), 2);
+/// assert_eq!(tx.outputs.len(), 1);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TransactionBuilder {
+    version: u32,
+    lock_time: u32,
+    inputs: Vec<TransactionInput>,
+    outputs: Vec<TransactionOutput>,
+}
+
+impl Default for TransactionBuilder {
+    fn default() -> Self {
+        TransactionBuilder {
+            version: 2,
+            lock_time: 0,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+}
+
+impl TransactionBuilder {
+    pub fn new() -> Self {
+        TransactionBuilder::default()
+    }
+
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn lock_time(mut self, lock_time: u32) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    /// Takes an [`InputBuilder`], or a bare `OutPoint` for an input with the defaults.
+    pub fn add_input<I: Into<InputBuilder>>(mut self, input: I) -> Self {
+        self.inputs.push(input.into().into_input());
+        self
+    }
+
+    pub fn add_output(mut self, value: Amount, script_pubkey: Script) -> Self {
+        self.outputs
+            .push(TransactionOutput::new(value, script_pubkey));
+        self
+    }
+
+    /// Fails with [`BitcoinError::NoInputs`] if no input was added.
+    pub fn build(self) -> Result<BitcoinTransaction, BitcoinError> {
+        if self.inputs.is_empty() {
+            return Err(BitcoinError::NoInputs);
+        }
+        Ok(BitcoinTransaction::new_with_outputs(
+            self.version,
+            self.inputs,
+            self.outputs,
+            self.lock_time,
+        ))
+    }
+}
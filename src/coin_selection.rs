@@ -0,0 +1,162 @@
+use crate::{
+    Amount, FeeRate, Hash160, InputType, OutPoint, Script, ScriptType, TransactionInput,
+    TransactionOutput, Weight, compact_size_len,
+};
+
+/// The order in which [`select_coins`] takes candidate UTXOs until the payment is covered.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SelectionStrategy {
+    /// Largest value first, which keeps the input count and fee low.
+    LargestFirst,
+    /// A shuffle determined by `seed`, so repeated payments don't reveal the largest coins.
+    SingleRandomDraw { seed: u64 },
+}
+
+/// The result of [`select_coins`]. `input_value` is always `target + fee + change`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Selection {
+    pub outpoints: Vec<OutPoint>,
+    pub input_value: Amount,
+    pub fee: Amount,
+    /// Zero when the leftover would be a dust output; it is then paid as fee.
+    pub change: Amount,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SelectionError {
+    /// The usable UTXOs are worth less than the target.
+    InsufficientFunds { available: Amount, target: Amount },
+    /// The usable UTXOs cover the target, but not the fee for spending them.
+    CannotCoverFee { available: Amount, target: Amount },
+}
+
+// The change output is assumed to be P2WPKH: value, script length and a 22-byte script.
+const CHANGE_OUTPUT_WEIGHT: u64 = (8 + 1 + 22) * 4;
+
+struct Candidate<'a> {
+    outpoint: &'a OutPoint,
+    value: u64,
+    weight: u64,
+    is_segwit: bool,
+}
+
+/// Picks UTXOs to pay `target` plus the fee at `fee_rate`.
+///
+/// The fee covers the transaction overhead, the selected inputs as estimated by
+/// [`TransactionInput::estimated_weight_for`], and a P2WPKH change output when one is made.
+/// The payment outputs are not known here, so their fee must be included in `target`.
+/// Only P2PKH, P2WPKH and P2TR (key path) UTXOs can be estimated; others are ignored, as
+/// are UTXOs worth less than the fee to spend them.
+pub fn select_coins(
+    utxos: &[(OutPoint, TransactionOutput)],
+    target: Amount,
+    fee_rate: FeeRate,
+    strategy: SelectionStrategy,
+) -> Result<Selection, SelectionError> {
+    let mut candidates: Vec<Candidate<'_>> = utxos
+        .iter()
+        .filter_map(|(outpoint, output)| {
+            let input_type = spend_type(&output.script_pubkey)?;
+            Some(Candidate {
+                outpoint,
+                value: output.value.to_sat(),
+                weight: TransactionInput::estimated_weight_for(input_type) as u64,
+                is_segwit: input_type != InputType::P2pkh,
+            })
+        })
+        .collect();
+
+    let available = Amount::from_sat(
+        candidates
+            .iter()
+            .fold(0u64, |sum, candidate| sum.saturating_add(candidate.value)),
+    );
+    if available < target {
+        return Err(SelectionError::InsufficientFunds { available, target });
+    }
+
+    candidates.retain(|candidate| fee(fee_rate, candidate.weight) < candidate.value);
+    match strategy {
+        SelectionStrategy::LargestFirst => {
+            candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.value));
+        }
+        SelectionStrategy::SingleRandomDraw { seed } => shuffle(&mut candidates, seed),
+    }
+
+    let target_sats = target.to_sat();
+    let change_dust_limit =
+        TransactionOutput::min_non_dust(&Script::new_p2wpkh(&Hash160([0; 20])), FeeRate::DUST_RATE)
+            .to_sat();
+    let mut outpoints = Vec::new();
+    let mut input_value = 0u64;
+    let mut inputs_weight = 0u64;
+    let mut legacy_inputs = 0u64;
+    let mut any_segwit = false;
+    for candidate in candidates {
+        outpoints.push(candidate.outpoint.clone());
+        input_value = input_value.saturating_add(candidate.value);
+        inputs_weight += candidate.weight;
+        if candidate.is_segwit {
+            any_segwit = true;
+        } else {
+            legacy_inputs += 1;
+        }
+
+        // Version, input count, a one-byte output count and lock time, then the inputs.
+        let input_count_len = compact_size_len(outpoints.len() as u64) as u64;
+        let mut weight = 4 * (4 + input_count_len + 1 + 4) + inputs_weight;
+        if any_segwit {
+            // The marker and flag, and an empty witness for each legacy input.
+            weight += 2 + legacy_inputs;
+        }
+
+        let fee_without_change = fee(fee_rate, weight);
+        if input_value < target_sats.saturating_add(fee_without_change) {
+            continue;
+        }
+        let fee_with_change = fee(fee_rate, weight + CHANGE_OUTPUT_WEIGHT);
+        let (fee, change) = match (input_value - target_sats).checked_sub(fee_with_change) {
+            Some(change) if change >= change_dust_limit => (fee_with_change, change),
+            _ => (input_value - target_sats, 0),
+        };
+        return Ok(Selection {
+            outpoints,
+            input_value: Amount::from_sat(input_value),
+            fee: Amount::from_sat(fee),
+            change: Amount::from_sat(change),
+        });
+    }
+    Err(SelectionError::CannotCoverFee { available, target })
+}
+
+fn spend_type(script_pubkey: &Script) -> Option<InputType> {
+    match script_pubkey.classify() {
+        ScriptType::P2PKH => Some(InputType::P2pkh),
+        ScriptType::P2WPKH => Some(InputType::P2wpkh),
+        ScriptType::P2TR => Some(InputType::P2trKeySpend),
+        _ => None,
+    }
+}
+
+// A fee too large to compute can never be paid.
+fn fee(fee_rate: FeeRate, weight: u64) -> u64 {
+    fee_rate
+        .fee_for_weight(Weight::from_wu(weight))
+        .map_or(u64::MAX, Amount::to_sat)
+}
+
+// Fisher-Yates driven by splitmix64, which is well mixed for any seed, including zero.
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    };
+    for i in (1..items.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    BitcoinError, CompactSize, OutPoint, SEGWIT_FLAG, SEGWIT_MARKER, ScriptRef, Sequence,
+    TransactionInput, Txid, Witness, parse_u32_le,
+};
+
+/// An input borrowed from a serialized transaction, decoded without copying its txid or
+/// scriptSig out of the buffer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TransactionInputRef<'a> {
+    txid: &'a [u8; 32],
+    vout: u32,
+    script_sig: ScriptRef<'a>,
+    sequence: Sequence,
+}
+
+impl<'a> TransactionInputRef<'a> {
+    /// The input encoding shared with [`TransactionInput::parse`].
+    pub fn parse(bytes: &'a [u8]) -> Result<(Self, &'a [u8]), BitcoinError> {
+        let (txid, rest) = bytes
+            .split_first_chunk::<32>()
+            .ok_or(BitcoinError::InsufficientBytes)?;
+        let (vout, rest) = parse_u32_le(rest)?;
+        let (script_sig, rest) = ScriptRef::parse(rest)?;
+        let (sequence, rest) = parse_u32_le(rest)?;
+        let input = TransactionInputRef {
+            txid,
+            vout,
+            script_sig,
+            sequence: Sequence(sequence),
+        };
+        Ok((input, rest))
+    }
+
+    /// The txid in internal byte order, as stored on the wire.
+    pub fn txid_bytes(&self) -> &'a [u8; 32] {
+        self.txid
+    }
+
+    pub fn vout(&self) -> u32 {
+        self.vout
+    }
+
+    pub fn previous_output(&self) -> OutPoint {
+        OutPoint::from_txid(Txid(*self.txid), self.vout)
+    }
+
+    pub fn script_sig(&self) -> ScriptRef<'a> {
+        self.script_sig
+    }
+
+    pub fn script_sig_bytes(&self) -> &'a [u8] {
+        self.script_sig.as_bytes()
+    }
+
+    pub fn sequence(&self) -> Sequence {
+        self.sequence
+    }
+
+    pub fn to_input(&self) -> TransactionInput {
+        TransactionInput {
+            previous_output: self.previous_output(),
+            script_sig: self.script_sig.to_script(),
+            sequence: self.sequence,
+            witness: Witness::default(),
+        }
+    }
+}
+
+/// Entry points for reading parts of a serialized transaction in place.
+pub struct TxDecoder;
+
+impl TxDecoder {
+    /// Iterates the inputs of a transaction in either serialization. Only the version,
+    /// segwit marker and input count are read up front.
+    pub fn inputs(bytes: &[u8]) -> Result<InputIter<'_>, BitcoinError> {
+        let (_version, rest) = parse_u32_le(bytes)?;
+        let rest = match rest {
+            [SEGWIT_MARKER, SEGWIT_FLAG, rest @ ..] => rest,
+            _ => rest,
+        };
+        // Same bound as the eager parser: every input takes at least 41 bytes.
+        let max_inputs = rest.len() as u64 / TransactionInput::MIN_SIZE as u64;
+        let (count, consumed) = CompactSize::from_bytes_with_max(rest, max_inputs)?;
+        Ok(InputIter {
+            rest: &rest[consumed..],
+            remaining: count.value as usize,
+        })
+    }
+}
+
+/// Iterator over borrowed inputs. Stops after the first error.
+#[derive(Debug, Clone)]
+pub struct InputIter<'a> {
+    rest: &'a [u8],
+    remaining: usize,
+}
+
+impl<'a> InputIter<'a> {
+    /// The bytes after the inputs read so far; once exhausted, the outputs, any witnesses,
+    /// and the lock time.
+    pub fn remainder(&self) -> &'a [u8] {
+        self.rest
+    }
+}
+
+impl<'a> Iterator for InputIter<'a> {
+    type Item = Result<TransactionInputRef<'a>, BitcoinError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        match TransactionInputRef::parse(self.rest) {
+            Ok((input, rest)) => {
+                self.rest = rest;
+                self.remaining -= 1;
+                Some(Ok(input))
+            }
+            Err(err) => {
+                self.remaining = 0;
+                Some(Err(err))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
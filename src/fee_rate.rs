@@ -0,0 +1,58 @@
+use std::fmt;
+
+use crate::{Amount, Weight};
+
+/// A fee rate, kept in satoshis per 1000 weight units so that sat/vB rates convert
+/// exactly.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct FeeRate(u64);
+
+impl FeeRate {
+    pub const ZERO: FeeRate = FeeRate(0);
+    /// Core's default `minrelaytxfee`, the lowest rate a node will relay.
+    pub const BROADCAST_MIN: FeeRate = FeeRate(250);
+    /// Core's `DUST_RELAY_TX_FEE`.
+    pub const DUST_RATE: FeeRate = FeeRate(3 * 250);
+
+    pub const fn from_sat_per_kwu(sat_per_kwu: u64) -> FeeRate {
+        FeeRate(sat_per_kwu)
+    }
+
+    /// `None` if the rate does not fit in sat/kwu.
+    pub const fn from_sat_per_vb(sat_per_vb: u64) -> Option<FeeRate> {
+        match sat_per_vb.checked_mul(250) {
+            Some(sat_per_kwu) => Some(FeeRate(sat_per_kwu)),
+            None => None,
+        }
+    }
+
+    pub const fn to_sat_per_kwu(self) -> u64 {
+        self.0
+    }
+
+    /// Rounded up to the next satoshi, so a relayer is never paid less than the rate.
+    /// `None` on overflow.
+    pub fn fee_for_weight(&self, weight: Weight) -> Option<Amount> {
+        let fee = weight.to_wu().checked_mul(self.0)?.div_ceil(1000);
+        Some(Amount::from_sat(fee))
+    }
+
+    /// Rounded up like [`FeeRate::fee_for_weight`].
+    pub fn fee_for_vsize(&self, vbytes: u64) -> Option<Amount> {
+        self.fee_for_weight(Weight::from_wu(vbytes.checked_mul(4)?))
+    }
+}
+
+/// In sat/vB, with up to three decimals: a sat/kwu rate is a whole number of 1/250 sat/vB.
+impl fmt::Display for FeeRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whole = self.0 / 250;
+        let thousandths = self.0 % 250 * 4;
+        if thousandths == 0 {
+            write!(f, "{whole} sat/vB")
+        } else {
+            let fraction = format!("{thousandths:03}");
+            write!(f, "{whole}.{} sat/vB", fraction.trim_end_matches('0'))
+        }
+    }
+}
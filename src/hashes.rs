@@ -0,0 +1,230 @@
+use std::sync::OnceLock;
+
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+// Shared by every fixed-size hash type: hex Debug/Display/FromStr in display order, and
+// serde as an internal-order hex string (human-readable) or raw bytes (binary).
+macro_rules! hash_newtype {
+    ($name:ident, $len:expr, reversed_display = $reversed:expr) => {
+        impl $name {
+            pub const ZERO: $name = $name([0u8; $len]);
+
+            pub const fn all_zeros() -> Self {
+                $name::ZERO
+            }
+
+            pub fn is_zero(&self) -> bool {
+                self.0 == [0u8; $len]
+            }
+
+            pub fn from_bytes(bytes: [u8; $len]) -> Self {
+                $name(bytes)
+            }
+
+            /// Parses the display byte order, same as `FromStr`.
+            pub fn from_hex(s: &str) -> Result<Self, $crate::BitcoinError> {
+                s.parse()
+            }
+
+            pub fn as_bytes(&self) -> &[u8; $len] {
+                &self.0
+            }
+
+            pub fn to_vec(&self) -> Vec<u8> {
+                self.0.to_vec()
+            }
+
+            pub fn to_hex_internal(&self) -> String {
+                ::hex::encode(self.0)
+            }
+
+            pub fn from_hex_internal(s: &str) -> Result<Self, $crate::BitcoinError> {
+                let mut array = [0u8; $len];
+                ::hex::decode_to_slice(s, &mut array)
+                    .map_err(|_| $crate::BitcoinError::InvalidFormat)?;
+                Ok($name(array))
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl From<[u8; $len]> for $name {
+            fn from(bytes: [u8; $len]) -> Self {
+                $name(bytes)
+            }
+        }
+
+        impl TryFrom<&[u8]> for $name {
+            type Error = $crate::BitcoinError;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                let array: [u8; $len] = bytes
+                    .try_into()
+                    .map_err(|_| $crate::BitcoinError::InvalidFormat)?;
+                Ok($name(array))
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                let mut bytes = self.0;
+                if $reversed {
+                    bytes.reverse();
+                }
+                f.write_str(&::hex::encode(bytes))
+            }
+        }
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(f, "{}({})", stringify!($name), self)
+            }
+        }
+
+        impl ::std::str::FromStr for $name {
+            type Err = $crate::BitcoinError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let mut hash = $name::from_hex_internal(s)?;
+                if $reversed {
+                    hash.0.reverse();
+                }
+                Ok(hash)
+            }
+        }
+
+        // JSON keeps the internal-order hex string `Txid` has always produced; binary
+        // formats get the raw bytes as a fixed-size tuple so no length prefix is added.
+        impl ::serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: ::serde::Serializer,
+            {
+                if serializer.is_human_readable() {
+                    serializer.serialize_str(&self.to_hex_internal())
+                } else {
+                    use ::serde::ser::SerializeTuple;
+                    let mut tuple = serializer.serialize_tuple($len)?;
+                    for byte in &self.0 {
+                        tuple.serialize_element(byte)?;
+                    }
+                    tuple.end()
+                }
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: ::serde::Deserializer<'de>,
+            {
+                struct HashVisitor;
+
+                impl<'de> ::serde::de::Visitor<'de> for HashVisitor {
+                    type Value = $name;
+
+                    fn expecting(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                        write!(f, "a hex string or {} bytes", $len)
+                    }
+
+                    fn visit_str<E: ::serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                        let decoded = ::hex::decode(s).map_err(E::custom)?;
+                        self.visit_bytes(&decoded).map_err(|_: E| {
+                            E::custom(concat!("Invalid ", stringify!($name), " length"))
+                        })
+                    }
+
+                    fn visit_bytes<E: ::serde::de::Error>(
+                        self,
+                        bytes: &[u8],
+                    ) -> Result<Self::Value, E> {
+                        let array: [u8; $len] = bytes
+                            .try_into()
+                            .map_err(|_| E::invalid_length(bytes.len(), &self))?;
+                        Ok($name(array))
+                    }
+
+                    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                    where
+                        A: ::serde::de::SeqAccess<'de>,
+                    {
+                        let mut array = [0u8; $len];
+                        for (i, byte) in array.iter_mut().enumerate() {
+                            *byte = seq
+                                .next_element()?
+                                .ok_or_else(|| ::serde::de::Error::invalid_length(i, &self))?;
+                        }
+                        Ok($name(array))
+                    }
+                }
+
+                if deserializer.is_human_readable() {
+                    deserializer.deserialize_str(HashVisitor)
+                } else {
+                    deserializer.deserialize_tuple($len, HashVisitor)
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use hash_newtype;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Hash256(pub [u8; 32]);
+
+hash_newtype!(Hash256, 32, reversed_display = false);
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Hash160(pub [u8; 20]);
+
+hash_newtype!(Hash160, 20, reversed_display = false);
+
+pub fn sha256(data: &[u8]) -> Hash256 {
+    Hash256(Sha256::digest(data).into())
+}
+
+pub fn sha256d(data: &[u8]) -> Hash256 {
+    Hash256(Sha256::digest(Sha256::digest(data)).into())
+}
+
+pub fn hash160(data: &[u8]) -> Hash160 {
+    Hash160(Ripemd160::digest(Sha256::digest(data)).into())
+}
+
+fn tag_midstate(tag: &str) -> Sha256 {
+    let tag_hash = Sha256::digest(tag.as_bytes());
+    let mut engine = Sha256::new();
+    engine.update(tag_hash);
+    engine.update(tag_hash);
+    engine
+}
+
+// The two tag hashes fill exactly one SHA256 block, so a cloned engine is a cached midstate.
+fn tagged_engine(tag: &str) -> Sha256 {
+    static TAP_LEAF: OnceLock<Sha256> = OnceLock::new();
+    static TAP_BRANCH: OnceLock<Sha256> = OnceLock::new();
+    static TAP_TWEAK: OnceLock<Sha256> = OnceLock::new();
+    static TAP_SIGHASH: OnceLock<Sha256> = OnceLock::new();
+
+    let cache = match tag {
+        "TapLeaf" => &TAP_LEAF,
+        "TapBranch" => &TAP_BRANCH,
+        "TapTweak" => &TAP_TWEAK,
+        "TapSighash" => &TAP_SIGHASH,
+        _ => return tag_midstate(tag),
+    };
+    cache.get_or_init(|| tag_midstate(tag)).clone()
+}
+
+/// BIP340 tagged hash: `SHA256(SHA256(tag) || SHA256(tag) || data)`.
+pub fn tagged_hash(tag: &str, data: &[u8]) -> [u8; 32] {
+    let mut engine = tagged_engine(tag);
+    engine.update(data);
+    engine.finalize().into()
+}
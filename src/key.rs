@@ -0,0 +1,262 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::hashes::hash_newtype;
+use crate::{BitcoinError, Hash160, Network, Script, ScriptType, base58, hash160};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+enum SecKey {
+    Compressed([u8; 33]),
+    Uncompressed([u8; 65]),
+}
+
+/// A SEC-encoded public key. Only the length and prefix are checked; use the `secp256k1`
+/// feature for curve validation.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct PublicKey(SecKey);
+
+impl PublicKey {
+    /// Accepts 33-byte keys starting with 0x02/0x03 and 65-byte keys starting with 0x04.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, BitcoinError> {
+        match (bytes.first(), bytes.len()) {
+            (Some(0x02 | 0x03), 33) => Ok(PublicKey(SecKey::Compressed(
+                bytes.try_into().expect("length checked"),
+            ))),
+            (Some(0x04), 65) => Ok(PublicKey(SecKey::Uncompressed(
+                bytes.try_into().expect("length checked"),
+            ))),
+            _ => Err(BitcoinError::InvalidPublicKey),
+        }
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        matches!(self.0, SecKey::Compressed(_))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.0 {
+            SecKey::Compressed(bytes) => bytes,
+            SecKey::Uncompressed(bytes) => bytes,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    pub fn pubkey_hash(&self) -> Hash160 {
+        hash160(self.as_bytes())
+    }
+}
+
+impl AsRef<[u8]> for PublicKey {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl TryFrom<&[u8]> for PublicKey {
+    type Error = BitcoinError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        PublicKey::from_slice(bytes)
+    }
+}
+
+impl fmt::Debug for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PublicKey({})", self)
+    }
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.as_bytes()))
+    }
+}
+
+impl FromStr for PublicKey {
+    type Err = BitcoinError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|_| BitcoinError::InvalidFormat)?;
+        PublicKey::from_slice(&bytes)
+    }
+}
+
+impl Serialize for PublicKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(self.as_bytes())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct PublicKeyVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for PublicKeyVisitor {
+            type Value = PublicKey;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a hex-encoded or raw SEC public key")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                s.parse()
+                    .map_err(|_| E::custom(format!("invalid public key: {:?}", s)))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+                PublicKey::from_slice(bytes).map_err(|_| E::custom("invalid public key bytes"))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut bytes = Vec::with_capacity(65);
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    bytes.push(byte);
+                }
+                self.visit_bytes(&bytes)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(PublicKeyVisitor)
+        } else {
+            deserializer.deserialize_bytes(PublicKeyVisitor)
+        }
+    }
+}
+
+impl Script {
+    pub fn new_p2pkh_from_key(key: &PublicKey) -> Script {
+        Script::new_p2pkh(&key.pubkey_hash())
+    }
+
+    /// Fails for uncompressed keys, which segwit policy does not allow.
+    pub fn new_p2wpkh_from_key(key: &PublicKey) -> Result<Script, BitcoinError> {
+        if !key.is_compressed() {
+            return Err(BitcoinError::UncompressedKey);
+        }
+        Ok(Script::new_p2wpkh(&key.pubkey_hash()))
+    }
+}
+
+/// A BIP340 x-only public key, as committed to by taproot outputs. Only the length is
+/// checked; use the `secp256k1` feature for curve validation.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct XOnlyPublicKey(pub [u8; 32]);
+
+hash_newtype!(XOnlyPublicKey, 32, reversed_display = false);
+
+impl XOnlyPublicKey {
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, BitcoinError> {
+        bytes
+            .try_into()
+            .map(XOnlyPublicKey)
+            .map_err(|_| BitcoinError::InvalidPublicKey)
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl Script {
+    /// The output key of a P2TR scriptPubKey.
+    pub fn p2tr_output_key(&self) -> Option<XOnlyPublicKey> {
+        if self.classify() != ScriptType::P2TR {
+            return None;
+        }
+        XOnlyPublicKey::from_slice(&self[2..]).ok()
+    }
+}
+
+/// A secret key with the network and compression flag carried by its WIF encoding. Debug
+/// output leaves out the secret.
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct PrivateKey {
+    network: Network,
+    compressed: bool,
+    key: [u8; 32],
+}
+
+impl PrivateKey {
+    pub fn new(key: [u8; 32], network: Network, compressed: bool) -> Self {
+        PrivateKey {
+            network,
+            compressed,
+            key,
+        }
+    }
+
+    /// Parses Base58Check WIF. Testnet-style version bytes parse as `Network::Testnet`.
+    pub fn from_wif(s: &str) -> Result<Self, BitcoinError> {
+        let data = base58::decode_check(s)?;
+        let (&prefix, payload) = data.split_first().ok_or(BitcoinError::InvalidPrivateKey)?;
+        let network = match prefix {
+            0x80 => Network::Mainnet,
+            0xEF => Network::Testnet,
+            _ => return Err(BitcoinError::InvalidFormat),
+        };
+        let (key, compressed) = match payload {
+            [key @ .., 0x01] if key.len() == 32 => (key, true),
+            key if key.len() == 32 => (key, false),
+            _ => return Err(BitcoinError::InvalidPrivateKey),
+        };
+        Ok(PrivateKey {
+            network,
+            compressed,
+            key: key.try_into().expect("length checked"),
+        })
+    }
+
+    pub fn to_wif(&self, network: Network) -> String {
+        let mut data = Vec::with_capacity(34);
+        data.push(network.wif_prefix());
+        data.extend_from_slice(&self.key);
+        if self.compressed {
+            data.push(0x01);
+        }
+        base58::encode_check(&data)
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn compressed(&self) -> bool {
+        self.compressed
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.key
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.key
+    }
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateKey")
+            .field("network", &self.network)
+            .field("compressed", &self.compressed)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,8 +1,76 @@
+pub mod address;
+pub mod amount;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+pub mod base58;
+pub mod bech32;
+#[cfg(feature = "bitcoinconsensus")]
+mod bitcoinconsensus_impls;
+pub mod builder;
+pub mod coin_selection;
+pub mod decoder;
+pub mod fee_rate;
+pub mod hashes;
+pub mod key;
+pub mod locktime;
+pub mod opcodes;
+#[cfg(feature = "rand")]
+mod rand_impls;
+mod rpc;
+pub mod script;
+#[cfg(feature = "secp256k1")]
+mod secp256k1_impls;
+pub mod sequence;
+pub mod sighash;
+pub mod signature;
+#[cfg(feature = "secp256k1")]
+pub mod signer;
+pub mod taproot;
+pub mod utxo;
+pub mod verify;
+pub mod weight;
+pub mod witness;
+
+pub use address::{Address, AddressType, Network};
+pub use amount::{Amount, AmountDisplay, Denomination, ParseAmountError};
+#[cfg(feature = "bitcoinconsensus")]
+pub use bitcoinconsensus_impls::BitcoinConsensus;
+pub use builder::{InputBuilder, TransactionBuilder};
+pub use coin_selection::{Selection, SelectionError, SelectionStrategy, select_coins};
+pub use decoder::{InputIter, TransactionInputRef, TxDecoder};
+pub use fee_rate::FeeRate;
+pub use hashes::{Hash160, Hash256, hash160, sha256, sha256d, tagged_hash};
+pub use key::{PrivateKey, PublicKey, XOnlyPublicKey};
+pub use locktime::{
+    ChainContext, InputAge, LockTime, SequenceLockError, TimelockError, median_time_past,
+    median_time_past_from_slice,
+};
+pub use opcodes::Opcode;
+pub use script::{
+    EncodedInstruction, Instruction, Instructions, LockTimeRequirement, ScriptAsm, ScriptBuilder,
+    ScriptNum, ScriptRef, ScriptType,
+};
+pub use sequence::{RelativeLockTime, Sequence};
+pub use sighash::{SegwitV0Hashes, SighashCache, TaprootHashes};
+pub use signature::{SighashType, Signature, TapSighashType};
+#[cfg(feature = "secp256k1")]
+pub use signer::{sign_input_p2pkh, sign_input_p2wpkh};
+pub use taproot::{ControlBlock, TAPROOT_LEAF_TAPSCRIPT};
+pub use utxo::UtxoSet;
+pub use verify::{ScriptVerifier, VerifyError};
+pub use weight::Weight;
+pub use witness::Witness;
+
+use hashes::hash_newtype;
 use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::collections::HashMap;
 use std::fmt;
+use std::io;
 use std::ops::Deref;
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct CompactSize {
     pub value: u64,
 }
@@ -11,28 +79,212 @@ pub struct CompactSize {
 pub enum BitcoinError {
     InsufficientBytes,
     InvalidFormat,
+    NonMinimalCompactSize,
+    NonMinimalScriptNum,
+    Io(io::ErrorKind),
+    IntegerOverflow,
+    ValueTooLarge {
+        value: u64,
+        max: u64,
+    },
+    TrailingBytes {
+        remaining: usize,
+    },
+    ScriptTooLarge {
+        size: usize,
+    },
+    InvalidBase58Character(char),
+    InvalidChecksum,
+    UnsupportedScript,
+    InvalidBech32Character(char),
+    MixedCase,
+    InvalidWitnessVersion(u8),
+    InvalidWitnessProgramLength(usize),
+    InvalidPublicKey,
+    InvalidPrivateKey,
+    UncompressedKey,
+    InvalidSignature,
+    InvalidSighashType(u8),
+    NetworkMismatch {
+        expected: Network,
+        found: Network,
+    },
+    NoInputs,
+    InvalidCoinbaseLength(usize),
+    IndexOutOfRange {
+        index: usize,
+        len: usize,
+    },
+    InvalidAmount,
+    UnsupportedSegwitFlag(u8),
+    SuperfluousWitnessRecord,
+    MissingPrevout(OutPoint),
+    /// Input `index` spends the same outpoint as the earlier input `first`.
+    DuplicateInput {
+        index: usize,
+        first: usize,
+    },
+    NegativeFee,
+    OutputValueTooLarge {
+        index: usize,
+    },
+    OutputSumTooLarge {
+        index: usize,
+    },
+    PrevoutsLengthMismatch {
+        inputs: usize,
+        prevouts: usize,
+    },
+    SighashSingleWithoutOutput {
+        index: usize,
+    },
+    InvalidAnnex,
+    NonStandardSighashType(u32),
+    InvalidLockTime(u32),
+}
+
+/// A structural rule broken by a transaction, as found by
+/// [`BitcoinTransaction::check_sanity`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TxSanityError {
+    NoInputs,
+    NoOutputs,
+    /// The output's value exceeds `MAX_MONEY`.
+    OutputValueTooLarge {
+        index: usize,
+    },
+    /// The sum of outputs up to `index` overflows or exceeds `MAX_MONEY`.
+    OutputSumTooLarge {
+        index: usize,
+    },
+    /// The input spends the same outpoint as the earlier input `first`.
+    DuplicateInput {
+        index: usize,
+        first: usize,
+    },
+    CoinbaseScriptSize {
+        size: usize,
+    },
+    /// A non-coinbase input spends the null outpoint.
+    NullPrevout {
+        index: usize,
+    },
+}
+
+/// An input relay policy rule from Core's `IsStandardTx`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StandardnessViolation {
+    ScriptSigTooLarge {
+        size: usize,
+    },
+    ScriptSigNotPushOnly,
+    /// The scriptPubKey matches no standard output template.
+    NonStandardScript,
+    BareMultisigTooLarge {
+        keys: usize,
+    },
+    DataCarrierTooLarge {
+        size: usize,
+    },
+    Dust {
+        value: Amount,
+        threshold: Amount,
+    },
+}
+
+// Only the kind is kept so the error stays `Clone` and `Eq`.
+impl From<io::Error> for BitcoinError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => BitcoinError::InsufficientBytes,
+            kind => BitcoinError::Io(kind),
+        }
+    }
+}
+
+const SEGWIT_MARKER: u8 = 0x00;
+const SEGWIT_FLAG: u8 = 0x01;
+
+fn parse_u32_le(bytes: &[u8]) -> Result<(u32, &[u8]), BitcoinError> {
+    match bytes.split_first_chunk::<4>() {
+        Some((head, rest)) => Ok((u32::from_le_bytes(*head), rest)),
+        None => Err(BitcoinError::InsufficientBytes),
+    }
+}
+
+pub const fn compact_size_len(value: u64) -> usize {
+    if value < 253 {
+        1
+    } else if value <= 0xFFFF {
+        3
+    } else if value <= 0xFFFFFFFF {
+        5
+    } else {
+        9
+    }
 }
 
 impl CompactSize {
-    pub fn new(value: u64) -> Self {
+    pub const ZERO: CompactSize = CompactSize::new(0);
+    pub const ONE: CompactSize = CompactSize::new(1);
+
+    pub const fn new(value: u64) -> Self {
         CompactSize { value }
     }
 
-    pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        if self.value < 253 {
-            bytes.push(self.value as u8);
-        } else if self.value <= 0xFFFF {
-            bytes.push(253);
-            bytes.extend_from_slice(&self.value.to_le_bytes()[..2]);
-        } else if self.value <= 0xFFFFFFFF {
-            bytes.push(254);
-            bytes.extend_from_slice(&self.value.to_le_bytes()[..4]);
-        } else {
-            bytes.push(255);
-            bytes.extend_from_slice(&self.value.to_le_bytes());
+    /// Single-byte encoding for values below 253; panics (at compile time in const contexts) otherwise.
+    pub const fn encode_small(value: u8) -> [u8; 1] {
+        assert!(value < 253, "value needs a multi-byte CompactSize");
+        [value]
+    }
+
+    pub fn checked_new(value: u64, max: u64) -> Result<Self, BitcoinError> {
+        if value > max {
+            return Err(BitcoinError::ValueTooLarge { value, max });
         }
-        bytes
+        Ok(CompactSize { value })
+    }
+
+    pub const fn encoded_size(&self) -> usize {
+        compact_size_len(self.value)
+    }
+
+    pub fn encode_into(&self, buf: &mut [u8]) -> Result<usize, BitcoinError> {
+        let size = self.encoded_size();
+        if buf.len() < size {
+            return Err(BitcoinError::InsufficientBytes);
+        }
+
+        let le = self.value.to_le_bytes();
+        match size {
+            1 => buf[0] = self.value as u8,
+            3 => {
+                buf[0] = 253;
+                buf[1..3].copy_from_slice(&le[..2]);
+            }
+            5 => {
+                buf[0] = 254;
+                buf[1..5].copy_from_slice(&le[..4]);
+            }
+            _ => {
+                buf[0] = 255;
+                buf[1..9].copy_from_slice(&le);
+            }
+        }
+        Ok(size)
+    }
+
+    pub fn to_array(&self) -> ([u8; 9], usize) {
+        let mut array = [0u8; 9];
+        let size = self
+            .encode_into(&mut array)
+            .expect("a 9-byte buffer fits every CompactSize");
+        (array, size)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (array, size) = self.to_array();
+        array[..size].to_vec()
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
@@ -73,38 +325,264 @@ impl CompactSize {
             }
         }
     }
+
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), BitcoinError> {
+        let (compact_size, consumed) = Self::from_bytes(bytes)?;
+        Ok((compact_size, &bytes[consumed..]))
+    }
+
+    pub fn from_bytes_strict(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
+        let (compact_size, consumed) = Self::from_bytes(bytes)?;
+        if consumed != compact_size.encoded_size() {
+            return Err(BitcoinError::NonMinimalCompactSize);
+        }
+        Ok((compact_size, consumed))
+    }
+
+    pub fn from_bytes_with_max(bytes: &[u8], max: u64) -> Result<(Self, usize), BitcoinError> {
+        let (compact_size, consumed) = Self::from_bytes(bytes)?;
+        Ok((CompactSize::checked_new(compact_size.value, max)?, consumed))
+    }
+
+    pub fn read_from<R: io::Read>(r: &mut R) -> Result<Self, BitcoinError> {
+        let mut prefix = [0u8; 1];
+        r.read_exact(&mut prefix)?;
+
+        let value = match prefix[0] {
+            0..=252 => prefix[0] as u64,
+            253 => {
+                let mut buf = [0u8; 2];
+                r.read_exact(&mut buf)?;
+                u16::from_le_bytes(buf) as u64
+            }
+            254 => {
+                let mut buf = [0u8; 4];
+                r.read_exact(&mut buf)?;
+                u32::from_le_bytes(buf) as u64
+            }
+            255 => {
+                let mut buf = [0u8; 8];
+                r.read_exact(&mut buf)?;
+                u64::from_le_bytes(buf)
+            }
+        };
+        Ok(CompactSize { value })
+    }
+
+    // Fallible and borrows the iterator, so it cannot be `FromIterator`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_iter<I: Iterator<Item = u8>>(iter: &mut I) -> Result<Self, BitcoinError> {
+        let prefix = iter.next().ok_or(BitcoinError::InsufficientBytes)?;
+        let width = match prefix {
+            0..=252 => return Ok(CompactSize::new(prefix as u64)),
+            253 => 2,
+            254 => 4,
+            255 => 8,
+        };
+
+        let mut le = [0u8; 8];
+        for byte in le.iter_mut().take(width) {
+            *byte = iter.next().ok_or(BitcoinError::InsufficientBytes)?;
+        }
+        Ok(CompactSize::new(u64::from_le_bytes(le)))
+    }
+
+    pub fn write_to<W: io::Write>(&self, w: &mut W) -> Result<usize, BitcoinError> {
+        let (array, size) = self.to_array();
+        w.write_all(&array[..size])?;
+        Ok(size)
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct Txid(pub [u8; 32]);
+impl fmt::LowerHex for CompactSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (array, size) = self.to_array();
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        f.write_str(&hex::encode(&array[..size]))
+    }
+}
+
+impl fmt::Display for CompactSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+impl FromStr for CompactSize {
+    type Err = BitcoinError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|_| BitcoinError::InvalidFormat)?;
+        let (compact_size, consumed) = CompactSize::from_bytes(&bytes)?;
+        if consumed != bytes.len() {
+            return Err(BitcoinError::InvalidFormat);
+        }
+        Ok(compact_size)
+    }
+}
 
-impl Serialize for Txid {
+impl Serialize for CompactSize {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        let hex_string = hex::encode(self.0);
-        serializer.serialize_str(&hex_string)
+        if serializer.is_human_readable() {
+            serializer.serialize_u64(self.value)
+        } else {
+            let (array, size) = self.to_array();
+            serializer.serialize_bytes(&array[..size])
+        }
     }
 }
 
-impl<'de> Deserialize<'de> for Txid {
+impl<'de> Deserialize<'de> for CompactSize {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let hex_string = String::deserialize(deserializer)?;
-        let decoded = hex::decode(&hex_string).map_err(serde::de::Error::custom)?;
-        if decoded.len() != 32 {
-            return Err(serde::de::Error::custom("Invalid Txid length"));
+        struct CompactSizeVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for CompactSizeVisitor {
+            type Value = CompactSize;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an integer or a varint byte sequence")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(CompactSize::new(value))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+                match CompactSize::from_bytes(bytes) {
+                    Ok((compact_size, consumed)) if consumed == bytes.len() => Ok(compact_size),
+                    _ => Err(E::invalid_value(serde::de::Unexpected::Bytes(bytes), &self)),
+                }
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut bytes = Vec::with_capacity(9);
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    bytes.push(byte);
+                }
+                self.visit_bytes(&bytes)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_u64(CompactSizeVisitor)
+        } else {
+            deserializer.deserialize_bytes(CompactSizeVisitor)
         }
-        let mut array = [0u8; 32];
-        array.copy_from_slice(&decoded);
-        Ok(Txid(array))
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub fn write_vec<T, F>(out: &mut Vec<u8>, items: &[T], mut encode: F)
+where
+    F: FnMut(&mut Vec<u8>, &T),
+{
+    let count = CompactSize::try_from(items.len()).expect("item count fits in u64");
+    let (array, size) = count.to_array();
+    out.extend_from_slice(&array[..size]);
+    for item in items {
+        encode(out, item);
+    }
+}
+
+pub fn read_vec<T, F>(
+    bytes: &[u8],
+    max_count: u64,
+    mut decode: F,
+) -> Result<(Vec<T>, usize), BitcoinError>
+where
+    F: FnMut(&[u8]) -> Result<(T, usize), BitcoinError>,
+{
+    let (count, mut offset) = CompactSize::from_bytes_with_max(bytes, max_count)?;
+    // The count is only a hint: never reserve more slots than there are bytes left.
+    let capacity = count.value.min((bytes.len() - offset) as u64) as usize;
+    let mut items = Vec::with_capacity(capacity);
+    for _ in 0..count.value {
+        let (item, size) = decode(&bytes[offset..])?;
+        items.push(item);
+        offset += size;
+    }
+    Ok((items, offset))
+}
+
+macro_rules! impl_compact_size_from {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for CompactSize {
+                fn from(value: $t) -> Self {
+                    CompactSize::new(value as u64)
+                }
+            }
+        )*
+    };
+}
+
+impl_compact_size_from!(u8, u16, u32, u64);
+
+impl TryFrom<usize> for CompactSize {
+    type Error = BitcoinError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        u64::try_from(value)
+            .map(CompactSize::new)
+            .map_err(|_| BitcoinError::IntegerOverflow)
+    }
+}
+
+impl TryFrom<i64> for CompactSize {
+    type Error = BitcoinError;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        u64::try_from(value)
+            .map(CompactSize::new)
+            .map_err(|_| BitcoinError::IntegerOverflow)
+    }
+}
+
+impl From<CompactSize> for u64 {
+    fn from(compact_size: CompactSize) -> Self {
+        compact_size.value
+    }
+}
+
+impl TryFrom<CompactSize> for u32 {
+    type Error = BitcoinError;
+
+    fn try_from(compact_size: CompactSize) -> Result<Self, Self::Error> {
+        u32::try_from(compact_size.value).map_err(|_| BitcoinError::IntegerOverflow)
+    }
+}
+
+impl TryFrom<CompactSize> for usize {
+    type Error = BitcoinError;
+
+    fn try_from(compact_size: CompactSize) -> Result<Self, Self::Error> {
+        usize::try_from(compact_size.value).map_err(|_| BitcoinError::IntegerOverflow)
+    }
+}
+
+/// Ordering compares the internal (wire) byte order, not the reversed display order.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct Txid(pub [u8; 32]);
+
+hash_newtype!(Txid, 32, reversed_display = true);
+
+/// Witness transaction id; kept distinct from `Txid` so the two cannot be mixed up.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct Wtxid(pub [u8; 32]);
+
+hash_newtype!(Wtxid, 32, reversed_display = true);
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct OutPoint {
     pub txid: Txid,
     pub vout: u32,
@@ -118,6 +596,22 @@ impl OutPoint {
         }
     }
 
+    pub fn from_txid(txid: Txid, vout: u32) -> Self {
+        OutPoint { txid, vout }
+    }
+
+    /// The previous-output sentinel referenced by coinbase inputs.
+    pub const fn null() -> Self {
+        OutPoint {
+            txid: Txid::ZERO,
+            vout: u32::MAX,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.txid.is_zero() && self.vout == u32::MAX
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
         bytes.extend_from_slice(&self.txid.0);
@@ -125,209 +619,1262 @@ impl OutPoint {
         bytes
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
-        if bytes.len() < 36 {
-            return Err(BitcoinError::InsufficientBytes);
-        }
-
-        let mut txid = [0u8; 32];
-        txid.copy_from_slice(&bytes[0..32]);
-        let vout = u32::from_le_bytes([bytes[32], bytes[33], bytes[34], bytes[35]]);
-
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), BitcoinError> {
+        let (txid, rest) = bytes
+            .split_first_chunk::<32>()
+            .ok_or(BitcoinError::InsufficientBytes)?;
+        let (vout, rest) = parse_u32_le(rest)?;
         Ok((
             OutPoint {
-                txid: Txid(txid),
+                txid: Txid(*txid),
                 vout,
             },
-            36,
+            rest,
         ))
     }
-}
 
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
-pub struct Script {
-    pub bytes: Vec<u8>,
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
+        let (outpoint, rest) = Self::parse(bytes)?;
+        Ok((outpoint, bytes.len() - rest.len()))
+    }
 }
 
-impl Script {
-    pub fn new(bytes: Vec<u8>) -> Self {
-        Script { bytes }
+// Human-readable formats keep the `{txid, vout}` struct; binary formats get the
+// 36 consensus bytes as a fixed-size tuple.
+impl Serialize for OutPoint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            use serde::ser::SerializeStruct;
+            let mut state = serializer.serialize_struct("OutPoint", 2)?;
+            state.serialize_field("txid", &self.txid)?;
+            state.serialize_field("vout", &self.vout)?;
+            state.end()
+        } else {
+            use serde::ser::SerializeTuple;
+            let mut tuple = serializer.serialize_tuple(36)?;
+            for byte in &self.to_bytes() {
+                tuple.serialize_element(byte)?;
+            }
+            tuple.end()
+        }
     }
+}
 
-    pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        let compact_size = CompactSize::new(self.bytes.len() as u64);
-        bytes.extend_from_slice(&compact_size.to_bytes());
-        bytes.extend_from_slice(&self.bytes);
-        bytes
-    }
+impl<'de> Deserialize<'de> for OutPoint {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename = "OutPoint")]
+        struct Fields {
+            txid: Txid,
+            vout: u32,
+        }
 
-    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
-        let (compact_size, prefix_size) = CompactSize::from_bytes(bytes)?;
-        let script_length = compact_size.value as usize;
+        struct ConsensusVisitor;
 
-        if bytes.len() < prefix_size + script_length {
-            return Err(BitcoinError::InsufficientBytes);
+        impl<'de> serde::de::Visitor<'de> for ConsensusVisitor {
+            type Value = OutPoint;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("36 consensus-encoded outpoint bytes")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut bytes = [0u8; 36];
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = seq
+                        .next_element()?
+                        .ok_or_else(|| serde::de::Error::invalid_length(i, &self))?;
+                }
+                OutPoint::from_bytes_exact(&bytes)
+                    .map_err(|_| serde::de::Error::invalid_length(36, &self))
+            }
         }
 
-        let script_bytes = bytes[prefix_size..prefix_size + script_length].to_vec();
-        Ok((
-            Script {
-                bytes: script_bytes,
-            },
-            prefix_size + script_length,
-        ))
+        if deserializer.is_human_readable() {
+            let fields = Fields::deserialize(deserializer)?;
+            Ok(OutPoint::from_txid(fields.txid, fields.vout))
+        } else {
+            deserializer.deserialize_tuple(36, ConsensusVisitor)
+        }
     }
 }
 
-impl Deref for Script {
-    type Target = Vec<u8>;
-    fn deref(&self) -> &Self::Target {
-        &self.bytes
+impl fmt::Display for OutPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.txid, self.vout)
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
-pub struct TransactionInput {
-    pub previous_output: OutPoint,
-    pub script_sig: Script,
-    pub sequence: u32,
-}
+impl FromStr for OutPoint {
+    type Err = BitcoinError;
 
-impl TransactionInput {
-    pub fn new(previous_output: OutPoint, script_sig: Script, sequence: u32) -> Self {
-        TransactionInput {
-            previous_output,
-            script_sig,
-            sequence,
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (txid, vout) = s.split_once(':').ok_or(BitcoinError::InvalidFormat)?;
+        if txid.len() != 64 {
+            return Err(BitcoinError::InvalidFormat);
         }
+        let vout = vout.parse().map_err(|_| BitcoinError::InvalidFormat)?;
+        Ok(OutPoint::from_txid(txid.parse()?, vout))
+    }
+}
+
+/// A raw script. Derefs to `[u8]`, so slice methods work directly:
+///
+/// ```
+/// use rust_week_3_exercises::Script;
+///
+/// let script = Script::new(vec![0x76, 0xa9, 0x14]);
+/// assert_eq!(script.len(), 3);
+/// assert_eq!(script.first(), Some(&0x76));
+/// assert!(script.starts_with(&[0x76, 0xa9]));
+/// assert_eq!(&script[1..], &[0xa9, 0x14]);
+/// ```
+#[derive(PartialEq, Eq, Clone)]
+pub struct Script {
+    bytes: Vec<u8>,
+}
+
+impl Script {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Script { bytes }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self, BitcoinError> {
+        s.parse()
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.bytes)
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.previous_output.to_bytes());
-        bytes.extend_from_slice(&self.script_sig.to_bytes());
-        bytes.extend_from_slice(&self.sequence.to_le_bytes());
+        let compact_size =
+            CompactSize::try_from(self.bytes.len()).expect("script length fits in u64");
+        let mut bytes = Vec::with_capacity(compact_size.encoded_size() + self.bytes.len());
+        bytes.extend_from_slice(&compact_size.to_bytes());
+        bytes.extend_from_slice(&self.bytes);
         bytes
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
-        let (previous_output, outpoint_size) = OutPoint::from_bytes(bytes)?;
-        let (script_sig, script_size) = Script::from_bytes(&bytes[outpoint_size..])?;
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), BitcoinError> {
+        let (script, rest) = ScriptRef::parse(bytes)?;
+        Ok((script.to_script(), rest))
+    }
 
-        let sequence_start = outpoint_size + script_size;
-        if bytes.len() < sequence_start + 4 {
-            return Err(BitcoinError::InsufficientBytes);
-        }
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
+        let (script, rest) = Self::parse(bytes)?;
+        Ok((script, bytes.len() - rest.len()))
+    }
+}
 
-        let sequence = u32::from_le_bytes([
-            bytes[sequence_start],
-            bytes[sequence_start + 1],
-            bytes[sequence_start + 2],
-            bytes[sequence_start + 3],
-        ]);
+impl fmt::Debug for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Script({})", self.to_hex())
+    }
+}
 
-        Ok((
-            TransactionInput {
-                previous_output,
-                script_sig,
-                sequence,
-            },
-            sequence_start + 4,
-        ))
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
-pub struct BitcoinTransaction {
-    pub version: u32,
-    pub inputs: Vec<TransactionInput>,
-    pub lock_time: u32,
+impl fmt::LowerHex for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        f.write_str(&hex::encode(&self.bytes))
+    }
 }
 
-impl BitcoinTransaction {
-    pub fn new(version: u32, inputs: Vec<TransactionInput>, lock_time: u32) -> Self {
-        BitcoinTransaction {
-            version,
-            inputs,
-            lock_time,
+impl fmt::UpperHex for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
         }
+        f.write_str(&hex::encode_upper(&self.bytes))
     }
+}
 
-    pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.version.to_le_bytes());
+impl FromStr for Script {
+    type Err = BitcoinError;
 
-        let input_count = CompactSize::new(self.inputs.len() as u64);
-        bytes.extend_from_slice(&input_count.to_bytes());
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        hex::decode(s)
+            .map(Script::new)
+            .map_err(|_| BitcoinError::InvalidFormat)
+    }
+}
 
-        for input in &self.inputs {
-            bytes.extend_from_slice(&input.to_bytes());
+// Hex string for human-readable formats, the consensus `to_bytes` encoding otherwise.
+impl Serialize for Script {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_hex())
+        } else {
+            serializer.serialize_bytes(&self.to_bytes())
         }
+    }
+}
 
-        bytes.extend_from_slice(&self.lock_time.to_le_bytes());
+impl<'de> Deserialize<'de> for Script {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct ScriptVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ScriptVisitor {
+            type Value = Script;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a hex-encoded script or length-prefixed script bytes")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                Script::from_hex(s).map_err(|_| E::custom(format!("invalid script hex: {:?}", s)))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+                Script::from_bytes_exact(bytes)
+                    .map_err(|_| E::custom("script length prefix does not match the encoded bytes"))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut bytes = Vec::new();
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    bytes.push(byte);
+                }
+                self.visit_bytes(&bytes)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(ScriptVisitor)
+        } else {
+            deserializer.deserialize_bytes(ScriptVisitor)
+        }
+    }
+}
+
+impl Deref for Script {
+    type Target = [u8];
+    fn deref(&self) -> &Self::Target {
+        &self.bytes
+    }
+}
+
+impl AsRef<[u8]> for Script {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Borrow<[u8]> for Script {
+    fn borrow(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Spend templates with a known worst-case signed size, for
+/// [`TransactionInput::estimated_weight_for`].
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum InputType {
+    P2pkh,
+    P2wpkh,
+    P2shP2wpkh,
+    P2wshMultisig { required: usize, total: usize },
+    P2trKeySpend,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TransactionInput {
+    pub previous_output: OutPoint,
+    pub script_sig: Script,
+    pub sequence: Sequence,
+    /// Not part of the legacy input encoding; see [`Witness`].
+    pub witness: Witness,
+}
+
+impl TransactionInput {
+    pub const MIN_SIZE: usize = 36 + 1 + 4;
+
+    pub fn new(previous_output: OutPoint, script_sig: Script, sequence: u32) -> Self {
+        TransactionInput {
+            previous_output,
+            script_sig,
+            sequence: Sequence(sequence),
+            witness: Witness::default(),
+        }
+    }
+
+    /// Worst-case ECDSA signature with its sighash byte, as pushed by standard signers.
+    const MAX_ECDSA_SIG_SIZE: usize = 72;
+    const COMPRESSED_PUBKEY_SIZE: usize = 33;
+    const SCHNORR_SIG_SIZE: usize = 64;
+
+    pub fn has_witness(&self) -> bool {
+        !self.witness.is_empty()
+    }
+
+    /// Non-witness bytes: outpoint, scriptSig with its length, and sequence.
+    pub fn serialized_size(&self) -> usize {
+        36 + compact_size_len(self.script_sig.len() as u64) + self.script_sig.len() + 4
+    }
+
+    /// Non-witness bytes at 4 WU each plus the witness at 1 WU per byte. An empty witness
+    /// counts nothing here, though a segwit transaction still spends a byte encoding it.
+    pub fn weight(&self) -> usize {
+        let witness_size = if self.has_witness() {
+            self.witness.total_size()
+        } else {
+            0
+        };
+        self.serialized_size() * 4 + witness_size
+    }
+
+    /// Weight of a fully signed input of the given type, assuming 72-byte ECDSA signatures
+    /// (71-byte DER plus sighash byte), compressed keys and default-sighash Schnorr
+    /// signatures.
+    pub fn estimated_weight_for(input_type: InputType) -> usize {
+        let ecdsa_push = 1 + Self::MAX_ECDSA_SIG_SIZE;
+        let pubkey_push = 1 + Self::COMPRESSED_PUBKEY_SIZE;
+        // Outpoint and sequence at 4 WU per byte, plus the given scriptSig length.
+        let base = |script_sig_len: usize| {
+            (36 + compact_size_len(script_sig_len as u64) + script_sig_len + 4) * 4
+        };
+        match input_type {
+            InputType::P2pkh => base(ecdsa_push + pubkey_push),
+            InputType::P2wpkh => base(0) + 1 + ecdsa_push + pubkey_push,
+            // The scriptSig pushes the 22-byte v0 witness program.
+            InputType::P2shP2wpkh => base(23) + 1 + ecdsa_push + pubkey_push,
+            InputType::P2wshMultisig { required, total } => {
+                // OP_m <keys> OP_n OP_CHECKMULTISIG
+                let script_len = 3 + total * pubkey_push;
+                // The CHECKMULTISIG dummy element, the signatures and the witness script.
+                let elements = required + 2;
+                base(0)
+                    + compact_size_len(elements as u64)
+                    + 1
+                    + required * ecdsa_push
+                    + compact_size_len(script_len as u64)
+                    + script_len
+            }
+            InputType::P2trKeySpend => base(0) + 1 + 1 + Self::SCHNORR_SIG_SIZE,
+        }
+    }
+
+    /// Consensus bounds on the scriptSig length of a coinbase input.
+    pub const MIN_COINBASE_SCRIPT_SIZE: usize = 2;
+    pub const MAX_COINBASE_SCRIPT_SIZE: usize = 100;
+
+    /// A coinbase input whose scriptSig starts with the BIP34 height, followed by `extra`
+    /// as raw script bytes. The height is pushed the way Core's `CScript() << height` does
+    /// and BIP34 checks: `OP_1`..`OP_16` for heights 1 to 16, a minimal ScriptNum push
+    /// otherwise. Fails with [`BitcoinError::InvalidCoinbaseLength`] unless the scriptSig
+    /// ends up 2 to 100 bytes long.
+    pub fn new_coinbase(height: u32, extra: &[u8]) -> Result<Self, BitcoinError> {
+        let mut bytes = ScriptBuilder::new()
+            .push_int(height as i64)
+            .into_script()
+            .bytes;
+        bytes.extend_from_slice(extra);
+        let size = bytes.len();
+        if !(Self::MIN_COINBASE_SCRIPT_SIZE..=Self::MAX_COINBASE_SCRIPT_SIZE).contains(&size) {
+            return Err(BitcoinError::InvalidCoinbaseLength(size));
+        }
+        Ok(TransactionInput::new(
+            OutPoint::null(),
+            Script::new(bytes),
+            Sequence::MAX.0,
+        ))
+    }
+
+    /// The BIP34 height at the start of a coinbase scriptSig. Small heights are read from
+    /// `OP_0`..`OP_16` as well as from a one-byte push; non-minimal numbers and inputs that
+    /// do not spend the null outpoint give `None`.
+    pub fn coinbase_height(&self) -> Option<u32> {
+        if !self.previous_output.is_null() {
+            return None;
+        }
+        let height = match self.script_sig.instructions().next()?.ok()? {
+            Instruction::Op(opcodes::OP_0) => 0,
+            Instruction::Op(op) if op >= opcodes::OP_1 && op <= opcodes::OP_16 => {
+                (op.to_u8() - opcodes::OP_1.to_u8() + 1) as i64
+            }
+            Instruction::Op(_) => return None,
+            // Every u32 fits in a 5-byte ScriptNum.
+            Instruction::PushBytes(data) => ScriptNum::decode(data, 5).ok()?.value(),
+        };
+        u32::try_from(height).ok()
+    }
+
+    /// Whether this input spends P2SH-P2WPKH or P2SH-P2WSH. The scriptSig alone cannot tell,
+    /// so the caller supplies the scriptPubKey of the output being spent.
+    pub fn is_nested_segwit(&self, prev_script_pubkey: &Script) -> bool {
+        if !prev_script_pubkey.is_p2sh() {
+            return false;
+        }
+        self.script_sig
+            .redeem_script()
+            .is_some_and(|redeem_script| {
+                hash160(&redeem_script).as_bytes()[..] == prev_script_pubkey[2..22]
+                    && redeem_script.witness_version() == Some(0)
+            })
+    }
+
+    /// The address that owned the spent output, judged by its scriptPubKey since the
+    /// scriptSig does not identify it. P2PK and other non-address scripts give `None`.
+    pub fn input_address(&self, prev_script: &Script, network: Network) -> Option<Address> {
+        Address::from_script(prev_script, network).ok()
+    }
+
+    /// Core's `MAX_STANDARD_SCRIPTSIG_SIZE`, enough for a 15-of-15 P2SH multisig spend.
+    pub const MAX_STANDARD_SCRIPT_SIG_SIZE: usize = 1650;
+
+    /// The scriptSig policy rules: at most 1650 bytes, and push-only.
+    pub fn is_standard(&self) -> Result<(), StandardnessViolation> {
+        let size = self.script_sig.len();
+        if size > Self::MAX_STANDARD_SCRIPT_SIG_SIZE {
+            return Err(StandardnessViolation::ScriptSigTooLarge { size });
+        }
+        if !self.script_sig.is_push_only() {
+            return Err(StandardnessViolation::ScriptSigNotPushOnly);
+        }
+        Ok(())
+    }
+
+    pub fn has_push_only_script_sig(&self) -> bool {
+        self.script_sig.is_push_only()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.previous_output.to_bytes());
+        bytes.extend_from_slice(&self.script_sig.to_bytes());
+        bytes.extend_from_slice(&self.sequence.to_le_bytes());
         bytes
     }
 
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), BitcoinError> {
+        let (input, rest) = TransactionInputRef::parse(bytes)?;
+        Ok((input.to_input(), rest))
+    }
+
     pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
-        if bytes.len() < 4 {
-            return Err(BitcoinError::InsufficientBytes);
+        let (input, rest) = Self::parse(bytes)?;
+        Ok((input, bytes.len() - rest.len()))
+    }
+}
+
+// Human-readable formats get Core's `getrawtransaction` input shape, with `txid` in display
+// order as Core prints it; `txinwitness` is left out when empty.
+// Binary formats get the consensus input bytes, followed by the witness if there is one.
+impl Serialize for TransactionInput {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            use serde::ser::SerializeStruct;
+
+            #[derive(Serialize)]
+            struct ScriptSig<'a> {
+                hex: &'a Script,
+            }
+
+            let fields = if self.witness.is_empty() { 4 } else { 5 };
+            let mut state = serializer.serialize_struct("TransactionInput", fields)?;
+            state.serialize_field("txid", &self.previous_output.txid.to_string())?;
+            state.serialize_field("vout", &self.previous_output.vout)?;
+            state.serialize_field(
+                "scriptSig",
+                &ScriptSig {
+                    hex: &self.script_sig,
+                },
+            )?;
+            state.serialize_field("sequence", &self.sequence)?;
+            if !self.witness.is_empty() {
+                state.serialize_field("txinwitness", &self.witness)?;
+            }
+            state.end()
+        } else {
+            let mut bytes = self.to_bytes();
+            if !self.witness.is_empty() {
+                bytes.extend_from_slice(&self.witness.to_bytes());
+            }
+            serializer.serialize_bytes(&bytes)
         }
+    }
+}
 
-        let version = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
-        let (input_count, input_count_size) = CompactSize::from_bytes(&bytes[4..])?;
-        let mut inputs = Vec::new();
-        let mut offset = 4 + input_count_size;
+impl<'de> Deserialize<'de> for TransactionInput {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct ScriptSig {
+            hex: Script,
+        }
 
-        for _ in 0..input_count.value {
-            let (input, input_size) = TransactionInput::from_bytes(&bytes[offset..])?;
-            inputs.push(input);
-            offset += input_size;
+        #[derive(Deserialize)]
+        #[serde(rename = "TransactionInput")]
+        struct Fields {
+            txid: String,
+            vout: u32,
+            #[serde(rename = "scriptSig")]
+            script_sig: ScriptSig,
+            sequence: Sequence,
+            #[serde(default, rename = "txinwitness")]
+            witness: Witness,
         }
 
-        if bytes.len() < offset + 4 {
-            return Err(BitcoinError::InsufficientBytes);
+        struct ConsensusVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ConsensusVisitor {
+            type Value = TransactionInput;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("consensus-encoded input bytes")
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+                let invalid = |_| E::custom("invalid consensus-encoded input");
+                let (mut input, rest) = TransactionInput::parse(bytes).map_err(invalid)?;
+                if !rest.is_empty() {
+                    input.witness = Witness::from_bytes_exact(rest).map_err(invalid)?;
+                }
+                Ok(input)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut bytes = Vec::new();
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    bytes.push(byte);
+                }
+                self.visit_bytes(&bytes)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            let fields = Fields::deserialize(deserializer)?;
+            let txid = fields
+                .txid
+                .parse::<Txid>()
+                .map_err(|_| serde::de::Error::custom("invalid txid"))?;
+            Ok(TransactionInput {
+                previous_output: OutPoint::from_txid(txid, fields.vout),
+                script_sig: fields.script_sig.hex,
+                sequence: fields.sequence,
+                witness: fields.witness,
+            })
+        } else {
+            deserializer.deserialize_bytes(ConsensusVisitor)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct TransactionOutput {
+    pub value: Amount,
+    #[serde(rename = "scriptPubKey")]
+    pub script_pubkey: Script,
+}
+
+impl TransactionOutput {
+    pub const MIN_SIZE: usize = 8 + 1;
+    /// Core's default `-datacarriersize`: the whole OP_RETURN script, not just its data.
+    pub const MAX_STANDARD_OP_RETURN_SIZE: usize = Script::MAX_OP_RETURN_DATA + 3;
+    pub const MAX_STANDARD_BARE_MULTISIG_KEYS: usize = 3;
+
+    pub fn new(value: Amount, script_pubkey: Script) -> Self {
+        TransactionOutput {
+            value,
+            script_pubkey,
+        }
+    }
+
+    /// The value, then the scriptPubKey with its length.
+    pub fn serialized_size(&self) -> usize {
+        8 + compact_size_len(self.script_pubkey.len() as u64) + self.script_pubkey.len()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.serialized_size());
+        bytes.extend_from_slice(&self.value.to_sat().to_le_bytes());
+        bytes.extend_from_slice(&self.script_pubkey.to_bytes());
+        bytes
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), BitcoinError> {
+        let (value, rest) = bytes
+            .split_first_chunk::<8>()
+            .ok_or(BitcoinError::InsufficientBytes)?;
+        let (script_pubkey, rest) = Script::parse(rest)?;
+        let output = TransactionOutput {
+            value: Amount::from_sat(u64::from_le_bytes(*value)),
+            script_pubkey,
+        };
+        Ok((output, rest))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
+        let (output, rest) = Self::parse(bytes)?;
+        Ok((output, bytes.len() - rest.len()))
+    }
+
+    /// OP_RETURN outputs and scripts over [`Script::MAX_SCRIPT_SIZE`] can never be spent.
+    pub fn is_unspendable(&self) -> bool {
+        is_unspendable(&self.script_pubkey)
+    }
+
+    /// Core's `IsDust`: the value is below what spending the output would cost at
+    /// `fee_rate`. Unspendable outputs are never dust.
+    pub fn is_dust(&self, fee_rate: FeeRate) -> bool {
+        self.value < Self::min_non_dust(&self.script_pubkey, fee_rate)
+    }
+
+    /// Core's `GetDustThreshold`: the fee for this output plus a typical input spending it,
+    /// 148 bytes, or 67 vbytes for a witness program. Zero for unspendable scripts.
+    pub fn min_non_dust(script_pubkey: &Script, fee_rate: FeeRate) -> Amount {
+        if is_unspendable(script_pubkey) {
+            return Amount::ZERO;
+        }
+        let output_size = 8 + compact_size_len(script_pubkey.len() as u64) + script_pubkey.len();
+        let spend_size = if script_pubkey.is_witness_program() {
+            32 + 4 + 1 + 107 / 4 + 4
+        } else {
+            32 + 4 + 1 + 107 + 4
+        };
+        // A rate too high to compute makes every spendable output dust.
+        fee_rate
+            .fee_for_vsize((output_size + spend_size) as u64)
+            .unwrap_or(Amount::MAX_MONEY)
+    }
+
+    /// The output policy rules of Core's `IsStandardTx`: a standard script template, bare
+    /// multisig with at most 3 keys, an OP_RETURN script of at most 83 bytes, and no dust at
+    /// [`FeeRate::DUST_RATE`].
+    pub fn is_standard(&self) -> Result<(), StandardnessViolation> {
+        match self.script_pubkey.classify() {
+            ScriptType::NonStandard => return Err(StandardnessViolation::NonStandardScript),
+            ScriptType::Multisig => {
+                let (_, keys) = self
+                    .script_pubkey
+                    .parse_multisig()
+                    .expect("classified as multisig");
+                if keys.len() > Self::MAX_STANDARD_BARE_MULTISIG_KEYS {
+                    return Err(StandardnessViolation::BareMultisigTooLarge { keys: keys.len() });
+                }
+            }
+            ScriptType::OpReturn => {
+                let size = self.script_pubkey.len();
+                if size > Self::MAX_STANDARD_OP_RETURN_SIZE {
+                    return Err(StandardnessViolation::DataCarrierTooLarge { size });
+                }
+            }
+            _ => {}
         }
+        let threshold = Self::min_non_dust(&self.script_pubkey, FeeRate::DUST_RATE);
+        if self.value < threshold {
+            return Err(StandardnessViolation::Dust {
+                value: self.value,
+                threshold,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn is_unspendable(script_pubkey: &Script) -> bool {
+    script_pubkey.is_op_return() || !script_pubkey.is_within_consensus_size()
+}
+
+impl fmt::Display for TransactionOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} to {}", self.value, self.script_pubkey)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct BitcoinTransaction {
+    pub version: u32,
+    pub inputs: Vec<TransactionInput>,
+    pub outputs: Vec<TransactionOutput>,
+    pub lock_time: u32,
+}
+
+impl BitcoinTransaction {
+    /// A transaction without outputs; see [`BitcoinTransaction::new_with_outputs`].
+    pub fn new(version: u32, inputs: Vec<TransactionInput>, lock_time: u32) -> Self {
+        Self::new_with_outputs(version, inputs, Vec::new(), lock_time)
+    }
+
+    pub fn new_with_outputs(
+        version: u32,
+        inputs: Vec<TransactionInput>,
+        outputs: Vec<TransactionOutput>,
+        lock_time: u32,
+    ) -> Self {
+        BitcoinTransaction {
+            version,
+            inputs,
+            outputs,
+            lock_time,
+        }
+    }
+
+    /// The BIP144 serialization when any input has a witness, the legacy one otherwise.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        if !self.has_witness() {
+            return self.to_bytes_legacy();
+        }
+        let legacy = self.to_bytes_legacy();
+        let (body, lock_time) = legacy.split_at(legacy.len() - 4);
+
+        let mut bytes = Vec::with_capacity(self.total_size());
+        bytes.extend_from_slice(&body[..4]);
+        bytes.extend_from_slice(&[SEGWIT_MARKER, SEGWIT_FLAG]);
+        bytes.extend_from_slice(&body[4..]);
+        for input in &self.inputs {
+            bytes.extend_from_slice(&input.witness.to_bytes());
+        }
+        bytes.extend_from_slice(lock_time);
+        bytes
+    }
+
+    /// Version, inputs, outputs and lock time, without witnesses: the form hashed for the
+    /// txid.
+    pub fn to_bytes_legacy(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.base_size());
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        write_vec(&mut bytes, &self.inputs, |out, input| {
+            out.extend_from_slice(&input.to_bytes())
+        });
+        write_vec(&mut bytes, &self.outputs, |out, output| {
+            out.extend_from_slice(&output.to_bytes())
+        });
+        bytes.extend_from_slice(&self.lock_time.to_le_bytes());
+        bytes
+    }
+
+    /// Length of [`BitcoinTransaction::to_bytes_legacy`], summed per component.
+    pub fn base_size(&self) -> usize {
+        let input_count =
+            CompactSize::try_from(self.inputs.len()).expect("input count fits in u64");
+        let output_count =
+            CompactSize::try_from(self.outputs.len()).expect("output count fits in u64");
+        let inputs_size: usize = self
+            .inputs
+            .iter()
+            .map(TransactionInput::serialized_size)
+            .sum();
+        let outputs_size: usize = self
+            .outputs
+            .iter()
+            .map(TransactionOutput::serialized_size)
+            .sum();
+        4 + input_count.encoded_size()
+            + inputs_size
+            + output_count.encoded_size()
+            + outputs_size
+            + 4
+    }
 
-        let lock_time = u32::from_le_bytes([
-            bytes[offset],
-            bytes[offset + 1],
-            bytes[offset + 2],
-            bytes[offset + 3],
-        ]);
+    /// Length of [`BitcoinTransaction::to_bytes`]: the base size, plus the marker, flag
+    /// and every witness (empty ones included) when any input has a witness.
+    pub fn total_size(&self) -> usize {
+        if !self.has_witness() {
+            return self.base_size();
+        }
+        let witness_size: usize = self
+            .inputs
+            .iter()
+            .map(|input| input.witness.total_size())
+            .sum();
+        self.base_size() + 2 + witness_size
+    }
+
+    /// BIP141 weight: the base size at 3 WU per byte, plus the total size.
+    pub fn weight(&self) -> Weight {
+        Weight::from_wu((self.base_size() * 3 + self.total_size()) as u64)
+    }
+
+    pub fn vsize(&self) -> u64 {
+        self.weight().to_vbytes_ceil()
+    }
+
+    /// Input value minus output value, with each spent output found by `prevout`. A
+    /// coinbase has no prevouts, so this fails with `MissingPrevout` for it.
+    pub fn fee<'a, F>(&self, prevout: F) -> Result<Amount, BitcoinError>
+    where
+        F: Fn(&OutPoint) -> Option<&'a TransactionOutput>,
+    {
+        let mut input_value = Amount::ZERO;
+        for input in &self.inputs {
+            let spent = prevout(&input.previous_output)
+                .ok_or_else(|| BitcoinError::MissingPrevout(input.previous_output.clone()))?;
+            input_value = input_value.checked_add(spent.value)?;
+        }
+        let output_value = self.total_output_value()?;
+        if output_value > input_value {
+            return Err(BitcoinError::NegativeFee);
+        }
+        input_value.checked_sub(output_value)
+    }
+
+    /// The sum of output values. Errors name the first output where the sum overflows a
+    /// u64, failing that the first above `MAX_MONEY`, then the first where the running sum
+    /// passes it.
+    pub fn total_output_value(&self) -> Result<Amount, BitcoinError> {
+        let mut raw_sum = 0u64;
+        for (index, output) in self.outputs.iter().enumerate() {
+            raw_sum = raw_sum
+                .checked_add(output.value.to_sat())
+                .ok_or(BitcoinError::OutputSumTooLarge { index })?;
+        }
+
+        let mut sum = Amount::ZERO;
+        for (index, output) in self.outputs.iter().enumerate() {
+            if !output.value.is_valid() {
+                return Err(BitcoinError::OutputValueTooLarge { index });
+            }
+            sum = sum
+                .checked_add(output.value)
+                .map_err(|_| BitcoinError::OutputSumTooLarge { index })?;
+        }
+        Ok(sum)
+    }
+
+    /// [`BitcoinTransaction::fee`] per weight, rounded down so the rate is never
+    /// overstated.
+    pub fn fee_rate<'a, F>(&self, prevout: F) -> Result<FeeRate, BitcoinError>
+    where
+        F: Fn(&OutPoint) -> Option<&'a TransactionOutput>,
+    {
+        let fee = self.fee(prevout)?;
+        // A fee within MAX_MONEY times 1000 fits in a u64.
+        Ok(FeeRate::from_sat_per_kwu(
+            fee.to_sat() * 1000 / self.weight().to_wu(),
+        ))
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        self.inputs.len() == 1 && self.inputs[0].previous_output.is_null()
+    }
+
+    pub fn input_mut(&mut self, index: usize) -> Option<&mut TransactionInput> {
+        self.inputs.get_mut(index)
+    }
+
+    fn checked_input_mut(&mut self, index: usize) -> Result<&mut TransactionInput, BitcoinError> {
+        let len = self.inputs.len();
+        self.inputs
+            .get_mut(index)
+            .ok_or(BitcoinError::IndexOutOfRange { index, len })
+    }
+
+    pub fn replace_script_sig(
+        &mut self,
+        index: usize,
+        script_sig: Script,
+    ) -> Result<(), BitcoinError> {
+        self.checked_input_mut(index)?.script_sig = script_sig;
+        Ok(())
+    }
+
+    /// Empties every scriptSig, as done before computing a legacy sighash.
+    pub fn clear_script_sigs(&mut self) {
+        for input in &mut self.inputs {
+            input.script_sig = Script::new(vec![]);
+        }
+    }
+
+    pub fn remove_input(&mut self, index: usize) -> Result<TransactionInput, BitcoinError> {
+        let len = self.inputs.len();
+        if index >= len {
+            return Err(BitcoinError::IndexOutOfRange { index, len });
+        }
+        Ok(self.inputs.remove(index))
+    }
+
+    pub fn push_input(&mut self, input: TransactionInput) {
+        self.inputs.push(input);
+    }
+
+    pub fn push_output(&mut self, output: TransactionOutput) {
+        self.outputs.push(output);
+    }
+
+    /// The address paid by each output, or `None` for OP_RETURN, P2PK and other scripts
+    /// without one.
+    pub fn output_addresses(&self, network: Network) -> Vec<Option<Address>> {
+        self.outputs
+            .iter()
+            .map(|output| Address::from_script(&output.script_pubkey, network).ok())
+            .collect()
+    }
+
+    /// Outputs that look like change, given the scriptPubKeys of the spent outputs in any
+    /// order. Starting from every spendable output, each heuristic that singles out some
+    /// but not all remaining candidates narrows them, in this order:
+    ///
+    /// 1. the output pays a script one of the inputs spent from (address reuse);
+    /// 2. all inputs share a script type, and the output has it too;
+    /// 3. the amount is not a multiple of 10,000 sat, while payments tend to be round.
+    ///
+    /// Empty when no heuristic discriminates, as for coinbases and transactions with fewer
+    /// than two spendable outputs. A guess, not a fact about the transaction.
+    pub fn likely_change_outputs(&self, prevout_scripts: &[Script]) -> Vec<usize> {
+        if self.is_coinbase() {
+            return Vec::new();
+        }
+        let mut candidates: Vec<usize> = (0..self.outputs.len())
+            .filter(|&index| !self.outputs[index].is_unspendable())
+            .collect();
+        if candidates.len() < 2 {
+            return Vec::new();
+        }
+
+        let input_type = prevout_scripts.first().map(Script::classify);
+        let shared_input_type =
+            input_type.filter(|&t| prevout_scripts.iter().all(|script| script.classify() == t));
+        let heuristics: [&dyn Fn(&TransactionOutput) -> bool; 3] = [
+            &|output| prevout_scripts.contains(&output.script_pubkey),
+            &|output| Some(output.script_pubkey.classify()) == shared_input_type,
+            &|output| !output.value.to_sat().is_multiple_of(10_000),
+        ];
+        let mut narrowed = false;
+        for heuristic in heuristics {
+            let matching: Vec<usize> = candidates
+                .iter()
+                .copied()
+                .filter(|&index| heuristic(&self.outputs[index]))
+                .collect();
+            if !matching.is_empty() && matching.len() < candidates.len() {
+                candidates = matching;
+                narrowed = true;
+            }
+        }
+        if narrowed { candidates } else { Vec::new() }
+    }
+
+    /// The structural checks of Core's `CheckTransaction`: at least one input and one
+    /// output, output values within `MAX_MONEY`, no outpoint spent twice, a 2..=100 byte coinbase scriptSig, and no null
+    /// prevouts outside a coinbase. Not full validation.
+    pub fn check_sanity(&self) -> Result<(), TxSanityError> {
+        if self.inputs.is_empty() {
+            return Err(TxSanityError::NoInputs);
+        }
+        if self.outputs.is_empty() {
+            return Err(TxSanityError::NoOutputs);
+        }
+        match self.total_output_value() {
+            Err(BitcoinError::OutputValueTooLarge { index }) => {
+                return Err(TxSanityError::OutputValueTooLarge { index });
+            }
+            Err(BitcoinError::OutputSumTooLarge { index }) => {
+                return Err(TxSanityError::OutputSumTooLarge { index });
+            }
+            _ => {}
+        }
+
+        let mut seen = HashMap::with_capacity(self.inputs.len());
+        for (index, input) in self.inputs.iter().enumerate() {
+            if let Some(&first) = seen.get(&input.previous_output) {
+                return Err(TxSanityError::DuplicateInput { index, first });
+            }
+            seen.insert(&input.previous_output, index);
+        }
+
+        if self.is_coinbase() {
+            let size = self.inputs[0].script_sig.len();
+            let allowed = TransactionInput::MIN_COINBASE_SCRIPT_SIZE
+                ..=TransactionInput::MAX_COINBASE_SCRIPT_SIZE;
+            if !allowed.contains(&size) {
+                return Err(TxSanityError::CoinbaseScriptSize { size });
+            }
+        } else if let Some(index) = self
+            .inputs
+            .iter()
+            .position(|input| input.previous_output.is_null())
+        {
+            return Err(TxSanityError::NullPrevout { index });
+        }
+        Ok(())
+    }
+
+    /// Every input failing [`TransactionInput::is_standard`], with its index.
+    pub fn check_input_standardness(&self) -> Result<(), Vec<(usize, StandardnessViolation)>> {
+        let violations: Vec<_> = self
+            .inputs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, input)| input.is_standard().err().map(|v| (index, v)))
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Every output failing [`TransactionOutput::is_standard`], with its index.
+    pub fn check_output_standardness(&self) -> Result<(), Vec<(usize, StandardnessViolation)>> {
+        let violations: Vec<_> = self
+            .outputs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, output)| output.is_standard().err().map(|v| (index, v)))
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// The input and output policy rules together. Weight and sigop limits are separate:
+    /// see [`BitcoinTransaction::weight`] and [`BitcoinTransaction::legacy_sigop_count`].
+    pub fn is_standard(&self) -> bool {
+        self.check_input_standardness().is_ok() && self.check_output_standardness().is_ok()
+    }
+
+    /// Whether any input carries witness data, so the segwit serialization is required.
+    pub fn has_witness(&self) -> bool {
+        self.inputs.iter().any(TransactionInput::has_witness)
+    }
+
+    /// BIP125 opt-in: any input with a sequence below `0xFFFFFFFE`.
+    pub fn signals_rbf(&self) -> bool {
+        self.inputs.iter().any(|input| input.sequence.is_rbf())
+    }
+
+    /// Makes the transaction signal BIP125 by setting each non-signaling sequence
+    /// (`0xFFFFFFFE` or `MAX`) to `ENABLE_RBF_NO_LOCKTIME`. Those two values have the BIP68
+    /// disable flag set, so no relative lock changes; lower sequences already signal and are
+    /// left exactly as they are. Note that moving off `MAX` also enables the lock time.
+    pub fn opt_in_rbf(&mut self) {
+        for input in &mut self.inputs {
+            if !input.sequence.is_rbf() {
+                input.sequence = Sequence::ENABLE_RBF_NO_LOCKTIME;
+            }
+        }
+    }
+
+    /// Orders inputs and outputs as BIP69 specifies. This changes the sighash of every
+    /// input, so sort before signing: existing signatures are invalidated.
+    pub fn sort_bip69(&mut self) {
+        self.inputs.sort_by_key(bip69_input_key);
+        self.outputs.sort_by(bip69_output_cmp);
+    }
+
+    pub fn is_bip69_sorted(&self) -> bool {
+        self.inputs.is_sorted_by_key(bip69_input_key)
+            && self
+                .outputs
+                .is_sorted_by(|a, b| bip69_output_cmp(a, b).is_le())
+    }
+
+    pub fn all_script_sigs_push_only(&self) -> bool {
+        self.inputs
+            .iter()
+            .all(TransactionInput::has_push_only_script_sig)
+    }
+
+    /// Inaccurate sigop count over every scriptSig and scriptPubKey, as used by Core's
+    /// legacy sigop limit.
+    pub fn legacy_sigop_count(&self) -> usize {
+        self.inputs
+            .iter()
+            .map(|input| input.script_sig.count_sigops(false))
+            .sum::<usize>()
+            + self
+                .outputs
+                .iter()
+                .map(|output| output.script_pubkey.count_sigops(false))
+                .sum::<usize>()
+    }
+
+    pub fn txid(&self) -> Txid {
+        Txid(sha256d(&self.to_bytes_legacy()).0)
+    }
+
+    /// Equal to the txid when no input has a witness.
+    pub fn wtxid(&self) -> Wtxid {
+        Wtxid(sha256d(&self.to_bytes()).0)
+    }
+
+    /// Reads either serialization. As in Core, a zero input count followed by a nonzero
+    /// byte is taken as the segwit marker and flag, so a legacy transaction with no inputs
+    /// but some outputs cannot be decoded.
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), BitcoinError> {
+        let (version, rest) = parse_u32_le(bytes)?;
+        let (segwit, rest) = match rest {
+            [SEGWIT_MARKER, SEGWIT_FLAG, rest @ ..] => (true, rest),
+            [SEGWIT_MARKER, flag, ..] if *flag != 0 => {
+                return Err(BitcoinError::UnsupportedSegwitFlag(*flag));
+            }
+            _ => (false, rest),
+        };
+        // Every input takes at least 41 bytes, so larger counts cannot fit in the buffer.
+        let max_inputs = rest.len() as u64 / TransactionInput::MIN_SIZE as u64;
+        let (mut inputs, consumed) = read_vec(rest, max_inputs, TransactionInput::from_bytes)?;
+        let rest = &rest[consumed..];
+        // Likewise 9 bytes per output.
+        let max_outputs = rest.len() as u64 / TransactionOutput::MIN_SIZE as u64;
+        let (outputs, consumed) = read_vec(rest, max_outputs, TransactionOutput::from_bytes)?;
+        let mut rest = &rest[consumed..];
+
+        if segwit {
+            for input in &mut inputs {
+                let (witness, remaining) = Witness::parse(rest)?;
+                input.witness = witness;
+                rest = remaining;
+            }
+            // Core rejects the extended format when it carries nothing.
+            if !inputs.iter().any(TransactionInput::has_witness) {
+                return Err(BitcoinError::SuperfluousWitnessRecord);
+            }
+        }
+        let (lock_time, rest) = parse_u32_le(rest)?;
 
         Ok((
             BitcoinTransaction {
                 version,
                 inputs,
+                outputs,
                 lock_time,
             },
-            offset + 4,
+            rest,
         ))
     }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
+        let (tx, rest) = Self::parse(bytes)?;
+        Ok((tx, bytes.len() - rest.len()))
+    }
+}
+
+// BIP69 compares txids "in reversed byte-order": the display order, not the serialized
+// one the derived `Ord` on `OutPoint` uses.
+fn bip69_input_key(input: &TransactionInput) -> ([u8; 32], u32) {
+    let mut txid = input.previous_output.txid.0;
+    txid.reverse();
+    (txid, input.previous_output.vout)
 }
 
+fn bip69_output_cmp(a: &TransactionOutput, b: &TransactionOutput) -> std::cmp::Ordering {
+    a.value
+        .cmp(&b.value)
+        .then_with(|| a.script_pubkey.as_bytes().cmp(b.script_pubkey.as_bytes()))
+}
+
+macro_rules! impl_exact_decoding {
+    ($($t:ty),*) => {
+        $(
+            impl $t {
+                pub fn from_bytes_exact(bytes: &[u8]) -> Result<Self, BitcoinError> {
+                    let (value, consumed) = Self::from_bytes(bytes)?;
+                    if consumed != bytes.len() {
+                        return Err(BitcoinError::TrailingBytes {
+                            remaining: bytes.len() - consumed,
+                        });
+                    }
+                    Ok(value)
+                }
+            }
+
+            impl TryFrom<&[u8]> for $t {
+                type Error = BitcoinError;
+
+                fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                    Self::from_bytes_exact(bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_exact_decoding!(
+    CompactSize,
+    OutPoint,
+    Script,
+    Witness,
+    TransactionInput,
+    TransactionOutput,
+    BitcoinTransaction
+);
+
 impl fmt::Display for BitcoinTransaction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "BitcoinTransaction:")?;
         writeln!(f, "  Version: {}", self.version)?;
-        writeln!(f, "  Lock Time: {}", self.lock_time)?;
+        writeln!(f, "  Lock Time: {}", self.lock_time_description())?;
         writeln!(f, "  Inputs:")?;
         for (i, input) in self.inputs.iter().enumerate() {
             writeln!(f, "    Input {}:", i + 1)?;
             writeln!(
                 f,
                 "      Previous Output: Txid: {}, Vout: {}",
-                hex::encode(input.previous_output.txid.0),
-                input.previous_output.vout
+                input.previous_output.txid, input.previous_output.vout
             )?;
             writeln!(
                 f,
-                "      ScriptSig ({} bytes): {:?}",
-                input.script_sig.len(),
+                "      ScriptSig ({} bytes): {}",
+                input.script_sig.as_bytes().len(),
                 input.script_sig
             )?;
-            writeln!(f, "      Sequence: {}", input.sequence)?;
+            writeln!(
+                f,
+                "      Sequence: {}",
+                input.sequence.description(self.version)
+            )?;
+        }
+        writeln!(f, "  Outputs:")?;
+        for (i, output) in self.outputs.iter().enumerate() {
+            let sats = output.value.to_sat();
+            writeln!(f, "    Output {}:", i + 1)?;
+            writeln!(
+                f,
+                "      Value: {}.{:08} BTC",
+                sats / Amount::ONE_BTC.to_sat(),
+                sats % Amount::ONE_BTC.to_sat()
+            )?;
+            writeln!(
+                f,
+                "      ScriptPubKey ({} bytes): {}",
+                output.script_pubkey.len(),
+                output.script_pubkey
+            )?;
         }
         Ok(())
     }
@@ -0,0 +1,336 @@
+use std::cmp::Ordering;
+
+use crate::{BitcoinError, BitcoinTransaction, RelativeLockTime};
+
+/// A transaction's `nLockTime`: a block height below [`LockTime::THRESHOLD`], a UNIX
+/// timestamp at or above it.
+///
+/// Heights and times don't compare with each other, so there is no `Ord`; see
+/// [`LockTime::partial_cmp_same_unit`].
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum LockTime {
+    Blocks(u32),
+    Seconds(u32),
+}
+
+impl LockTime {
+    pub const THRESHOLD: u32 = 500_000_000;
+    pub const ZERO: LockTime = LockTime::Blocks(0);
+
+    pub fn from_consensus(value: u32) -> Self {
+        if value < LockTime::THRESHOLD {
+            LockTime::Blocks(value)
+        } else {
+            LockTime::Seconds(value)
+        }
+    }
+
+    pub fn from_height(height: u32) -> Result<Self, BitcoinError> {
+        if height >= LockTime::THRESHOLD {
+            return Err(BitcoinError::InvalidLockTime(height));
+        }
+        Ok(LockTime::Blocks(height))
+    }
+
+    pub fn from_time(time: u32) -> Result<Self, BitcoinError> {
+        if time < LockTime::THRESHOLD {
+            return Err(BitcoinError::InvalidLockTime(time));
+        }
+        Ok(LockTime::Seconds(time))
+    }
+
+    pub fn to_consensus_u32(self) -> u32 {
+        match self {
+            LockTime::Blocks(value) | LockTime::Seconds(value) => value,
+        }
+    }
+
+    pub fn is_block_height(&self) -> bool {
+        matches!(self, LockTime::Blocks(_))
+    }
+
+    pub fn is_same_unit(&self, other: &LockTime) -> bool {
+        self.is_block_height() == other.is_block_height()
+    }
+
+    /// Orders two lock times of the same unit, `None` for a height and a time.
+    pub fn partial_cmp_same_unit(&self, other: &LockTime) -> Option<Ordering> {
+        self.is_same_unit(other)
+            .then(|| self.to_consensus_u32().cmp(&other.to_consensus_u32()))
+    }
+
+    /// Whether a chain whose tip is at `height` with `median_time_past` has reached this
+    /// lock, so that the transaction could go in the next block.
+    pub fn is_satisfied_by(&self, height: u32, median_time_past: u32) -> bool {
+        match *self {
+            LockTime::Blocks(lock) => lock <= height,
+            LockTime::Seconds(lock) => lock <= median_time_past,
+        }
+    }
+}
+
+impl Default for LockTime {
+    fn default() -> Self {
+        LockTime::ZERO
+    }
+}
+
+impl From<LockTime> for u32 {
+    fn from(lock_time: LockTime) -> Self {
+        lock_time.to_consensus_u32()
+    }
+}
+
+/// The chain a transaction would be mined on top of: the tip's height and its
+/// median-time-past.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct ChainContext {
+    pub height: u32,
+    pub median_time_past: u32,
+}
+
+impl ChainContext {
+    pub fn new(height: u32, median_time_past: u32) -> Self {
+        ChainContext {
+            height,
+            median_time_past,
+        }
+    }
+}
+
+/// The median of the timestamps of the last 11 blocks, in any order.
+pub fn median_time_past(timestamps: &[u32; 11]) -> u32 {
+    median_time_past_from_slice(timestamps).expect("11 timestamps")
+}
+
+/// [`median_time_past`] for a chain that may have fewer than 11 blocks: the upper median of
+/// the last (up to) 11 `timestamps`, as Core computes it. `None` if there are none.
+pub fn median_time_past_from_slice(timestamps: &[u32]) -> Option<u32> {
+    let mut last = timestamps[timestamps.len().saturating_sub(11)..].to_vec();
+    last.sort_unstable();
+    last.get(last.len() / 2).copied()
+}
+
+/// How long an input's prevout will have been confirmed when the spending transaction is
+/// mined: the difference in block height, and the difference in median-time-past from the
+/// block before the one confirming the prevout (BIP68).
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct InputAge {
+    pub blocks: u32,
+    pub seconds: u32,
+}
+
+impl InputAge {
+    pub fn new(blocks: u32, seconds: u32) -> Self {
+        InputAge { blocks, seconds }
+    }
+
+    /// The age of a prevout whose block was mined on top of `confirmed_on`, for a spend
+    /// mined on top of `ctx`.
+    pub fn between(confirmed_on: &ChainContext, ctx: &ChainContext) -> Self {
+        InputAge {
+            blocks: ctx.height.saturating_sub(confirmed_on.height),
+            seconds: ctx
+                .median_time_past
+                .saturating_sub(confirmed_on.median_time_past),
+        }
+    }
+}
+
+/// Why [`BitcoinTransaction::check_sequence_locks`] rejected a transaction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SequenceLockError {
+    InputAgesMismatch {
+        inputs: usize,
+        ages: usize,
+    },
+    /// Input `index` needs its prevout to be `blocks` blocks older.
+    BlocksRemaining {
+        index: usize,
+        blocks: u32,
+    },
+    /// Input `index` needs its prevout to be `seconds` seconds older.
+    SecondsRemaining {
+        index: usize,
+        seconds: u32,
+    },
+}
+
+/// Why [`BitcoinTransaction::is_valid_at`] would not let a transaction into the next block,
+/// with the earliest block height, or tip median-time-past, at which that lock clears.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TimelockError {
+    InputAgesMismatch {
+        inputs: usize,
+        ages: usize,
+    },
+    AbsoluteHeight {
+        earliest_height: u32,
+    },
+    AbsoluteTime {
+        earliest_median_time_past: u32,
+    },
+    RelativeHeight {
+        index: usize,
+        earliest_height: u32,
+    },
+    RelativeTime {
+        index: usize,
+        earliest_median_time_past: u32,
+    },
+}
+
+impl BitcoinTransaction {
+    pub fn lock_time(&self) -> LockTime {
+        LockTime::from_consensus(self.lock_time)
+    }
+
+    pub fn set_lock_time(&mut self, lock_time: LockTime) {
+        self.lock_time = lock_time.to_consensus_u32();
+    }
+
+    /// The lock time with its meaning, such as `850000 (block height)` or
+    /// `1718000000 (UNIX time ≈ 2024-06-10T06:13:20Z)`.
+    pub fn lock_time_description(&self) -> String {
+        match self.lock_time() {
+            LockTime::Blocks(0) => "0 (none)".to_string(),
+            LockTime::Blocks(height) => format!("{height} (block height)"),
+            LockTime::Seconds(time) => format!("{time} (UNIX time ≈ {})", format_utc(time)),
+        }
+    }
+
+    /// Core's `IsFinalTx` for inclusion in a block at `block_height` with `block_time`: the
+    /// lock time must be zero or lie strictly before the block, unless every input's
+    /// sequence is final.
+    pub fn is_final(&self, block_height: u32, block_time: u32) -> bool {
+        let reached = match self.lock_time() {
+            LockTime::Blocks(0) => return true,
+            LockTime::Blocks(lock) => lock < block_height,
+            LockTime::Seconds(lock) => lock < block_time,
+        };
+        reached || self.inputs.iter().all(|input| input.sequence.is_final())
+    }
+
+    /// [`BitcoinTransaction::is_final`] for the block after `ctx`'s tip, comparing time
+    /// locks against the median-time-past as BIP113 requires.
+    pub fn is_final_at(&self, ctx: &ChainContext) -> bool {
+        self.is_final(ctx.height.saturating_add(1), ctx.median_time_past)
+    }
+
+    /// Checks each input's BIP68 relative lock against the age of its prevout, given in
+    /// input order. With `version_check`, transactions before version 2 have no relative
+    /// locks, as consensus requires; without it, the sequences are read regardless.
+    pub fn check_sequence_locks(
+        &self,
+        version_check: bool,
+        input_ages: &[InputAge],
+    ) -> Result<(), SequenceLockError> {
+        if input_ages.len() != self.inputs.len() {
+            return Err(SequenceLockError::InputAgesMismatch {
+                inputs: self.inputs.len(),
+                ages: input_ages.len(),
+            });
+        }
+        if version_check && self.version < 2 {
+            return Ok(());
+        }
+        for (index, (input, age)) in self.inputs.iter().zip(input_ages).enumerate() {
+            match input.sequence.relative_lock_time() {
+                Some(RelativeLockTime::Blocks(blocks)) if age.blocks < u32::from(blocks) => {
+                    return Err(SequenceLockError::BlocksRemaining {
+                        index,
+                        blocks: u32::from(blocks) - age.blocks,
+                    });
+                }
+                Some(RelativeLockTime::Time(units)) if age.seconds < u32::from(units) << 9 => {
+                    return Err(SequenceLockError::SecondsRemaining {
+                        index,
+                        seconds: (u32::from(units) << 9) - age.seconds,
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// [`BitcoinTransaction::check_sequence_locks`] for a spend mined on top of `ctx`, given
+    /// the chain each prevout's block was mined on top of; see [`InputAge::between`].
+    pub fn check_sequence_locks_at(
+        &self,
+        ctx: &ChainContext,
+        version_check: bool,
+        confirmed_on: &[ChainContext],
+    ) -> Result<(), SequenceLockError> {
+        let ages: Vec<InputAge> = confirmed_on
+            .iter()
+            .map(|confirmed_on| InputAge::between(confirmed_on, ctx))
+            .collect();
+        self.check_sequence_locks(version_check, &ages)
+    }
+
+    /// Whether the transaction could be mined in the block after `ctx`'s tip: its lock time
+    /// is checked as [`BitcoinTransaction::is_final_at`] does (BIP113), and its BIP68 locks
+    /// against `prevout_ages`, measured to that block.
+    pub fn is_valid_at(
+        &self,
+        ctx: &ChainContext,
+        prevout_ages: &[InputAge],
+    ) -> Result<(), TimelockError> {
+        if !self.is_final_at(ctx) {
+            return Err(match self.lock_time() {
+                LockTime::Blocks(lock) => TimelockError::AbsoluteHeight {
+                    earliest_height: lock.saturating_add(1),
+                },
+                LockTime::Seconds(lock) => TimelockError::AbsoluteTime {
+                    earliest_median_time_past: lock.saturating_add(1),
+                },
+            });
+        }
+        self.check_sequence_locks(true, prevout_ages)
+            .map_err(|error| match error {
+                SequenceLockError::InputAgesMismatch { inputs, ages } => {
+                    TimelockError::InputAgesMismatch { inputs, ages }
+                }
+                SequenceLockError::BlocksRemaining { index, blocks } => {
+                    TimelockError::RelativeHeight {
+                        index,
+                        earliest_height: ctx.height.saturating_add(1).saturating_add(blocks),
+                    }
+                }
+                SequenceLockError::SecondsRemaining { index, seconds } => {
+                    TimelockError::RelativeTime {
+                        index,
+                        earliest_median_time_past: ctx.median_time_past.saturating_add(seconds),
+                    }
+                }
+            })
+    }
+}
+
+/// `seconds` since the UNIX epoch as an ISO 8601 UTC timestamp.
+fn format_utc(seconds: u32) -> String {
+    let days = i64::from(seconds / 86_400);
+    let time = seconds % 86_400;
+    // Howard Hinnant's civil_from_days, with eras of 400 years starting on March 1st.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3_600,
+        time / 60 % 60,
+        time % 60
+    )
+}
@@ -0,0 +1,453 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::BitcoinError;
+
+/// A single script opcode byte.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Opcode(u8);
+
+impl Opcode {
+    pub const fn from_u8(byte: u8) -> Self {
+        Opcode(byte)
+    }
+
+    pub const fn to_u8(self) -> u8 {
+        self.0
+    }
+
+    /// The opcode's name, as in Bitcoin Core's `GetOpName` with two exceptions: direct
+    /// pushes 0x01..=0x4b are `OP_PUSHBYTES_N`, where Core has no name, and `OP_0`,
+    /// `OP_1NEGATE` and `OP_1`..`OP_16` keep their names where Core prints the number.
+    /// Undefined opcodes are `OP_UNKNOWN`.
+    pub fn name(self) -> &'static str {
+        match self.0 {
+            0x00 => "OP_0",
+            0x01 => "OP_PUSHBYTES_1",
+            0x02 => "OP_PUSHBYTES_2",
+            0x03 => "OP_PUSHBYTES_3",
+            0x04 => "OP_PUSHBYTES_4",
+            0x05 => "OP_PUSHBYTES_5",
+            0x06 => "OP_PUSHBYTES_6",
+            0x07 => "OP_PUSHBYTES_7",
+            0x08 => "OP_PUSHBYTES_8",
+            0x09 => "OP_PUSHBYTES_9",
+            0x0a => "OP_PUSHBYTES_10",
+            0x0b => "OP_PUSHBYTES_11",
+            0x0c => "OP_PUSHBYTES_12",
+            0x0d => "OP_PUSHBYTES_13",
+            0x0e => "OP_PUSHBYTES_14",
+            0x0f => "OP_PUSHBYTES_15",
+            0x10 => "OP_PUSHBYTES_16",
+            0x11 => "OP_PUSHBYTES_17",
+            0x12 => "OP_PUSHBYTES_18",
+            0x13 => "OP_PUSHBYTES_19",
+            0x14 => "OP_PUSHBYTES_20",
+            0x15 => "OP_PUSHBYTES_21",
+            0x16 => "OP_PUSHBYTES_22",
+            0x17 => "OP_PUSHBYTES_23",
+            0x18 => "OP_PUSHBYTES_24",
+            0x19 => "OP_PUSHBYTES_25",
+            0x1a => "OP_PUSHBYTES_26",
+            0x1b => "OP_PUSHBYTES_27",
+            0x1c => "OP_PUSHBYTES_28",
+            0x1d => "OP_PUSHBYTES_29",
+            0x1e => "OP_PUSHBYTES_30",
+            0x1f => "OP_PUSHBYTES_31",
+            0x20 => "OP_PUSHBYTES_32",
+            0x21 => "OP_PUSHBYTES_33",
+            0x22 => "OP_PUSHBYTES_34",
+            0x23 => "OP_PUSHBYTES_35",
+            0x24 => "OP_PUSHBYTES_36",
+            0x25 => "OP_PUSHBYTES_37",
+            0x26 => "OP_PUSHBYTES_38",
+            0x27 => "OP_PUSHBYTES_39",
+            0x28 => "OP_PUSHBYTES_40",
+            0x29 => "OP_PUSHBYTES_41",
+            0x2a => "OP_PUSHBYTES_42",
+            0x2b => "OP_PUSHBYTES_43",
+            0x2c => "OP_PUSHBYTES_44",
+            0x2d => "OP_PUSHBYTES_45",
+            0x2e => "OP_PUSHBYTES_46",
+            0x2f => "OP_PUSHBYTES_47",
+            0x30 => "OP_PUSHBYTES_48",
+            0x31 => "OP_PUSHBYTES_49",
+            0x32 => "OP_PUSHBYTES_50",
+            0x33 => "OP_PUSHBYTES_51",
+            0x34 => "OP_PUSHBYTES_52",
+            0x35 => "OP_PUSHBYTES_53",
+            0x36 => "OP_PUSHBYTES_54",
+            0x37 => "OP_PUSHBYTES_55",
+            0x38 => "OP_PUSHBYTES_56",
+            0x39 => "OP_PUSHBYTES_57",
+            0x3a => "OP_PUSHBYTES_58",
+            0x3b => "OP_PUSHBYTES_59",
+            0x3c => "OP_PUSHBYTES_60",
+            0x3d => "OP_PUSHBYTES_61",
+            0x3e => "OP_PUSHBYTES_62",
+            0x3f => "OP_PUSHBYTES_63",
+            0x40 => "OP_PUSHBYTES_64",
+            0x41 => "OP_PUSHBYTES_65",
+            0x42 => "OP_PUSHBYTES_66",
+            0x43 => "OP_PUSHBYTES_67",
+            0x44 => "OP_PUSHBYTES_68",
+            0x45 => "OP_PUSHBYTES_69",
+            0x46 => "OP_PUSHBYTES_70",
+            0x47 => "OP_PUSHBYTES_71",
+            0x48 => "OP_PUSHBYTES_72",
+            0x49 => "OP_PUSHBYTES_73",
+            0x4a => "OP_PUSHBYTES_74",
+            0x4b => "OP_PUSHBYTES_75",
+            0x4c => "OP_PUSHDATA1",
+            0x4d => "OP_PUSHDATA2",
+            0x4e => "OP_PUSHDATA4",
+            0x4f => "OP_1NEGATE",
+            0x50 => "OP_RESERVED",
+            0x51 => "OP_1",
+            0x52 => "OP_2",
+            0x53 => "OP_3",
+            0x54 => "OP_4",
+            0x55 => "OP_5",
+            0x56 => "OP_6",
+            0x57 => "OP_7",
+            0x58 => "OP_8",
+            0x59 => "OP_9",
+            0x5a => "OP_10",
+            0x5b => "OP_11",
+            0x5c => "OP_12",
+            0x5d => "OP_13",
+            0x5e => "OP_14",
+            0x5f => "OP_15",
+            0x60 => "OP_16",
+            0x61 => "OP_NOP",
+            0x62 => "OP_VER",
+            0x63 => "OP_IF",
+            0x64 => "OP_NOTIF",
+            0x65 => "OP_VERIF",
+            0x66 => "OP_VERNOTIF",
+            0x67 => "OP_ELSE",
+            0x68 => "OP_ENDIF",
+            0x69 => "OP_VERIFY",
+            0x6a => "OP_RETURN",
+            0x6b => "OP_TOALTSTACK",
+            0x6c => "OP_FROMALTSTACK",
+            0x6d => "OP_2DROP",
+            0x6e => "OP_2DUP",
+            0x6f => "OP_3DUP",
+            0x70 => "OP_2OVER",
+            0x71 => "OP_2ROT",
+            0x72 => "OP_2SWAP",
+            0x73 => "OP_IFDUP",
+            0x74 => "OP_DEPTH",
+            0x75 => "OP_DROP",
+            0x76 => "OP_DUP",
+            0x77 => "OP_NIP",
+            0x78 => "OP_OVER",
+            0x79 => "OP_PICK",
+            0x7a => "OP_ROLL",
+            0x7b => "OP_ROT",
+            0x7c => "OP_SWAP",
+            0x7d => "OP_TUCK",
+            0x7e => "OP_CAT",
+            0x7f => "OP_SUBSTR",
+            0x80 => "OP_LEFT",
+            0x81 => "OP_RIGHT",
+            0x82 => "OP_SIZE",
+            0x83 => "OP_INVERT",
+            0x84 => "OP_AND",
+            0x85 => "OP_OR",
+            0x86 => "OP_XOR",
+            0x87 => "OP_EQUAL",
+            0x88 => "OP_EQUALVERIFY",
+            0x89 => "OP_RESERVED1",
+            0x8a => "OP_RESERVED2",
+            0x8b => "OP_1ADD",
+            0x8c => "OP_1SUB",
+            0x8d => "OP_2MUL",
+            0x8e => "OP_2DIV",
+            0x8f => "OP_NEGATE",
+            0x90 => "OP_ABS",
+            0x91 => "OP_NOT",
+            0x92 => "OP_0NOTEQUAL",
+            0x93 => "OP_ADD",
+            0x94 => "OP_SUB",
+            0x95 => "OP_MUL",
+            0x96 => "OP_DIV",
+            0x97 => "OP_MOD",
+            0x98 => "OP_LSHIFT",
+            0x99 => "OP_RSHIFT",
+            0x9a => "OP_BOOLAND",
+            0x9b => "OP_BOOLOR",
+            0x9c => "OP_NUMEQUAL",
+            0x9d => "OP_NUMEQUALVERIFY",
+            0x9e => "OP_NUMNOTEQUAL",
+            0x9f => "OP_LESSTHAN",
+            0xa0 => "OP_GREATERTHAN",
+            0xa1 => "OP_LESSTHANOREQUAL",
+            0xa2 => "OP_GREATERTHANOREQUAL",
+            0xa3 => "OP_MIN",
+            0xa4 => "OP_MAX",
+            0xa5 => "OP_WITHIN",
+            0xa6 => "OP_RIPEMD160",
+            0xa7 => "OP_SHA1",
+            0xa8 => "OP_SHA256",
+            0xa9 => "OP_HASH160",
+            0xaa => "OP_HASH256",
+            0xab => "OP_CODESEPARATOR",
+            0xac => "OP_CHECKSIG",
+            0xad => "OP_CHECKSIGVERIFY",
+            0xae => "OP_CHECKMULTISIG",
+            0xaf => "OP_CHECKMULTISIGVERIFY",
+            0xb0 => "OP_NOP1",
+            0xb1 => "OP_CHECKLOCKTIMEVERIFY",
+            0xb2 => "OP_CHECKSEQUENCEVERIFY",
+            0xb3 => "OP_NOP4",
+            0xb4 => "OP_NOP5",
+            0xb5 => "OP_NOP6",
+            0xb6 => "OP_NOP7",
+            0xb7 => "OP_NOP8",
+            0xb8 => "OP_NOP9",
+            0xb9 => "OP_NOP10",
+            0xba => "OP_CHECKSIGADD",
+            0xff => "OP_INVALIDOPCODE",
+            _ => "OP_UNKNOWN",
+        }
+    }
+}
+
+impl From<u8> for Opcode {
+    fn from(byte: u8) -> Self {
+        Opcode(byte)
+    }
+}
+
+impl From<Opcode> for u8 {
+    fn from(opcode: Opcode) -> Self {
+        opcode.0
+    }
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Accepts the names produced by `Display` plus the aliases defined below (`OP_TRUE`,
+/// `OP_CSV`, ...). `OP_UNKNOWN` is ambiguous and rejected.
+impl FromStr for Opcode {
+    type Err = BitcoinError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "OP_FALSE" => return Ok(OP_FALSE),
+            "OP_TRUE" => return Ok(OP_TRUE),
+            "OP_NOP2" => return Ok(OP_NOP2),
+            "OP_NOP3" => return Ok(OP_NOP3),
+            "OP_CLTV" => return Ok(OP_CLTV),
+            "OP_CSV" => return Ok(OP_CSV),
+            "OP_UNKNOWN" => return Err(BitcoinError::InvalidFormat),
+            _ => {}
+        }
+        (0..=u8::MAX)
+            .map(Opcode)
+            .find(|opcode| opcode.name() == s)
+            .ok_or(BitcoinError::InvalidFormat)
+    }
+}
+
+pub const OP_0: Opcode = Opcode(0x00);
+pub const OP_PUSHBYTES_1: Opcode = Opcode(0x01);
+pub const OP_PUSHBYTES_2: Opcode = Opcode(0x02);
+pub const OP_PUSHBYTES_3: Opcode = Opcode(0x03);
+pub const OP_PUSHBYTES_4: Opcode = Opcode(0x04);
+pub const OP_PUSHBYTES_5: Opcode = Opcode(0x05);
+pub const OP_PUSHBYTES_6: Opcode = Opcode(0x06);
+pub const OP_PUSHBYTES_7: Opcode = Opcode(0x07);
+pub const OP_PUSHBYTES_8: Opcode = Opcode(0x08);
+pub const OP_PUSHBYTES_9: Opcode = Opcode(0x09);
+pub const OP_PUSHBYTES_10: Opcode = Opcode(0x0a);
+pub const OP_PUSHBYTES_11: Opcode = Opcode(0x0b);
+pub const OP_PUSHBYTES_12: Opcode = Opcode(0x0c);
+pub const OP_PUSHBYTES_13: Opcode = Opcode(0x0d);
+pub const OP_PUSHBYTES_14: Opcode = Opcode(0x0e);
+pub const OP_PUSHBYTES_15: Opcode = Opcode(0x0f);
+pub const OP_PUSHBYTES_16: Opcode = Opcode(0x10);
+pub const OP_PUSHBYTES_17: Opcode = Opcode(0x11);
+pub const OP_PUSHBYTES_18: Opcode = Opcode(0x12);
+pub const OP_PUSHBYTES_19: Opcode = Opcode(0x13);
+pub const OP_PUSHBYTES_20: Opcode = Opcode(0x14);
+pub const OP_PUSHBYTES_21: Opcode = Opcode(0x15);
+pub const OP_PUSHBYTES_22: Opcode = Opcode(0x16);
+pub const OP_PUSHBYTES_23: Opcode = Opcode(0x17);
+pub const OP_PUSHBYTES_24: Opcode = Opcode(0x18);
+pub const OP_PUSHBYTES_25: Opcode = Opcode(0x19);
+pub const OP_PUSHBYTES_26: Opcode = Opcode(0x1a);
+pub const OP_PUSHBYTES_27: Opcode = Opcode(0x1b);
+pub const OP_PUSHBYTES_28: Opcode = Opcode(0x1c);
+pub const OP_PUSHBYTES_29: Opcode = Opcode(0x1d);
+pub const OP_PUSHBYTES_30: Opcode = Opcode(0x1e);
+pub const OP_PUSHBYTES_31: Opcode = Opcode(0x1f);
+pub const OP_PUSHBYTES_32: Opcode = Opcode(0x20);
+pub const OP_PUSHBYTES_33: Opcode = Opcode(0x21);
+pub const OP_PUSHBYTES_34: Opcode = Opcode(0x22);
+pub const OP_PUSHBYTES_35: Opcode = Opcode(0x23);
+pub const OP_PUSHBYTES_36: Opcode = Opcode(0x24);
+pub const OP_PUSHBYTES_37: Opcode = Opcode(0x25);
+pub const OP_PUSHBYTES_38: Opcode = Opcode(0x26);
+pub const OP_PUSHBYTES_39: Opcode = Opcode(0x27);
+pub const OP_PUSHBYTES_40: Opcode = Opcode(0x28);
+pub const OP_PUSHBYTES_41: Opcode = Opcode(0x29);
+pub const OP_PUSHBYTES_42: Opcode = Opcode(0x2a);
+pub const OP_PUSHBYTES_43: Opcode = Opcode(0x2b);
+pub const OP_PUSHBYTES_44: Opcode = Opcode(0x2c);
+pub const OP_PUSHBYTES_45: Opcode = Opcode(0x2d);
+pub const OP_PUSHBYTES_46: Opcode = Opcode(0x2e);
+pub const OP_PUSHBYTES_47: Opcode = Opcode(0x2f);
+pub const OP_PUSHBYTES_48: Opcode = Opcode(0x30);
+pub const OP_PUSHBYTES_49: Opcode = Opcode(0x31);
+pub const OP_PUSHBYTES_50: Opcode = Opcode(0x32);
+pub const OP_PUSHBYTES_51: Opcode = Opcode(0x33);
+pub const OP_PUSHBYTES_52: Opcode = Opcode(0x34);
+pub const OP_PUSHBYTES_53: Opcode = Opcode(0x35);
+pub const OP_PUSHBYTES_54: Opcode = Opcode(0x36);
+pub const OP_PUSHBYTES_55: Opcode = Opcode(0x37);
+pub const OP_PUSHBYTES_56: Opcode = Opcode(0x38);
+pub const OP_PUSHBYTES_57: Opcode = Opcode(0x39);
+pub const OP_PUSHBYTES_58: Opcode = Opcode(0x3a);
+pub const OP_PUSHBYTES_59: Opcode = Opcode(0x3b);
+pub const OP_PUSHBYTES_60: Opcode = Opcode(0x3c);
+pub const OP_PUSHBYTES_61: Opcode = Opcode(0x3d);
+pub const OP_PUSHBYTES_62: Opcode = Opcode(0x3e);
+pub const OP_PUSHBYTES_63: Opcode = Opcode(0x3f);
+pub const OP_PUSHBYTES_64: Opcode = Opcode(0x40);
+pub const OP_PUSHBYTES_65: Opcode = Opcode(0x41);
+pub const OP_PUSHBYTES_66: Opcode = Opcode(0x42);
+pub const OP_PUSHBYTES_67: Opcode = Opcode(0x43);
+pub const OP_PUSHBYTES_68: Opcode = Opcode(0x44);
+pub const OP_PUSHBYTES_69: Opcode = Opcode(0x45);
+pub const OP_PUSHBYTES_70: Opcode = Opcode(0x46);
+pub const OP_PUSHBYTES_71: Opcode = Opcode(0x47);
+pub const OP_PUSHBYTES_72: Opcode = Opcode(0x48);
+pub const OP_PUSHBYTES_73: Opcode = Opcode(0x49);
+pub const OP_PUSHBYTES_74: Opcode = Opcode(0x4a);
+pub const OP_PUSHBYTES_75: Opcode = Opcode(0x4b);
+pub const OP_PUSHDATA1: Opcode = Opcode(0x4c);
+pub const OP_PUSHDATA2: Opcode = Opcode(0x4d);
+pub const OP_PUSHDATA4: Opcode = Opcode(0x4e);
+pub const OP_1NEGATE: Opcode = Opcode(0x4f);
+pub const OP_RESERVED: Opcode = Opcode(0x50);
+pub const OP_1: Opcode = Opcode(0x51);
+pub const OP_2: Opcode = Opcode(0x52);
+pub const OP_3: Opcode = Opcode(0x53);
+pub const OP_4: Opcode = Opcode(0x54);
+pub const OP_5: Opcode = Opcode(0x55);
+pub const OP_6: Opcode = Opcode(0x56);
+pub const OP_7: Opcode = Opcode(0x57);
+pub const OP_8: Opcode = Opcode(0x58);
+pub const OP_9: Opcode = Opcode(0x59);
+pub const OP_10: Opcode = Opcode(0x5a);
+pub const OP_11: Opcode = Opcode(0x5b);
+pub const OP_12: Opcode = Opcode(0x5c);
+pub const OP_13: Opcode = Opcode(0x5d);
+pub const OP_14: Opcode = Opcode(0x5e);
+pub const OP_15: Opcode = Opcode(0x5f);
+pub const OP_16: Opcode = Opcode(0x60);
+pub const OP_NOP: Opcode = Opcode(0x61);
+pub const OP_VER: Opcode = Opcode(0x62);
+pub const OP_IF: Opcode = Opcode(0x63);
+pub const OP_NOTIF: Opcode = Opcode(0x64);
+pub const OP_VERIF: Opcode = Opcode(0x65);
+pub const OP_VERNOTIF: Opcode = Opcode(0x66);
+pub const OP_ELSE: Opcode = Opcode(0x67);
+pub const OP_ENDIF: Opcode = Opcode(0x68);
+pub const OP_VERIFY: Opcode = Opcode(0x69);
+pub const OP_RETURN: Opcode = Opcode(0x6a);
+pub const OP_TOALTSTACK: Opcode = Opcode(0x6b);
+pub const OP_FROMALTSTACK: Opcode = Opcode(0x6c);
+pub const OP_2DROP: Opcode = Opcode(0x6d);
+pub const OP_2DUP: Opcode = Opcode(0x6e);
+pub const OP_3DUP: Opcode = Opcode(0x6f);
+pub const OP_2OVER: Opcode = Opcode(0x70);
+pub const OP_2ROT: Opcode = Opcode(0x71);
+pub const OP_2SWAP: Opcode = Opcode(0x72);
+pub const OP_IFDUP: Opcode = Opcode(0x73);
+pub const OP_DEPTH: Opcode = Opcode(0x74);
+pub const OP_DROP: Opcode = Opcode(0x75);
+pub const OP_DUP: Opcode = Opcode(0x76);
+pub const OP_NIP: Opcode = Opcode(0x77);
+pub const OP_OVER: Opcode = Opcode(0x78);
+pub const OP_PICK: Opcode = Opcode(0x79);
+pub const OP_ROLL: Opcode = Opcode(0x7a);
+pub const OP_ROT: Opcode = Opcode(0x7b);
+pub const OP_SWAP: Opcode = Opcode(0x7c);
+pub const OP_TUCK: Opcode = Opcode(0x7d);
+pub const OP_CAT: Opcode = Opcode(0x7e);
+pub const OP_SUBSTR: Opcode = Opcode(0x7f);
+pub const OP_LEFT: Opcode = Opcode(0x80);
+pub const OP_RIGHT: Opcode = Opcode(0x81);
+pub const OP_SIZE: Opcode = Opcode(0x82);
+pub const OP_INVERT: Opcode = Opcode(0x83);
+pub const OP_AND: Opcode = Opcode(0x84);
+pub const OP_OR: Opcode = Opcode(0x85);
+pub const OP_XOR: Opcode = Opcode(0x86);
+pub const OP_EQUAL: Opcode = Opcode(0x87);
+pub const OP_EQUALVERIFY: Opcode = Opcode(0x88);
+pub const OP_RESERVED1: Opcode = Opcode(0x89);
+pub const OP_RESERVED2: Opcode = Opcode(0x8a);
+pub const OP_1ADD: Opcode = Opcode(0x8b);
+pub const OP_1SUB: Opcode = Opcode(0x8c);
+pub const OP_2MUL: Opcode = Opcode(0x8d);
+pub const OP_2DIV: Opcode = Opcode(0x8e);
+pub const OP_NEGATE: Opcode = Opcode(0x8f);
+pub const OP_ABS: Opcode = Opcode(0x90);
+pub const OP_NOT: Opcode = Opcode(0x91);
+pub const OP_0NOTEQUAL: Opcode = Opcode(0x92);
+pub const OP_ADD: Opcode = Opcode(0x93);
+pub const OP_SUB: Opcode = Opcode(0x94);
+pub const OP_MUL: Opcode = Opcode(0x95);
+pub const OP_DIV: Opcode = Opcode(0x96);
+pub const OP_MOD: Opcode = Opcode(0x97);
+pub const OP_LSHIFT: Opcode = Opcode(0x98);
+pub const OP_RSHIFT: Opcode = Opcode(0x99);
+pub const OP_BOOLAND: Opcode = Opcode(0x9a);
+pub const OP_BOOLOR: Opcode = Opcode(0x9b);
+pub const OP_NUMEQUAL: Opcode = Opcode(0x9c);
+pub const OP_NUMEQUALVERIFY: Opcode = Opcode(0x9d);
+pub const OP_NUMNOTEQUAL: Opcode = Opcode(0x9e);
+pub const OP_LESSTHAN: Opcode = Opcode(0x9f);
+pub const OP_GREATERTHAN: Opcode = Opcode(0xa0);
+pub const OP_LESSTHANOREQUAL: Opcode = Opcode(0xa1);
+pub const OP_GREATERTHANOREQUAL: Opcode = Opcode(0xa2);
+pub const OP_MIN: Opcode = Opcode(0xa3);
+pub const OP_MAX: Opcode = Opcode(0xa4);
+pub const OP_WITHIN: Opcode = Opcode(0xa5);
+pub const OP_RIPEMD160: Opcode = Opcode(0xa6);
+pub const OP_SHA1: Opcode = Opcode(0xa7);
+pub const OP_SHA256: Opcode = Opcode(0xa8);
+pub const OP_HASH160: Opcode = Opcode(0xa9);
+pub const OP_HASH256: Opcode = Opcode(0xaa);
+pub const OP_CODESEPARATOR: Opcode = Opcode(0xab);
+pub const OP_CHECKSIG: Opcode = Opcode(0xac);
+pub const OP_CHECKSIGVERIFY: Opcode = Opcode(0xad);
+pub const OP_CHECKMULTISIG: Opcode = Opcode(0xae);
+pub const OP_CHECKMULTISIGVERIFY: Opcode = Opcode(0xaf);
+pub const OP_NOP1: Opcode = Opcode(0xb0);
+pub const OP_CHECKLOCKTIMEVERIFY: Opcode = Opcode(0xb1);
+pub const OP_CHECKSEQUENCEVERIFY: Opcode = Opcode(0xb2);
+pub const OP_NOP4: Opcode = Opcode(0xb3);
+pub const OP_NOP5: Opcode = Opcode(0xb4);
+pub const OP_NOP6: Opcode = Opcode(0xb5);
+pub const OP_NOP7: Opcode = Opcode(0xb6);
+pub const OP_NOP8: Opcode = Opcode(0xb7);
+pub const OP_NOP9: Opcode = Opcode(0xb8);
+pub const OP_NOP10: Opcode = Opcode(0xb9);
+pub const OP_CHECKSIGADD: Opcode = Opcode(0xba);
+pub const OP_INVALIDOPCODE: Opcode = Opcode(0xff);
+
+pub const OP_FALSE: Opcode = OP_0;
+pub const OP_TRUE: Opcode = OP_1;
+pub const OP_NOP2: Opcode = OP_CHECKLOCKTIMEVERIFY;
+pub const OP_NOP3: Opcode = OP_CHECKSEQUENCEVERIFY;
+pub const OP_CLTV: Opcode = OP_CHECKLOCKTIMEVERIFY;
+pub const OP_CSV: Opcode = OP_CHECKSEQUENCEVERIFY;
@@ -0,0 +1,29 @@
+use rand::Rng;
+
+use crate::{BitcoinTransaction, OutPoint, Script, TransactionInput, Txid};
+
+impl Txid {
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Txid(rng.r#gen())
+    }
+}
+
+impl OutPoint {
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        OutPoint::from_txid(Txid::random(rng), rng.r#gen())
+    }
+}
+
+impl BitcoinTransaction {
+    pub fn dummy(n_inputs: usize) -> Self {
+        Self::dummy_with_rng(&mut rand::thread_rng(), n_inputs)
+    }
+
+    /// Version 2 transaction spending random outpoints with empty scripts and final sequences.
+    pub fn dummy_with_rng<R: Rng + ?Sized>(rng: &mut R, n_inputs: usize) -> Self {
+        let inputs = (0..n_inputs)
+            .map(|_| TransactionInput::new(OutPoint::random(rng), Script::new(vec![]), 0xFFFFFFFF))
+            .collect();
+        BitcoinTransaction::new(2, inputs, 0)
+    }
+}
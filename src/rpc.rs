@@ -0,0 +1,230 @@
+use serde_json::{Map, Value, json};
+
+use crate::{
+    Address, BitcoinTransaction, Instruction, Network, Script, ScriptType, SighashType, Signature,
+    TransactionInput, TransactionOutput, opcodes,
+};
+
+impl BitcoinTransaction {
+    /// The transaction as Bitcoin Core's `decoderawtransaction` prints it. Values are in
+    /// BTC. Taproot output keys are not checked to lie on the curve, so one that doesn't
+    /// gets a `rawtr()` descriptor where Core infers `addr()`.
+    pub fn to_rpc_json(&self, network: Network) -> Value {
+        let is_coinbase = self.is_coinbase();
+        json!({
+            "txid": self.txid().to_string(),
+            "hash": self.wtxid().to_string(),
+            "version": self.version,
+            "size": self.total_size(),
+            "vsize": self.vsize(),
+            "weight": self.weight().to_wu(),
+            "locktime": self.lock_time,
+            "vin": self
+                .inputs
+                .iter()
+                .map(|input| rpc_input(input, is_coinbase))
+                .collect::<Vec<_>>(),
+            "vout": self
+                .outputs
+                .iter()
+                .enumerate()
+                .map(|(n, output)| rpc_output(n, output, network))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn rpc_input(input: &TransactionInput, is_coinbase: bool) -> Value {
+    let mut map = Map::new();
+    if is_coinbase {
+        map.insert("coinbase".into(), input.script_sig.to_hex().into());
+    } else {
+        map.insert("txid".into(), input.previous_output.txid.to_string().into());
+        map.insert("vout".into(), input.previous_output.vout.into());
+        map.insert(
+            "scriptSig".into(),
+            json!({
+                "asm": core_asm(&input.script_sig, true),
+                "hex": input.script_sig.to_hex(),
+            }),
+        );
+    }
+    if input.has_witness() {
+        let witness: Vec<_> = input.witness.iter().map(hex::encode).collect();
+        map.insert("txinwitness".into(), witness.into());
+    }
+    map.insert("sequence".into(), input.sequence.0.into());
+    Value::Object(map)
+}
+
+fn rpc_output(n: usize, output: &TransactionOutput, network: Network) -> Value {
+    let script_pubkey = &output.script_pubkey;
+    let mut script = Map::new();
+    let address = Address::from_script(script_pubkey, network).ok();
+    script.insert("asm".into(), core_asm(script_pubkey, false).into());
+    script.insert(
+        "desc".into(),
+        inferred_descriptor(script_pubkey, address.as_ref()).into(),
+    );
+    script.insert("hex".into(), script_pubkey.to_hex().into());
+    if let Some(address) = address {
+        script.insert("address".into(), address.to_string().into());
+    }
+    script.insert("type".into(), rpc_type(script_pubkey.classify()).into());
+    json!({
+        "value": output.value.to_btc(),
+        "n": n,
+        "scriptPubKey": script,
+    })
+}
+
+fn rpc_type(script_type: ScriptType) -> &'static str {
+    match script_type {
+        ScriptType::P2PK => "pubkey",
+        ScriptType::P2PKH => "pubkeyhash",
+        ScriptType::P2SH => "scripthash",
+        ScriptType::P2WPKH => "witness_v0_keyhash",
+        ScriptType::P2WSH => "witness_v0_scripthash",
+        ScriptType::P2TR => "witness_v1_taproot",
+        ScriptType::WitnessUnknown => "witness_unknown",
+        ScriptType::Multisig => "multisig",
+        ScriptType::OpReturn => "nulldata",
+        ScriptType::NonStandard => "nonstandard",
+    }
+}
+
+// Core's `InferDescriptor` with no keys or scripts known: `pk()`, `multi()` and `rawtr()`
+// where the script shows the keys, otherwise `addr()`, otherwise `raw()`.
+fn inferred_descriptor(script: &Script, address: Option<&Address>) -> String {
+    // Core has no descriptor for hybrid keys.
+    let key = |key: &[u8]| matches!(key.first(), Some(0x02..=0x04)).then(|| hex::encode(key));
+    let bytes = script.as_bytes();
+    let inferred = match script.classify() {
+        ScriptType::P2PK => key(&bytes[1..bytes.len() - 1]).map(|key| format!("pk({key})")),
+        ScriptType::P2TR => Some(format!("rawtr({})", hex::encode(&bytes[2..]))),
+        ScriptType::Multisig => script.parse_multisig().and_then(|(required, keys)| {
+            let keys = keys.into_iter().map(key).collect::<Option<Vec<_>>>()?;
+            Some(format!("multi({required},{})", keys.join(",")))
+        }),
+        _ => None,
+    };
+    let descriptor = inferred
+        .or_else(|| address.map(|address| format!("addr({address})")))
+        .unwrap_or_else(|| format!("raw({})", script.to_hex()));
+    let checksum = descriptor_checksum(&descriptor);
+    format!("{descriptor}#{checksum}")
+}
+
+// The BIP380 descriptor checksum.
+fn descriptor_checksum(descriptor: &str) -> String {
+    const INPUT_CHARSET: &str = "0123456789()[],'/*abcdefgh@:$%{}\
+        IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+    const CHECKSUM_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+    const GENERATOR: [u64; 5] = [
+        0xf5dee51989,
+        0xa9fdca3312,
+        0x1bab10e32d,
+        0x3706b1677a,
+        0x644d626ffd,
+    ];
+
+    fn poly_mod(checksum: u64, value: u64) -> u64 {
+        let top = checksum >> 35;
+        let mut checksum = ((checksum & 0x7ffffffff) << 5) ^ value;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 != 0 {
+                checksum ^= generator;
+            }
+        }
+        checksum
+    }
+
+    let mut checksum = 1;
+    let mut groups = Vec::with_capacity(3);
+    // Descriptors built here only use characters from the input charset.
+    for position in descriptor.chars().filter_map(|c| INPUT_CHARSET.find(c)) {
+        let position = position as u64;
+        checksum = poly_mod(checksum, position & 31);
+        groups.push(position >> 5);
+        if groups.len() == 3 {
+            checksum = poly_mod(checksum, groups[0] * 9 + groups[1] * 3 + groups[2]);
+            groups.clear();
+        }
+    }
+    match groups[..] {
+        [a] => checksum = poly_mod(checksum, a),
+        [a, b] => checksum = poly_mod(checksum, a * 3 + b),
+        _ => {}
+    }
+    for _ in 0..8 {
+        checksum = poly_mod(checksum, 0);
+    }
+    checksum ^= 1;
+    (0..8)
+        .map(|i| CHECKSUM_CHARSET[((checksum >> (5 * (7 - i))) & 31) as usize] as char)
+        .collect()
+}
+
+// Core's `ScriptToAsmStr`, which differs from `Script::display_asm`: small-number opcodes
+// and pushes of up to 4 bytes print as integers, and in scriptSigs a trailing sighash byte
+// on a strict-DER signature prints by name, as in `3044...[ALL]`.
+fn core_asm(script: &Script, decode_sighash: bool) -> String {
+    let mut words = Vec::new();
+    for instruction in script.instructions() {
+        let word = match instruction {
+            Err(_) => {
+                words.push("[error]".to_string());
+                break;
+            }
+            Ok(Instruction::Op(opcodes::OP_0)) => "0".to_string(),
+            Ok(Instruction::Op(opcodes::OP_1NEGATE)) => "-1".to_string(),
+            Ok(Instruction::Op(op)) if op >= opcodes::OP_1 && op <= opcodes::OP_16 => {
+                (op.to_u8() - opcodes::OP_1.to_u8() + 1).to_string()
+            }
+            Ok(Instruction::Op(op)) => op.to_string(),
+            Ok(Instruction::PushBytes(data)) if data.len() <= 4 => script_num(data).to_string(),
+            Ok(Instruction::PushBytes(data)) => {
+                let signature = Signature::from_der_with_sighash(data)
+                    .ok()
+                    .filter(|signature| signature.is_strict_der());
+                // Like Core, only the six defined hash types are decoded.
+                let sighash_type = signature
+                    .as_ref()
+                    .and_then(|signature| SighashType::from_u8(signature.sighash_byte()).ok());
+                match (signature, sighash_type) {
+                    (Some(signature), Some(sighash_type))
+                        if decode_sighash && !script.is_op_return() =>
+                    {
+                        // Core names the type without the prefix, as in `ALL|ANYONECANPAY`.
+                        let sighash_type = sighash_type.to_string();
+                        format!(
+                            "{}[{}]",
+                            hex::encode(signature.der()),
+                            sighash_type.trim_start_matches("SIGHASH_")
+                        )
+                    }
+                    _ => hex::encode(data),
+                }
+            }
+        };
+        words.push(word);
+    }
+    words.join(" ")
+}
+
+// `CScriptNum` without the minimal-encoding check, for at most 4 bytes.
+fn script_num(data: &[u8]) -> i64 {
+    let Some((&last, _)) = data.split_last() else {
+        return 0;
+    };
+    let magnitude = data
+        .iter()
+        .enumerate()
+        .fold(0i64, |acc, (i, &byte)| acc | (i64::from(byte) << (8 * i)))
+        & !(0x80i64 << (8 * (data.len() - 1)));
+    if last & 0x80 != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
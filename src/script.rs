@@ -0,0 +1,888 @@
+use std::fmt;
+use std::ops::Deref;
+
+use crate::opcodes::{self, Opcode};
+use crate::{BitcoinError, CompactSize, Hash160, Hash256, Script, XOnlyPublicKey, hash160, sha256};
+
+/// One parsed script element. Data pushes of any encoding (direct, `OP_PUSHDATA1/2/4`)
+/// become `PushBytes`; everything else, including `OP_0` and `OP_1`..`OP_16`, is an `Op`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Instruction<'a> {
+    PushBytes(&'a [u8]),
+    Op(Opcode),
+}
+
+/// An instruction with the opcode byte it was read from, as returned by
+/// [`Instructions::next_with_opcode`]. `PushBytes` doesn't record its encoding, so the
+/// minimal push check lives here, where the opcode can only be the one actually parsed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct EncodedInstruction<'a> {
+    opcode: Opcode,
+    instruction: Instruction<'a>,
+}
+
+impl<'a> EncodedInstruction<'a> {
+    pub fn opcode(&self) -> Opcode {
+        self.opcode
+    }
+
+    pub fn instruction(&self) -> Instruction<'a> {
+        self.instruction
+    }
+
+    /// BIP62 minimal push rule: whether the opcode is the shortest encoding of the pushed
+    /// data. Instructions that push nothing are trivially minimal.
+    pub fn is_minimal_push(&self) -> bool {
+        let Instruction::PushBytes(data) = self.instruction else {
+            return true;
+        };
+        self.opcode == minimal_push_opcode(data)
+    }
+}
+
+// The opcode a minimal encoding of `data` starts with.
+fn minimal_push_opcode(data: &[u8]) -> Opcode {
+    match data {
+        [] => opcodes::OP_0,
+        [n @ 1..=16] => Opcode::from_u8(opcodes::OP_1.to_u8() + n - 1),
+        [0x81] => opcodes::OP_1NEGATE,
+        _ if data.len() <= opcodes::OP_PUSHBYTES_75.to_u8() as usize => {
+            Opcode::from_u8(data.len() as u8)
+        }
+        _ if data.len() <= u8::MAX as usize => opcodes::OP_PUSHDATA1,
+        _ if data.len() <= u16::MAX as usize => opcodes::OP_PUSHDATA2,
+        _ => opcodes::OP_PUSHDATA4,
+    }
+}
+
+/// Iterator over the instructions of a script. Stops after the first error.
+#[derive(Debug, Clone)]
+pub struct Instructions<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> Instructions<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Instructions { remaining: bytes }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BitcoinError> {
+        if self.remaining.len() < len {
+            self.remaining = &[];
+            return Err(BitcoinError::InvalidFormat);
+        }
+        let (taken, rest) = self.remaining.split_at(len);
+        self.remaining = rest;
+        Ok(taken)
+    }
+
+    fn take_len(&mut self, width: usize) -> Result<usize, BitcoinError> {
+        let mut le = [0u8; 4];
+        le[..width].copy_from_slice(self.take(width)?);
+        usize::try_from(u32::from_le_bytes(le)).map_err(|_| BitcoinError::InvalidFormat)
+    }
+
+    /// Like `next`, but also reports the opcode byte that introduced the instruction, which
+    /// is what [`EncodedInstruction::is_minimal_push`] checks against.
+    pub fn next_with_opcode(&mut self) -> Option<Result<EncodedInstruction<'a>, BitcoinError>> {
+        let (&byte, rest) = self.remaining.split_first()?;
+        self.remaining = rest;
+        let opcode = Opcode::from_u8(byte);
+
+        let push_len = match opcode {
+            op if op.to_u8() >= 0x01 && op <= opcodes::OP_PUSHBYTES_75 => Ok(byte as usize),
+            opcodes::OP_PUSHDATA1 => self.take_len(1),
+            opcodes::OP_PUSHDATA2 => self.take_len(2),
+            opcodes::OP_PUSHDATA4 => self.take_len(4),
+            _ => {
+                return Some(Ok(EncodedInstruction {
+                    opcode,
+                    instruction: Instruction::Op(opcode),
+                }));
+            }
+        };
+        let data = push_len.and_then(|len| self.take(len));
+        Some(data.map(|data| EncodedInstruction {
+            opcode,
+            instruction: Instruction::PushBytes(data),
+        }))
+    }
+}
+
+impl<'a> Iterator for Instructions<'a> {
+    type Item = Result<Instruction<'a>, BitcoinError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_opcode()
+            .map(|result| result.map(|encoded| encoded.instruction))
+    }
+}
+
+/// A script integer in `CScriptNum` form: minimal little-endian magnitude with the sign in
+/// the top bit of the last byte.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct ScriptNum(i64);
+
+impl ScriptNum {
+    /// Operand size limit for arithmetic opcodes.
+    pub const DEFAULT_MAX_LEN: usize = 4;
+    /// Operand size limit for `OP_CHECKLOCKTIMEVERIFY` and `OP_CHECKSEQUENCEVERIFY`.
+    pub const LOCKTIME_MAX_LEN: usize = 5;
+
+    pub const fn new(value: i64) -> Self {
+        ScriptNum(value)
+    }
+
+    pub const fn value(self) -> i64 {
+        self.0
+    }
+
+    pub fn encode(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let negative = self.0 < 0;
+        let mut magnitude = self.0.unsigned_abs();
+        while magnitude > 0 {
+            bytes.push((magnitude & 0xFF) as u8);
+            magnitude >>= 8;
+        }
+        if let Some(last) = bytes.last_mut() {
+            if *last & 0x80 != 0 {
+                bytes.push(if negative { 0x80 } else { 0x00 });
+            } else if negative {
+                *last |= 0x80;
+            }
+        }
+        bytes
+    }
+
+    /// Decodes a stack element, rejecting encodings longer than `max_len` bytes and
+    /// non-minimal ones (including negative zero).
+    pub fn decode(bytes: &[u8], max_len: usize) -> Result<Self, BitcoinError> {
+        if bytes.len() > max_len {
+            return Err(BitcoinError::ValueTooLarge {
+                value: bytes.len() as u64,
+                max: max_len as u64,
+            });
+        }
+        let Some((&last, init)) = bytes.split_last() else {
+            return Ok(ScriptNum(0));
+        };
+        // The top byte may only be 0x00/0x80 when it is needed to hold the sign bit.
+        if last & 0x7F == 0 && init.last().is_none_or(|byte| byte & 0x80 == 0) {
+            return Err(BitcoinError::NonMinimalScriptNum);
+        }
+        if bytes.len() > 8 {
+            return Err(BitcoinError::IntegerOverflow);
+        }
+
+        let magnitude = bytes
+            .iter()
+            .enumerate()
+            .fold(0u64, |acc, (i, byte)| acc | (u64::from(*byte) << (8 * i)))
+            & !(0x80u64 << (8 * init.len()));
+        let magnitude = i64::try_from(magnitude).map_err(|_| BitcoinError::IntegerOverflow)?;
+        Ok(ScriptNum(if last & 0x80 != 0 {
+            -magnitude
+        } else {
+            magnitude
+        }))
+    }
+}
+
+impl From<i64> for ScriptNum {
+    fn from(value: i64) -> Self {
+        ScriptNum(value)
+    }
+}
+
+impl From<ScriptNum> for i64 {
+    fn from(num: ScriptNum) -> Self {
+        num.0
+    }
+}
+
+/// Builds a script one instruction at a time, always choosing the minimal push encoding.
+///
+/// ```
+/// use rust_week_3_exercises::{ScriptBuilder, opcodes};
+///
+/// let script = ScriptBuilder::new()
+///     .push_opcode(opcodes::OP_RETURN)
+///     .push_slice(b"hi")
+///     .into_script();
+/// assert_eq!(script.to_hex(), "6a026869");
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct ScriptBuilder {
+    bytes: Vec<u8>,
+}
+
+impl ScriptBuilder {
+    pub fn new() -> Self {
+        ScriptBuilder::default()
+    }
+
+    pub fn push_opcode(mut self, opcode: Opcode) -> Self {
+        self.bytes.push(opcode.to_u8());
+        self
+    }
+
+    pub fn push_slice(mut self, data: &[u8]) -> Self {
+        let len = data.len();
+        if len <= opcodes::OP_PUSHBYTES_75.to_u8() as usize {
+            self.bytes.push(len as u8);
+        } else if let Ok(len) = u8::try_from(len) {
+            self.bytes.push(opcodes::OP_PUSHDATA1.to_u8());
+            self.bytes.push(len);
+        } else if let Ok(len) = u16::try_from(len) {
+            self.bytes.push(opcodes::OP_PUSHDATA2.to_u8());
+            self.bytes.extend_from_slice(&len.to_le_bytes());
+        } else {
+            let len = u32::try_from(len).expect("push data length fits in u32");
+            self.bytes.push(opcodes::OP_PUSHDATA4.to_u8());
+            self.bytes.extend_from_slice(&len.to_le_bytes());
+        }
+        self.bytes.extend_from_slice(data);
+        self
+    }
+
+    /// Pushes `OP_0`, `OP_1NEGATE` or `OP_1`..`OP_16` when possible, otherwise the
+    /// `CScriptNum` encoding of `value`.
+    pub fn push_int(self, value: i64) -> Self {
+        match value {
+            0 => self.push_opcode(opcodes::OP_0),
+            -1 => self.push_opcode(opcodes::OP_1NEGATE),
+            1..=16 => {
+                let opcode = Opcode::from_u8(opcodes::OP_1.to_u8() + value as u8 - 1);
+                self.push_opcode(opcode)
+            }
+            _ => self.push_slice(&ScriptNum::new(value).encode()),
+        }
+    }
+
+    pub fn into_script(self) -> Script {
+        Script::new(self.bytes)
+    }
+}
+
+/// Standard output templates recognised by [`Script::classify`].
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ScriptType {
+    P2PK,
+    P2PKH,
+    P2SH,
+    P2WPKH,
+    P2WSH,
+    P2TR,
+    /// A witness program with a version not yet given meaning (v2..v16, or v1 not 32 bytes).
+    WitnessUnknown,
+    Multisig,
+    OpReturn,
+    NonStandard,
+}
+
+// BIP141: a version opcode followed by a single direct push of 2..=40 bytes, where v0
+// programs can only be 20 (P2WPKH) or 32 (P2WSH) bytes.
+pub(crate) fn witness_version_and_program(bytes: &[u8]) -> Option<(u8, &[u8])> {
+    if !(4..=42).contains(&bytes.len()) || bytes[1] as usize + 2 != bytes.len() {
+        return None;
+    }
+    let program = &bytes[2..];
+    match Opcode::from_u8(bytes[0]) {
+        opcodes::OP_0 if program.len() == 20 || program.len() == 32 => Some((0, program)),
+        op if op >= opcodes::OP_1 && op <= opcodes::OP_16 => {
+            Some((op.to_u8() - opcodes::OP_1.to_u8() + 1, program))
+        }
+        _ => None,
+    }
+}
+
+// Every opcode up to and including OP_16 except OP_RESERVED, and no truncated pushes.
+pub(crate) fn is_push_only(bytes: &[u8]) -> bool {
+    let mut instructions = Instructions::new(bytes);
+    std::iter::from_fn(|| instructions.next_with_opcode()).all(|result| {
+        matches!(result, Ok(encoded) if encoded.opcode <= opcodes::OP_16 && encoded.opcode != opcodes::OP_RESERVED)
+    })
+}
+
+pub(crate) fn is_valid_pubkey_len(key: &[u8]) -> bool {
+    matches!(
+        (key.first(), key.len()),
+        (Some(0x02 | 0x03), 33) | (Some(0x04 | 0x06 | 0x07), 65)
+    )
+}
+
+// Value pushed by `OP_1`..`OP_16`.
+fn small_int_value(opcode: Opcode) -> Option<usize> {
+    (opcode >= opcodes::OP_1 && opcode <= opcodes::OP_16)
+        .then(|| (opcode.to_u8() - opcodes::OP_1.to_u8() + 1) as usize)
+}
+
+// A multisig key count: `OP_1`..`OP_16`, or a minimal push of 17 up to
+// `MAX_PUBKEYS_PER_MULTISIG`, as `Script::new_multisig` writes them.
+fn multisig_count(encoded: &EncodedInstruction<'_>) -> Option<usize> {
+    match encoded.instruction {
+        Instruction::Op(op) => small_int_value(op),
+        Instruction::PushBytes([n])
+            if (17..=Script::MAX_PUBKEYS_PER_MULTISIG).contains(&(*n as usize))
+                && encoded.is_minimal_push() =>
+        {
+            Some(*n as usize)
+        }
+        Instruction::PushBytes(_) => None,
+    }
+}
+
+fn parse_multisig(bytes: &[u8]) -> Option<(usize, Vec<&[u8]>)> {
+    let mut iter = Instructions::new(bytes);
+    let instructions = std::iter::from_fn(|| iter.next_with_opcode())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    let [
+        first,
+        keys @ ..,
+        last_n,
+        EncodedInstruction {
+            instruction: Instruction::Op(opcodes::OP_CHECKMULTISIG),
+            ..
+        },
+    ] = &instructions[..]
+    else {
+        return None;
+    };
+    let (required, total) = (multisig_count(first)?, multisig_count(last_n)?);
+    if required > total || keys.len() != total {
+        return None;
+    }
+    let keys = keys
+        .iter()
+        .map(|key| match key.instruction {
+            Instruction::PushBytes(key) if is_valid_pubkey_len(key) => Some(key),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some((required, keys))
+}
+
+pub(crate) fn classify(bytes: &[u8]) -> ScriptType {
+    use opcodes::*;
+
+    match bytes {
+        [op_dup, op_hash160, 0x14, .., op_equalverify, op_checksig]
+            if bytes.len() == 25
+                && *op_dup == OP_DUP.to_u8()
+                && *op_hash160 == OP_HASH160.to_u8()
+                && *op_equalverify == OP_EQUALVERIFY.to_u8()
+                && *op_checksig == OP_CHECKSIG.to_u8() =>
+        {
+            return ScriptType::P2PKH;
+        }
+        [op_hash160, 0x14, .., op_equal]
+            if bytes.len() == 23
+                && *op_hash160 == OP_HASH160.to_u8()
+                && *op_equal == OP_EQUAL.to_u8() =>
+        {
+            return ScriptType::P2SH;
+        }
+        [len, key @ .., op_checksig]
+            if *len as usize == key.len()
+                && *op_checksig == OP_CHECKSIG.to_u8()
+                && is_valid_pubkey_len(key) =>
+        {
+            return ScriptType::P2PK;
+        }
+        _ => {}
+    }
+
+    if let Some((version, program)) = witness_version_and_program(bytes) {
+        return match (version, program.len()) {
+            (0, 20) => ScriptType::P2WPKH,
+            (0, _) => ScriptType::P2WSH,
+            (1, 32) => ScriptType::P2TR,
+            _ => ScriptType::WitnessUnknown,
+        };
+    }
+
+    if bytes.first() == Some(&OP_RETURN.to_u8()) && is_push_only(&bytes[1..]) {
+        return ScriptType::OpReturn;
+    }
+    // Counts may be `OP_1`..`OP_16` or minimal pushes up to 20 (see `multisig_count`), but
+    // only scripts of at most 16 keys make the template, as in Core's `Solver` before v22.
+    if parse_multisig(bytes).is_some_and(|(_, keys)| keys.len() <= 16) {
+        return ScriptType::Multisig;
+    }
+    ScriptType::NonStandard
+}
+
+/// Displays a script as Core-style asm; returned by [`Script::display_asm`].
+#[derive(Debug, Clone, Copy)]
+pub struct ScriptAsm<'a>(&'a [u8]);
+
+impl fmt::Display for ScriptAsm<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, instruction) in Instructions::new(self.0).enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            match instruction {
+                Ok(Instruction::Op(opcode)) => write!(f, "{opcode}")?,
+                Ok(Instruction::PushBytes([])) => write!(f, "{}", opcodes::OP_0)?,
+                Ok(Instruction::PushBytes(data)) => f.write_str(&hex::encode(data))?,
+                // Rendered rather than reported so broken scripts can still be inspected.
+                Err(_) => f.write_str("[truncated push]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn count_sigops(bytes: &[u8], accurate: bool) -> usize {
+    let mut instructions = Instructions::new(bytes);
+    let mut count = 0;
+    let mut last_opcode = opcodes::OP_INVALIDOPCODE;
+    while let Some(Ok(EncodedInstruction { opcode, .. })) = instructions.next_with_opcode() {
+        match opcode {
+            opcodes::OP_CHECKSIG | opcodes::OP_CHECKSIGVERIFY => count += 1,
+            opcodes::OP_CHECKMULTISIG | opcodes::OP_CHECKMULTISIGVERIFY => {
+                count += small_int_value(last_opcode)
+                    .filter(|_| accurate)
+                    .unwrap_or(Script::MAX_PUBKEYS_PER_MULTISIG);
+            }
+            _ => {}
+        }
+        last_opcode = opcode;
+    }
+    count
+}
+
+// Stack elements pushed by OP_1NEGATE, OP_RESERVED (unused) and OP_1..OP_16, by opcode offset.
+static SMALL_INT_ELEMENTS: [u8; 18] = [
+    0x81, 0x00, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+];
+
+/// Timelock operand of an `OP_CHECKLOCKTIMEVERIFY` (absolute) or `OP_CHECKSEQUENCEVERIFY`
+/// (relative) found by [`Script::locktime_requirement`].
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum LockTimeRequirement {
+    Absolute(i64),
+    Relative(i64),
+}
+
+// Operand pushed by a single instruction, as the timelock opcodes would decode it.
+fn locktime_operand(instruction: &Instruction<'_>) -> Option<i64> {
+    match instruction {
+        Instruction::PushBytes(data) => ScriptNum::decode(data, ScriptNum::LOCKTIME_MAX_LEN)
+            .ok()
+            .map(ScriptNum::value),
+        Instruction::Op(opcodes::OP_0) => Some(0),
+        Instruction::Op(opcodes::OP_1NEGATE) => Some(-1),
+        Instruction::Op(op) => small_int_value(*op).map(|n| n as i64),
+    }
+}
+
+impl Script {
+    /// Largest payload `new_op_return` accepts, matching Core's default `-datacarriersize`.
+    pub const MAX_OP_RETURN_DATA: usize = 80;
+
+    /// Relay policy limit on the whole `OP_RETURN` script (Core's `MAX_OP_RETURN_RELAY`).
+    pub const MAX_OP_RETURN_RELAY: usize = 83;
+
+    /// Signature checks a bare `OP_CHECKMULTISIG` is assumed to perform.
+    pub const MAX_PUBKEYS_PER_MULTISIG: usize = 20;
+
+    /// Consensus limit on the size of an executed script.
+    pub const MAX_SCRIPT_SIZE: usize = 10_000;
+    /// Consensus limit on a single pushed stack element.
+    pub const MAX_SCRIPT_ELEMENT_SIZE: usize = 520;
+
+    /// Like `Script::new`, but rejects scripts over [`Script::MAX_SCRIPT_SIZE`].
+    pub fn new_checked(bytes: Vec<u8>) -> Result<Script, BitcoinError> {
+        if bytes.len() > Script::MAX_SCRIPT_SIZE {
+            return Err(BitcoinError::ScriptTooLarge { size: bytes.len() });
+        }
+        Ok(Script::new(bytes))
+    }
+
+    pub fn new_p2pkh(hash: &Hash160) -> Script {
+        ScriptBuilder::new()
+            .push_opcode(opcodes::OP_DUP)
+            .push_opcode(opcodes::OP_HASH160)
+            .push_slice(hash.as_bytes())
+            .push_opcode(opcodes::OP_EQUALVERIFY)
+            .push_opcode(opcodes::OP_CHECKSIG)
+            .into_script()
+    }
+
+    pub fn new_p2sh(hash: &Hash160) -> Script {
+        ScriptBuilder::new()
+            .push_opcode(opcodes::OP_HASH160)
+            .push_slice(hash.as_bytes())
+            .push_opcode(opcodes::OP_EQUAL)
+            .into_script()
+    }
+
+    pub fn new_p2wpkh(hash: &Hash160) -> Script {
+        Script::new_witness_program(opcodes::OP_0, hash.as_bytes())
+    }
+
+    pub fn new_p2wsh(hash: &Hash256) -> Script {
+        Script::new_witness_program(opcodes::OP_0, hash.as_bytes())
+    }
+
+    pub fn new_p2tr(output_key: &XOnlyPublicKey) -> Script {
+        Script::new_witness_program(opcodes::OP_1, output_key.as_bytes())
+    }
+
+    fn new_witness_program(version: Opcode, program: &[u8]) -> Script {
+        ScriptBuilder::new()
+            .push_opcode(version)
+            .push_slice(program)
+            .into_script()
+    }
+
+    /// Builds `OP_m <keys...> OP_n OP_CHECKMULTISIG`. Keys must be 33- or 65-byte SEC
+    /// encodings; at most [`Script::MAX_PUBKEYS_PER_MULTISIG`] are allowed.
+    pub fn new_multisig<K: AsRef<[u8]>>(
+        required: usize,
+        pubkeys: &[K],
+    ) -> Result<Script, BitcoinError> {
+        if pubkeys.len() > Script::MAX_PUBKEYS_PER_MULTISIG {
+            return Err(BitcoinError::ValueTooLarge {
+                value: pubkeys.len() as u64,
+                max: Script::MAX_PUBKEYS_PER_MULTISIG as u64,
+            });
+        }
+        if required == 0 || required > pubkeys.len() {
+            return Err(BitcoinError::InvalidFormat);
+        }
+        let mut builder = ScriptBuilder::new().push_int(required as i64);
+        for key in pubkeys {
+            if !is_valid_pubkey_len(key.as_ref()) {
+                return Err(BitcoinError::InvalidFormat);
+            }
+            builder = builder.push_slice(key.as_ref());
+        }
+        Ok(builder
+            .push_int(pubkeys.len() as i64)
+            .push_opcode(opcodes::OP_CHECKMULTISIG)
+            .into_script())
+    }
+
+    pub fn new_op_return(data: &[u8]) -> Result<Script, BitcoinError> {
+        if data.len() > Script::MAX_OP_RETURN_DATA {
+            return Err(BitcoinError::ValueTooLarge {
+                value: data.len() as u64,
+                max: Script::MAX_OP_RETURN_DATA as u64,
+            });
+        }
+        Ok(ScriptBuilder::new()
+            .push_opcode(opcodes::OP_RETURN)
+            .push_slice(data)
+            .into_script())
+    }
+
+    /// Parses whitespace-separated opcode names and hex data pushes. Pushes are re-encoded
+    /// minimally, so non-minimal scripts do not survive a trip through asm.
+    pub fn from_asm_str(asm: &str) -> Result<Script, BitcoinError> {
+        let mut builder = ScriptBuilder::new();
+        for token in asm.split_whitespace() {
+            builder = if token.starts_with("OP_") {
+                builder.push_opcode(token.parse()?)
+            } else {
+                let data = hex::decode(token).map_err(|_| BitcoinError::InvalidFormat)?;
+                builder.push_slice(&data)
+            };
+        }
+        Ok(builder.into_script())
+    }
+
+    /// Re-encodes every push minimally, turning e.g. `OP_PUSHDATA1 0x14 <20 bytes>` into
+    /// `0x14 <20 bytes>` and a push of `[0x05]` into `OP_5`. Fails on malformed scripts.
+    pub fn to_minimal(&self) -> Result<Script, BitcoinError> {
+        let mut builder = ScriptBuilder::new();
+        for instruction in self.instructions() {
+            builder = match instruction? {
+                Instruction::PushBytes(data) => match minimal_push_opcode(data) {
+                    opcode if opcode <= opcodes::OP_PUSHDATA4 => builder.push_slice(data),
+                    small_int => builder.push_opcode(small_int),
+                },
+                Instruction::Op(opcode) => builder.push_opcode(opcode),
+            };
+        }
+        Ok(builder.into_script())
+    }
+}
+
+impl Script {
+    pub fn classify(&self) -> ScriptType {
+        classify(self.as_bytes())
+    }
+
+    pub fn is_p2pkh(&self) -> bool {
+        self.classify() == ScriptType::P2PKH
+    }
+
+    pub fn is_p2sh(&self) -> bool {
+        self.classify() == ScriptType::P2SH
+    }
+
+    pub fn is_witness_program(&self) -> bool {
+        witness_version_and_program(self.as_bytes()).is_some()
+    }
+
+    /// Segwit version (0..=16) if this is a BIP141 witness program.
+    pub fn witness_version(&self) -> Option<u8> {
+        witness_version_and_program(self.as_bytes()).map(|(version, _)| version)
+    }
+
+    /// Program bytes following the version if this is a BIP141 witness program.
+    pub fn witness_program(&self) -> Option<&[u8]> {
+        witness_version_and_program(self.as_bytes()).map(|(_, program)| program)
+    }
+
+    /// True for any script starting with `OP_RETURN`, which makes it provably unspendable;
+    /// `classify` additionally requires the rest to be push-only.
+    pub fn is_op_return(&self) -> bool {
+        self.first() == Some(&opcodes::OP_RETURN.to_u8())
+    }
+
+    /// True if the script only pushes data: direct and `OP_PUSHDATA` pushes, `OP_0`,
+    /// `OP_1NEGATE` and `OP_1`..`OP_16`. Malformed scripts are not push-only.
+    pub fn is_push_only(&self) -> bool {
+        is_push_only(self.as_bytes())
+    }
+
+    pub fn is_standard_op_return(&self) -> bool {
+        self.len() <= Script::MAX_OP_RETURN_RELAY && self.classify() == ScriptType::OpReturn
+    }
+
+    pub fn is_within_consensus_size(&self) -> bool {
+        self.len() <= Script::MAX_SCRIPT_SIZE
+    }
+
+    /// True if any push before the first malformed instruction exceeds
+    /// [`Script::MAX_SCRIPT_ELEMENT_SIZE`].
+    pub fn has_oversized_element(&self) -> bool {
+        self.instructions()
+            .map_while(Result::ok)
+            .any(|instruction| {
+                matches!(instruction, Instruction::PushBytes(data) if data.len() > Script::MAX_SCRIPT_ELEMENT_SIZE)
+            })
+    }
+
+    /// True if the script parses and every push uses its minimal encoding.
+    pub fn is_minimal(&self) -> bool {
+        let mut instructions = self.instructions();
+        std::iter::from_fn(|| instructions.next_with_opcode())
+            .all(|result| result.is_ok_and(|encoded| encoded.is_minimal_push()))
+    }
+
+    /// Required signature count and key pushes of a script that classifies as
+    /// [`ScriptType::Multisig`], or of one with 17 to 20 keys as built by
+    /// [`Script::new_multisig`], which the template stops short of.
+    pub fn parse_multisig(&self) -> Option<(usize, Vec<&[u8]>)> {
+        parse_multisig(self.as_bytes())
+    }
+}
+
+impl Script {
+    pub fn instructions(&self) -> Instructions<'_> {
+        Instructions::new(self.as_bytes())
+    }
+
+    pub fn as_ref_script(&self) -> ScriptRef<'_> {
+        ScriptRef(self.as_bytes())
+    }
+
+    pub fn display_asm(&self) -> ScriptAsm<'_> {
+        ScriptAsm(self.as_bytes())
+    }
+
+    pub fn to_asm_string(&self) -> String {
+        self.display_asm().to_string()
+    }
+
+    /// HASH160 of the script, as committed to by a P2SH output.
+    pub fn script_hash(&self) -> Hash160 {
+        hash160(self.as_bytes())
+    }
+
+    /// Single SHA256 of the script, as committed to by a P2WSH output.
+    pub fn wscript_hash(&self) -> Hash256 {
+        sha256(self.as_bytes())
+    }
+
+    /// Core's `GetSigOpCount`: with `accurate`, a multisig preceded by `OP_1`..`OP_16`
+    /// counts that many keys instead of the maximum. Counting stops quietly at the first
+    /// malformed push, matching Core.
+    pub fn count_sigops(&self, accurate: bool) -> usize {
+        count_sigops(self.as_bytes(), accurate)
+    }
+
+    /// Stack elements pushed after a leading `OP_RETURN`, with `OP_0`, `OP_1NEGATE` and
+    /// `OP_1`..`OP_16` yielding the element they push. Returns `None` if the script does not
+    /// start with `OP_RETURN`, or if anything after it is not a well-formed push.
+    pub fn op_return_data(&self) -> Option<Vec<&[u8]>> {
+        let rest = self.strip_prefix(&[opcodes::OP_RETURN.to_u8()])?;
+        Instructions::new(rest)
+            .map(|instruction| match instruction {
+                Ok(Instruction::PushBytes(data)) => Some(data),
+                Ok(Instruction::Op(opcodes::OP_0)) => Some(&[][..]),
+                Ok(Instruction::Op(op)) if op >= opcodes::OP_1NEGATE && op <= opcodes::OP_16 => {
+                    (op != opcodes::OP_RESERVED).then(|| {
+                        let i = (op.to_u8() - opcodes::OP_1NEGATE.to_u8()) as usize;
+                        &SMALL_INT_ELEMENTS[i..=i]
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// For a push-only scriptSig, its final data push read as a script: the redeem script
+    /// when this spends a P2SH output. Only meaningful alongside the spent scriptPubKey.
+    pub fn redeem_script(&self) -> Option<ScriptRef<'_>> {
+        if !self.is_push_only() {
+            return None;
+        }
+        match self.instructions().last()? {
+            Ok(Instruction::PushBytes(data)) => Some(ScriptRef::new(data)),
+            _ => None,
+        }
+    }
+
+    /// The BIP143 script code for spending this P2WPKH output: the P2PKH script of its key
+    /// hash. `None` for any other script.
+    pub fn p2wpkh_script_code(&self) -> Option<Script> {
+        if self.classify() != ScriptType::P2WPKH {
+            return None;
+        }
+        let hash = self.as_bytes()[2..]
+            .try_into()
+            .expect("P2WPKH has a 20-byte program");
+        Some(Script::new_p2pkh(&Hash160(hash)))
+    }
+
+    /// Scans for `<n> OP_CHECKLOCKTIMEVERIFY` and `<n> OP_CHECKSEQUENCEVERIFY`, returning the
+    /// largest operand of each kind since that is the one that binds. Absolute requirements
+    /// take precedence when a script has both. Operands that are not valid 5-byte script
+    /// numbers are ignored, as is anything after a malformed push.
+    pub fn locktime_requirement(&self) -> Option<LockTimeRequirement> {
+        let mut absolute = None;
+        let mut relative = None;
+        let mut operand = None;
+        for instruction in self.instructions().map_while(Result::ok) {
+            match instruction {
+                Instruction::Op(opcodes::OP_CHECKLOCKTIMEVERIFY) => {
+                    absolute = absolute.max(operand);
+                }
+                Instruction::Op(opcodes::OP_CHECKSEQUENCEVERIFY) => {
+                    relative = relative.max(operand);
+                }
+                _ => {}
+            }
+            operand = locktime_operand(&instruction);
+        }
+        absolute
+            .map(LockTimeRequirement::Absolute)
+            .or(relative.map(LockTimeRequirement::Relative))
+    }
+}
+
+/// A borrowed script, for analysing scripts in place without copying them out of a buffer.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct ScriptRef<'a>(&'a [u8]);
+
+impl<'a> ScriptRef<'a> {
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        ScriptRef(bytes)
+    }
+
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Reads a CompactSize-prefixed script, borrowing its body from `bytes`.
+    pub fn parse(bytes: &'a [u8]) -> Result<(Self, &'a [u8]), BitcoinError> {
+        let (compact_size, rest) = CompactSize::parse(bytes)?;
+        let script_length = usize::try_from(compact_size)?;
+        if rest.len() < script_length {
+            return Err(BitcoinError::InsufficientBytes);
+        }
+        let (script_bytes, rest) = rest.split_at(script_length);
+        Ok((ScriptRef(script_bytes), rest))
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    pub fn to_script(&self) -> Script {
+        Script::new(self.0.to_vec())
+    }
+
+    pub fn instructions(&self) -> Instructions<'a> {
+        Instructions::new(self.0)
+    }
+
+    pub fn classify(&self) -> ScriptType {
+        classify(self.0)
+    }
+
+    pub fn is_witness_program(&self) -> bool {
+        witness_version_and_program(self.0).is_some()
+    }
+
+    pub fn witness_version(&self) -> Option<u8> {
+        witness_version_and_program(self.0).map(|(version, _)| version)
+    }
+
+    pub fn witness_program(&self) -> Option<&'a [u8]> {
+        witness_version_and_program(self.0).map(|(_, program)| program)
+    }
+
+    pub fn is_push_only(&self) -> bool {
+        is_push_only(self.0)
+    }
+
+    pub fn count_sigops(&self, accurate: bool) -> usize {
+        count_sigops(self.0, accurate)
+    }
+}
+
+impl Deref for ScriptRef<'_> {
+    type Target = [u8];
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for ScriptRef<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+impl fmt::Debug for ScriptRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ScriptRef({})", self.to_hex())
+    }
+}
+
+impl fmt::Display for ScriptRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl From<ScriptRef<'_>> for Script {
+    fn from(script: ScriptRef<'_>) -> Self {
+        script.to_script()
+    }
+}
+
+impl PartialEq<ScriptRef<'_>> for Script {
+    fn eq(&self, other: &ScriptRef<'_>) -> bool {
+        self.as_bytes() == other.0
+    }
+}
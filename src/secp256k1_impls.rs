@@ -0,0 +1,89 @@
+use secp256k1::{Message, Parity, Scalar, Secp256k1, SecretKey, ecdsa, schnorr};
+
+use crate::{
+    BitcoinError, ControlBlock, PrivateKey, PublicKey, Script, SighashType, Signature,
+    XOnlyPublicKey, tagged_hash,
+};
+
+impl PublicKey {
+    /// Verifies against a 32-byte digest. High-S signatures are normalized first, as
+    /// consensus allows them; check `Signature::is_low_s` separately for policy.
+    pub fn verify_ecdsa(&self, msg: &[u8; 32], sig: &Signature) -> Result<(), BitcoinError> {
+        let key = secp256k1::PublicKey::from_slice(self.as_bytes())
+            .map_err(|_| BitcoinError::InvalidPublicKey)?;
+        let mut sig = ecdsa::Signature::from_der_lax(sig.der())
+            .map_err(|_| BitcoinError::InvalidSignature)?;
+        sig.normalize_s();
+        Secp256k1::verification_only()
+            .verify_ecdsa(&Message::from_digest(*msg), &sig, &key)
+            .map_err(|_| BitcoinError::InvalidSignature)
+    }
+}
+
+impl XOnlyPublicKey {
+    pub fn verify_schnorr(&self, msg: &[u8; 32], sig: &[u8; 64]) -> Result<(), BitcoinError> {
+        let key = secp256k1::XOnlyPublicKey::from_slice(self.as_bytes())
+            .map_err(|_| BitcoinError::InvalidPublicKey)?;
+        let sig =
+            schnorr::Signature::from_slice(sig).map_err(|_| BitcoinError::InvalidSignature)?;
+        Secp256k1::verification_only()
+            .verify_schnorr(&sig, &Message::from_digest(*msg), &key)
+            .map_err(|_| BitcoinError::InvalidSignature)
+    }
+}
+
+impl PrivateKey {
+    fn secret_key(&self) -> Result<SecretKey, BitcoinError> {
+        SecretKey::from_slice(self.as_bytes()).map_err(|_| BitcoinError::InvalidPrivateKey)
+    }
+
+    /// The public key, serialized compressed or not to match the WIF flag.
+    pub fn public_key(&self) -> Result<PublicKey, BitcoinError> {
+        let key =
+            secp256k1::PublicKey::from_secret_key(&Secp256k1::signing_only(), &self.secret_key()?);
+        if self.compressed() {
+            PublicKey::from_slice(&key.serialize())
+        } else {
+            PublicKey::from_slice(&key.serialize_uncompressed())
+        }
+    }
+
+    /// Deterministic (RFC6979) low-S signature over a 32-byte digest, tagged with
+    /// `sighash_type` ready for a scriptSig or witness.
+    pub fn sign_ecdsa(
+        &self,
+        msg: &[u8; 32],
+        sighash_type: SighashType,
+    ) -> Result<Signature, BitcoinError> {
+        let sig =
+            Secp256k1::signing_only().sign_ecdsa(&Message::from_digest(*msg), &self.secret_key()?);
+        let mut bytes = sig.serialize_der().to_vec();
+        bytes.push(sighash_type.to_u8());
+        Signature::from_der_with_sighash(&bytes)
+    }
+}
+
+impl ControlBlock {
+    /// Checks that `output_key` is the internal key tweaked by the tree root this control
+    /// block and `leaf_script` commit to, with the parity it claims.
+    pub fn verify_taproot_commitment(
+        &self,
+        output_key: &XOnlyPublicKey,
+        leaf_script: &Script,
+    ) -> bool {
+        let internal_key = self.internal_key();
+        let mut data = internal_key.to_vec();
+        data.extend_from_slice(self.merkle_root(leaf_script).as_bytes());
+        let Ok(tweak) = Scalar::from_be_bytes(tagged_hash("TapTweak", &data)) else {
+            return false;
+        };
+        let (Ok(internal_key), Ok(output_key), Ok(parity)) = (
+            secp256k1::XOnlyPublicKey::from_slice(internal_key.as_bytes()),
+            secp256k1::XOnlyPublicKey::from_slice(output_key.as_bytes()),
+            Parity::from_u8(self.output_key_parity()),
+        ) else {
+            return false;
+        };
+        internal_key.tweak_add_check(&Secp256k1::verification_only(), &output_key, parity, tweak)
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// A BIP68 relative lock time decoded from a sequence number.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum RelativeLockTime {
+    Blocks(u16),
+    /// In units of 512 seconds.
+    Time(u16),
+}
+
+/// An input's `nSequence`. Encodes replaceability (BIP125), whether the transaction's
+/// lock time is enforced, and a relative lock time (BIP68).
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
+pub struct Sequence(pub u32);
+
+impl Sequence {
+    pub const MAX: Sequence = Sequence(0xFFFFFFFF);
+    pub const ZERO: Sequence = Sequence(0);
+    /// Enforces the lock time without signaling replaceability.
+    pub const ENABLE_LOCKTIME_NO_RBF: Sequence = Sequence(0xFFFFFFFE);
+    pub const ENABLE_RBF_NO_LOCKTIME: Sequence = Sequence(0xFFFFFFFD);
+
+    pub const LOCK_TIME_DISABLE_FLAG: u32 = 1 << 31;
+    pub const LOCK_TIME_TYPE_FLAG: u32 = 1 << 22;
+    pub const LOCK_TIME_MASK: u32 = 0x0000FFFF;
+
+    pub fn is_final(&self) -> bool {
+        *self == Sequence::MAX
+    }
+
+    /// Signals BIP125 replaceability.
+    pub fn is_rbf(&self) -> bool {
+        self.0 < Sequence::ENABLE_LOCKTIME_NO_RBF.0
+    }
+
+    /// The transaction's lock time is only enforced if some input is not final.
+    pub fn enables_absolute_lock_time(&self) -> bool {
+        !self.is_final()
+    }
+
+    /// The BIP68 lock, or `None` when the disable flag is set. Bits outside the type flag
+    /// and the low 16 bits carry no meaning and are ignored.
+    pub fn relative_lock_time(&self) -> Option<RelativeLockTime> {
+        if self.0 & Sequence::LOCK_TIME_DISABLE_FLAG != 0 {
+            return None;
+        }
+        let value = (self.0 & Sequence::LOCK_TIME_MASK) as u16;
+        if self.0 & Sequence::LOCK_TIME_TYPE_FLAG != 0 {
+            Some(RelativeLockTime::Time(value))
+        } else {
+            Some(RelativeLockTime::Blocks(value))
+        }
+    }
+
+    /// The sequence with its meaning in a transaction of version `tx_version`, such as
+    /// `4294967293 (RBF)` or `10 (RBF, relative lock: 10 blocks)`. The relative lock is
+    /// only shown from version 2, where BIP68 applies.
+    pub fn description(&self, tx_version: u32) -> String {
+        if self.is_final() {
+            return format!("{} (final)", self.0);
+        }
+        let rbf = if self.is_rbf() { "RBF" } else { "no RBF" };
+        let relative_lock_time = if tx_version >= 2 {
+            self.relative_lock_time()
+        } else {
+            None
+        };
+        match relative_lock_time {
+            None => format!("{} ({rbf})", self.0),
+            Some(RelativeLockTime::Blocks(blocks)) => {
+                format!("{} ({rbf}, relative lock: {blocks} blocks)", self.0)
+            }
+            Some(RelativeLockTime::Time(units)) => format!(
+                "{} ({rbf}, relative lock: {} seconds)",
+                self.0,
+                u32::from(units) * 512
+            ),
+        }
+    }
+
+    pub fn to_consensus_u32(self) -> u32 {
+        self.0
+    }
+
+    pub fn to_le_bytes(self) -> [u8; 4] {
+        self.0.to_le_bytes()
+    }
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Sequence::MAX
+    }
+}
+
+impl From<u32> for Sequence {
+    fn from(value: u32) -> Self {
+        Sequence(value)
+    }
+}
+
+impl From<Sequence> for u32 {
+    fn from(sequence: Sequence) -> Self {
+        sequence.0
+    }
+}
+
+impl fmt::Display for Sequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::LowerHex for Sequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
@@ -0,0 +1,530 @@
+use crate::witness::TAPROOT_ANNEX_PREFIX;
+use crate::{
+    Amount, BitcoinError, BitcoinTransaction, CompactSize, Hash256, Script, SighashType,
+    TapSighashType, TransactionOutput, opcodes, sha256, sha256d, tagged_hash,
+};
+
+const SIGHASH_NONE: u32 = 0x02;
+const SIGHASH_SINGLE: u32 = 0x03;
+const SIGHASH_ANYONECANPAY: u32 = 0x80;
+
+/// The BIP143 hashes over all inputs and outputs, which every SIGHASH_ALL signature of a
+/// transaction shares. The other sighash types zero or narrow some of them.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct SegwitV0Hashes {
+    pub hash_prevouts: Hash256,
+    pub hash_sequence: Hash256,
+    pub hash_outputs: Hash256,
+}
+
+/// The BIP341 single-SHA256 hashes over all inputs and outputs, shared by every taproot
+/// signature of a transaction that doesn't use ANYONECANPAY.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct TaprootHashes {
+    pub sha_prevouts: Hash256,
+    pub sha_amounts: Hash256,
+    pub sha_script_pubkeys: Hash256,
+    pub sha_sequences: Hash256,
+    pub sha_outputs: Hash256,
+}
+
+impl BitcoinTransaction {
+    /// The pre-segwit signature hash of input `input_index`, signing `script_code` (the
+    /// spent scriptPubKey or redeem script, after any `FindAndDelete`).
+    pub fn legacy_sighash(
+        &self,
+        input_index: usize,
+        script_code: &Script,
+        sighash_type: SighashType,
+    ) -> Result<Hash256, BitcoinError> {
+        self.legacy_sighash_raw(input_index, script_code, sighash_type.to_u32())
+    }
+
+    /// [`BitcoinTransaction::legacy_sighash`] with the 4-byte hash type as consensus reads
+    /// it: only the low 5 bits and the ANYONECANPAY bit select the mode, but all 32 bits are
+    /// committed to.
+    ///
+    /// With SIGHASH_SINGLE and no output at `input_index`, the result is the constant 1
+    /// (`01 00 .. 00`) rather than an error, as in the original client.
+    pub fn legacy_sighash_raw(
+        &self,
+        input_index: usize,
+        script_code: &Script,
+        sighash_type: u32,
+    ) -> Result<Hash256, BitcoinError> {
+        let len = self.inputs.len();
+        if input_index >= len {
+            return Err(BitcoinError::IndexOutOfRange {
+                index: input_index,
+                len,
+            });
+        }
+        let base_type = sighash_type & 0x1f;
+        let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+        if base_type == SIGHASH_SINGLE && input_index >= self.outputs.len() {
+            let mut one = [0u8; 32];
+            one[0] = 1;
+            return Ok(Hash256(one));
+        }
+
+        let mut data = Vec::with_capacity(self.base_size() + script_code.len() + 4);
+        data.extend_from_slice(&self.version.to_le_bytes());
+
+        let signed_inputs = if anyone_can_pay {
+            input_index..input_index + 1
+        } else {
+            0..len
+        };
+        data.extend_from_slice(&CompactSize::new(signed_inputs.len() as u64).to_bytes());
+        for index in signed_inputs {
+            let input = &self.inputs[index];
+            data.extend_from_slice(&input.previous_output.to_bytes());
+            if index == input_index {
+                write_script_code(&mut data, script_code);
+            } else {
+                data.push(0x00);
+            }
+            // NONE and SINGLE let the other inputs be updated.
+            let sequence =
+                if index != input_index && matches!(base_type, SIGHASH_NONE | SIGHASH_SINGLE) {
+                    0
+                } else {
+                    input.sequence.0
+                };
+            data.extend_from_slice(&sequence.to_le_bytes());
+        }
+
+        match base_type {
+            SIGHASH_NONE => data.push(0x00),
+            SIGHASH_SINGLE => {
+                // Outputs before the signed one are blanked: value -1 and an empty script.
+                data.extend_from_slice(&CompactSize::new(input_index as u64 + 1).to_bytes());
+                for _ in 0..input_index {
+                    data.extend_from_slice(&u64::MAX.to_le_bytes());
+                    data.push(0x00);
+                }
+                data.extend_from_slice(&self.outputs[input_index].to_bytes());
+            }
+            _ => {
+                data.extend_from_slice(&CompactSize::new(self.outputs.len() as u64).to_bytes());
+                for output in &self.outputs {
+                    data.extend_from_slice(&output.to_bytes());
+                }
+            }
+        }
+        data.extend_from_slice(&self.lock_time.to_le_bytes());
+        data.extend_from_slice(&sighash_type.to_le_bytes());
+        Ok(sha256d(&data))
+    }
+
+    pub fn segwit_v0_hashes(&self) -> SegwitV0Hashes {
+        let mut prevouts = Vec::with_capacity(36 * self.inputs.len());
+        let mut sequences = Vec::with_capacity(4 * self.inputs.len());
+        for input in &self.inputs {
+            prevouts.extend_from_slice(&input.previous_output.to_bytes());
+            sequences.extend_from_slice(&input.sequence.0.to_le_bytes());
+        }
+        let mut outputs = Vec::new();
+        for output in &self.outputs {
+            outputs.extend_from_slice(&output.to_bytes());
+        }
+        SegwitV0Hashes {
+            hash_prevouts: sha256d(&prevouts),
+            hash_sequence: sha256d(&sequences),
+            hash_outputs: sha256d(&outputs),
+        }
+    }
+
+    /// The BIP143 signature hash of input `input_index`, spending an output worth `value`.
+    /// `script_code` is signed as given; for P2WPKH it is the equivalent P2PKH script.
+    pub fn segwit_v0_sighash(
+        &self,
+        input_index: usize,
+        script_code: &Script,
+        value: Amount,
+        sighash_type: SighashType,
+    ) -> Result<Hash256, BitcoinError> {
+        self.segwit_v0_sighash_raw(input_index, script_code, value, sighash_type.to_u32())
+    }
+
+    /// [`BitcoinTransaction::segwit_v0_sighash`] with the 4-byte hash type as consensus
+    /// reads it, committing to all 32 bits; see [`BitcoinTransaction::legacy_sighash_raw`].
+    pub fn segwit_v0_sighash_raw(
+        &self,
+        input_index: usize,
+        script_code: &Script,
+        value: Amount,
+        sighash_type: u32,
+    ) -> Result<Hash256, BitcoinError> {
+        self.segwit_v0_sighash_with(
+            &self.segwit_v0_hashes(),
+            input_index,
+            script_code,
+            value,
+            sighash_type,
+        )
+    }
+
+    pub(crate) fn segwit_v0_sighash_with(
+        &self,
+        hashes: &SegwitV0Hashes,
+        input_index: usize,
+        script_code: &Script,
+        value: Amount,
+        sighash_type: u32,
+    ) -> Result<Hash256, BitcoinError> {
+        let len = self.inputs.len();
+        let input = self
+            .inputs
+            .get(input_index)
+            .ok_or(BitcoinError::IndexOutOfRange {
+                index: input_index,
+                len,
+            })?;
+        let base_type = sighash_type & 0x1f;
+        let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+
+        let hash_prevouts = if anyone_can_pay {
+            Hash256::ZERO
+        } else {
+            hashes.hash_prevouts
+        };
+        let hash_sequence = if anyone_can_pay || matches!(base_type, SIGHASH_NONE | SIGHASH_SINGLE)
+        {
+            Hash256::ZERO
+        } else {
+            hashes.hash_sequence
+        };
+        let hash_outputs = match base_type {
+            SIGHASH_NONE => Hash256::ZERO,
+            SIGHASH_SINGLE => self
+                .outputs
+                .get(input_index)
+                .map_or(Hash256::ZERO, |output| sha256d(&output.to_bytes())),
+            _ => hashes.hash_outputs,
+        };
+
+        let mut data = Vec::with_capacity(4 + 32 * 3 + 36 + 9 + script_code.len() + 8 + 4 + 8);
+        data.extend_from_slice(&self.version.to_le_bytes());
+        data.extend_from_slice(&hash_prevouts.0);
+        data.extend_from_slice(&hash_sequence.0);
+        data.extend_from_slice(&input.previous_output.to_bytes());
+        data.extend_from_slice(&script_code.to_bytes());
+        data.extend_from_slice(&value.to_sat().to_le_bytes());
+        data.extend_from_slice(&input.sequence.0.to_le_bytes());
+        data.extend_from_slice(&hash_outputs.0);
+        data.extend_from_slice(&self.lock_time.to_le_bytes());
+        data.extend_from_slice(&sighash_type.to_le_bytes());
+        Ok(sha256d(&data))
+    }
+
+    /// Fails with `PrevoutsLengthMismatch` unless `prevouts` has one output per input.
+    pub fn taproot_hashes(
+        &self,
+        prevouts: &[TransactionOutput],
+    ) -> Result<TaprootHashes, BitcoinError> {
+        if prevouts.len() != self.inputs.len() {
+            return Err(BitcoinError::PrevoutsLengthMismatch {
+                inputs: self.inputs.len(),
+                prevouts: prevouts.len(),
+            });
+        }
+        let mut outpoints = Vec::with_capacity(36 * self.inputs.len());
+        let mut sequences = Vec::with_capacity(4 * self.inputs.len());
+        for input in &self.inputs {
+            outpoints.extend_from_slice(&input.previous_output.to_bytes());
+            sequences.extend_from_slice(&input.sequence.0.to_le_bytes());
+        }
+        let mut amounts = Vec::with_capacity(8 * prevouts.len());
+        let mut script_pubkeys = Vec::new();
+        for prevout in prevouts {
+            amounts.extend_from_slice(&prevout.value.to_sat().to_le_bytes());
+            script_pubkeys.extend_from_slice(&prevout.script_pubkey.to_bytes());
+        }
+        let mut outputs = Vec::new();
+        for output in &self.outputs {
+            outputs.extend_from_slice(&output.to_bytes());
+        }
+        Ok(TaprootHashes {
+            sha_prevouts: sha256(&outpoints),
+            sha_amounts: sha256(&amounts),
+            sha_script_pubkeys: sha256(&script_pubkeys),
+            sha_sequences: sha256(&sequences),
+            sha_outputs: sha256(&outputs),
+        })
+    }
+
+    /// The BIP341 signature hash of input `input_index`, given the outputs spent by every
+    /// input. A `leaf_hash` makes it a BIP342 script path hash, with no OP_CODESEPARATOR
+    /// executed; `annex` includes its 0x50 prefix.
+    pub fn taproot_sighash(
+        &self,
+        input_index: usize,
+        prevouts: &[TransactionOutput],
+        sighash_type: TapSighashType,
+        leaf_hash: Option<Hash256>,
+        annex: Option<&[u8]>,
+    ) -> Result<Hash256, BitcoinError> {
+        let hashes = self.taproot_hashes(prevouts)?;
+        self.taproot_sighash_with(
+            &hashes,
+            input_index,
+            prevouts,
+            sighash_type,
+            leaf_hash,
+            annex,
+        )
+    }
+
+    pub(crate) fn taproot_sighash_with(
+        &self,
+        hashes: &TaprootHashes,
+        input_index: usize,
+        prevouts: &[TransactionOutput],
+        sighash_type: TapSighashType,
+        leaf_hash: Option<Hash256>,
+        annex: Option<&[u8]>,
+    ) -> Result<Hash256, BitcoinError> {
+        let len = self.inputs.len();
+        if prevouts.len() != len {
+            return Err(BitcoinError::PrevoutsLengthMismatch {
+                inputs: len,
+                prevouts: prevouts.len(),
+            });
+        }
+        let input = self
+            .inputs
+            .get(input_index)
+            .ok_or(BitcoinError::IndexOutOfRange {
+                index: input_index,
+                len,
+            })?;
+        if annex.is_some_and(|annex| annex.first() != Some(&TAPROOT_ANNEX_PREFIX)) {
+            return Err(BitcoinError::InvalidAnnex);
+        }
+        let base_type = u32::from(sighash_type.to_u8()) & 0x1f;
+        let anyone_can_pay = sighash_type.is_anyone_can_pay();
+
+        // The epoch, then SigMsg.
+        let mut data = Vec::with_capacity(1 + 1 + 8 + 32 * 5 + 1 + 4 + 32 * 3 + 1 + 4);
+        data.push(0x00);
+        data.push(sighash_type.to_u8());
+        data.extend_from_slice(&self.version.to_le_bytes());
+        data.extend_from_slice(&self.lock_time.to_le_bytes());
+        if !anyone_can_pay {
+            data.extend_from_slice(&hashes.sha_prevouts.0);
+            data.extend_from_slice(&hashes.sha_amounts.0);
+            data.extend_from_slice(&hashes.sha_script_pubkeys.0);
+            data.extend_from_slice(&hashes.sha_sequences.0);
+        }
+        if !matches!(base_type, SIGHASH_NONE | SIGHASH_SINGLE) {
+            data.extend_from_slice(&hashes.sha_outputs.0);
+        }
+
+        let ext_flag = u8::from(leaf_hash.is_some());
+        data.push(ext_flag * 2 + u8::from(annex.is_some()));
+        if anyone_can_pay {
+            let prevout = &prevouts[input_index];
+            data.extend_from_slice(&input.previous_output.to_bytes());
+            data.extend_from_slice(&prevout.value.to_sat().to_le_bytes());
+            data.extend_from_slice(&prevout.script_pubkey.to_bytes());
+            data.extend_from_slice(&input.sequence.0.to_le_bytes());
+        } else {
+            data.extend_from_slice(&(input_index as u32).to_le_bytes());
+        }
+        if let Some(annex) = annex {
+            let len = CompactSize::try_from(annex.len()).expect("annex length fits in u64");
+            let mut serialized = len.to_bytes();
+            serialized.extend_from_slice(annex);
+            data.extend_from_slice(&sha256(&serialized).0);
+        }
+        if base_type == SIGHASH_SINGLE {
+            let output = self
+                .outputs
+                .get(input_index)
+                .ok_or(BitcoinError::SighashSingleWithoutOutput { index: input_index })?;
+            data.extend_from_slice(&sha256(&output.to_bytes()).0);
+        }
+
+        if let Some(leaf_hash) = leaf_hash {
+            // The key version, then the position of the last executed OP_CODESEPARATOR.
+            data.extend_from_slice(&leaf_hash.0);
+            data.push(0x00);
+            data.extend_from_slice(&u32::MAX.to_le_bytes());
+        }
+        Ok(Hash256(tagged_hash("TapSighash", &data)))
+    }
+}
+
+/// Signature hashes for the inputs of one transaction, computing the BIP143 and BIP341
+/// hashes over all inputs and outputs once instead of once per input.
+///
+/// The taproot hashes are taken from the `prevouts` of the first taproot call, so later
+/// calls must pass the same outputs.
+#[derive(Debug, Clone)]
+pub struct SighashCache<'a> {
+    tx: &'a BitcoinTransaction,
+    segwit_v0: Option<SegwitV0Hashes>,
+    taproot: Option<TaprootHashes>,
+}
+
+impl<'a> SighashCache<'a> {
+    pub fn new(tx: &'a BitcoinTransaction) -> Self {
+        SighashCache {
+            tx,
+            segwit_v0: None,
+            taproot: None,
+        }
+    }
+
+    pub fn transaction(&self) -> &'a BitcoinTransaction {
+        self.tx
+    }
+
+    pub fn segwit_v0_hashes(&mut self) -> &SegwitV0Hashes {
+        let tx = self.tx;
+        self.segwit_v0.get_or_insert_with(|| tx.segwit_v0_hashes())
+    }
+
+    pub fn taproot_hashes(
+        &mut self,
+        prevouts: &[TransactionOutput],
+    ) -> Result<&TaprootHashes, BitcoinError> {
+        if self.taproot.is_none() {
+            self.taproot = Some(self.tx.taproot_hashes(prevouts)?);
+        }
+        Ok(self.taproot.as_ref().expect("just computed"))
+    }
+
+    /// Legacy hashes share nothing between inputs; this is
+    /// [`BitcoinTransaction::legacy_sighash`].
+    pub fn legacy_sighash(
+        &self,
+        input_index: usize,
+        script_code: &Script,
+        sighash_type: SighashType,
+    ) -> Result<Hash256, BitcoinError> {
+        self.tx
+            .legacy_sighash(input_index, script_code, sighash_type)
+    }
+
+    pub fn segwit_v0_sighash(
+        &mut self,
+        input_index: usize,
+        script_code: &Script,
+        value: Amount,
+        sighash_type: SighashType,
+    ) -> Result<Hash256, BitcoinError> {
+        let tx = self.tx;
+        let hashes = *self.segwit_v0_hashes();
+        tx.segwit_v0_sighash_with(
+            &hashes,
+            input_index,
+            script_code,
+            value,
+            sighash_type.to_u32(),
+        )
+    }
+
+    /// The segwit v0 hash for spending the P2WPKH `script_pubkey`, with the script code
+    /// derived from it. Fails with `UnsupportedScript` for any other script.
+    pub fn p2wpkh_signature_hash(
+        &mut self,
+        input_index: usize,
+        script_pubkey: &Script,
+        value: Amount,
+        sighash_type: SighashType,
+    ) -> Result<Hash256, BitcoinError> {
+        let script_code = script_pubkey
+            .p2wpkh_script_code()
+            .ok_or(BitcoinError::UnsupportedScript)?;
+        self.segwit_v0_sighash(input_index, &script_code, value, sighash_type)
+    }
+
+    /// [`SighashCache::p2wpkh_signature_hash`] for a P2SH-wrapped spend, where
+    /// `redeem_script` is the P2WPKH program the P2SH output commits to.
+    pub fn p2sh_p2wpkh_signature_hash(
+        &mut self,
+        input_index: usize,
+        redeem_script: &Script,
+        value: Amount,
+        sighash_type: SighashType,
+    ) -> Result<Hash256, BitcoinError> {
+        self.p2wpkh_signature_hash(input_index, redeem_script, value, sighash_type)
+    }
+
+    pub fn taproot_sighash(
+        &mut self,
+        input_index: usize,
+        prevouts: &[TransactionOutput],
+        sighash_type: TapSighashType,
+        leaf_hash: Option<Hash256>,
+        annex: Option<&[u8]>,
+    ) -> Result<Hash256, BitcoinError> {
+        let tx = self.tx;
+        let hashes = *self.taproot_hashes(prevouts)?;
+        tx.taproot_sighash_with(
+            &hashes,
+            input_index,
+            prevouts,
+            sighash_type,
+            leaf_hash,
+            annex,
+        )
+    }
+}
+
+// Core's `SerializeScriptCode`: the script with its OP_CODESEPARATORs removed, which for a
+// malformed script also drops the data of a truncated final push.
+fn write_script_code(out: &mut Vec<u8>, script_code: &Script) {
+    let script = script_code.as_bytes();
+    let separator = opcodes::OP_CODESEPARATOR.to_u8();
+    let mut pc = 0;
+    let mut separators = 0;
+    while let Some(opcode) = next_opcode(script, &mut pc) {
+        if opcode == separator {
+            separators += 1;
+        }
+    }
+    out.extend_from_slice(&CompactSize::new((script.len() - separators) as u64).to_bytes());
+
+    let (mut pc, mut begin) = (0, 0);
+    while let Some(opcode) = next_opcode(script, &mut pc) {
+        if opcode == separator {
+            out.extend_from_slice(&script[begin..pc - 1]);
+            begin = pc;
+        }
+    }
+    if begin != script.len() {
+        out.extend_from_slice(&script[begin..pc]);
+    }
+}
+
+// Core's `GetScriptOp`, including where it leaves `pc` on failure: past the opcode and any
+// complete length bytes of a truncated push.
+fn next_opcode(script: &[u8], pc: &mut usize) -> Option<u8> {
+    let &opcode = script.get(*pc)?;
+    *pc += 1;
+    let width = match opcode {
+        0x4c => 1,
+        0x4d => 2,
+        0x4e => 4,
+        _ if opcode < 0x4c => {
+            return advance(script, pc, opcode as usize).then_some(opcode);
+        }
+        _ => return Some(opcode),
+    };
+    let len_bytes = script.get(*pc..*pc + width)?;
+    let mut le = [0u8; 4];
+    le[..width].copy_from_slice(len_bytes);
+    *pc += width;
+    advance(script, pc, u32::from_le_bytes(le) as usize).then_some(opcode)
+}
+
+fn advance(script: &[u8], pc: &mut usize, len: usize) -> bool {
+    if script.len() - *pc < len {
+        return false;
+    }
+    *pc += len;
+    true
+}
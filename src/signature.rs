@@ -0,0 +1,326 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::BitcoinError;
+
+/// The sighash flag appended to a legacy or segwit v0 signature.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SighashType {
+    All,
+    None,
+    Single,
+    AllPlusAnyoneCanPay,
+    NonePlusAnyoneCanPay,
+    SinglePlusAnyoneCanPay,
+}
+
+impl SighashType {
+    pub const ANYONECANPAY: u8 = 0x80;
+
+    /// Parses one of the six defined flag bytes.
+    pub fn from_u8(byte: u8) -> Result<Self, BitcoinError> {
+        Ok(match byte {
+            0x01 => SighashType::All,
+            0x02 => SighashType::None,
+            0x03 => SighashType::Single,
+            0x81 => SighashType::AllPlusAnyoneCanPay,
+            0x82 => SighashType::NonePlusAnyoneCanPay,
+            0x83 => SighashType::SinglePlusAnyoneCanPay,
+            _ => return Err(BitcoinError::InvalidSighashType(byte)),
+        })
+    }
+
+    /// The type consensus signs with for any 32-bit value: bits other than the low five
+    /// and ANYONECANPAY are ignored, and an undefined base type counts as ALL. The original
+    /// value is still what a signature commits to; see
+    /// [`BitcoinTransaction::legacy_sighash_raw`](crate::BitcoinTransaction::legacy_sighash_raw).
+    pub fn from_u32(n: u32) -> Self {
+        let anyone_can_pay = n & u32::from(SighashType::ANYONECANPAY) != 0;
+        match (n & 0x1f, anyone_can_pay) {
+            (0x02, false) => SighashType::None,
+            (0x03, false) => SighashType::Single,
+            (0x02, true) => SighashType::NonePlusAnyoneCanPay,
+            (0x03, true) => SighashType::SinglePlusAnyoneCanPay,
+            (_, false) => SighashType::All,
+            (_, true) => SighashType::AllPlusAnyoneCanPay,
+        }
+    }
+
+    /// Accepts only the six values standardness policy allows.
+    pub fn from_standard_u32(n: u32) -> Result<Self, BitcoinError> {
+        u8::try_from(n)
+            .ok()
+            .and_then(|byte| SighashType::from_u8(byte).ok())
+            .ok_or(BitcoinError::NonStandardSighashType(n))
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            SighashType::All => 0x01,
+            SighashType::None => 0x02,
+            SighashType::Single => 0x03,
+            SighashType::AllPlusAnyoneCanPay => 0x81,
+            SighashType::NonePlusAnyoneCanPay => 0x82,
+            SighashType::SinglePlusAnyoneCanPay => 0x83,
+        }
+    }
+
+    pub fn to_u32(self) -> u32 {
+        u32::from(self.to_u8())
+    }
+
+    pub fn is_anyone_can_pay(self) -> bool {
+        self.to_u8() & SighashType::ANYONECANPAY != 0
+    }
+}
+
+impl fmt::Display for SighashType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SighashType::All => "SIGHASH_ALL",
+            SighashType::None => "SIGHASH_NONE",
+            SighashType::Single => "SIGHASH_SINGLE",
+            SighashType::AllPlusAnyoneCanPay => "SIGHASH_ALL|ANYONECANPAY",
+            SighashType::NonePlusAnyoneCanPay => "SIGHASH_NONE|ANYONECANPAY",
+            SighashType::SinglePlusAnyoneCanPay => "SIGHASH_SINGLE|ANYONECANPAY",
+        })
+    }
+}
+
+impl FromStr for SighashType {
+    type Err = BitcoinError;
+
+    /// Parses the `Display` form.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "SIGHASH_ALL" => SighashType::All,
+            "SIGHASH_NONE" => SighashType::None,
+            "SIGHASH_SINGLE" => SighashType::Single,
+            "SIGHASH_ALL|ANYONECANPAY" => SighashType::AllPlusAnyoneCanPay,
+            "SIGHASH_NONE|ANYONECANPAY" => SighashType::NonePlusAnyoneCanPay,
+            "SIGHASH_SINGLE|ANYONECANPAY" => SighashType::SinglePlusAnyoneCanPay,
+            _ => return Err(BitcoinError::InvalidFormat),
+        })
+    }
+}
+
+/// The sighash flag of a taproot signature. `Default` is the missing flag byte of a 64-byte
+/// signature: it signs the same data as `All`, but commits to 0x00 instead of 0x01.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum TapSighashType {
+    #[default]
+    Default,
+    All,
+    None,
+    Single,
+    AllPlusAnyoneCanPay,
+    NonePlusAnyoneCanPay,
+    SinglePlusAnyoneCanPay,
+}
+
+impl TapSighashType {
+    /// Parses one of the seven defined flag bytes; taproot has no masking.
+    pub fn from_u8(byte: u8) -> Result<Self, BitcoinError> {
+        Ok(match byte {
+            0x00 => TapSighashType::Default,
+            _ => SighashType::from_u8(byte)?.into(),
+        })
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            TapSighashType::Default => 0x00,
+            TapSighashType::All => 0x01,
+            TapSighashType::None => 0x02,
+            TapSighashType::Single => 0x03,
+            TapSighashType::AllPlusAnyoneCanPay => 0x81,
+            TapSighashType::NonePlusAnyoneCanPay => 0x82,
+            TapSighashType::SinglePlusAnyoneCanPay => 0x83,
+        }
+    }
+
+    pub fn is_anyone_can_pay(self) -> bool {
+        self.to_u8() & SighashType::ANYONECANPAY != 0
+    }
+}
+
+impl From<SighashType> for TapSighashType {
+    fn from(sighash_type: SighashType) -> Self {
+        match sighash_type {
+            SighashType::All => TapSighashType::All,
+            SighashType::None => TapSighashType::None,
+            SighashType::Single => TapSighashType::Single,
+            SighashType::AllPlusAnyoneCanPay => TapSighashType::AllPlusAnyoneCanPay,
+            SighashType::NonePlusAnyoneCanPay => TapSighashType::NonePlusAnyoneCanPay,
+            SighashType::SinglePlusAnyoneCanPay => TapSighashType::SinglePlusAnyoneCanPay,
+        }
+    }
+}
+
+impl fmt::Display for TapSighashType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TapSighashType::Default => f.write_str("SIGHASH_DEFAULT"),
+            _ => SighashType::from_u8(self.to_u8())
+                .expect("non-default types are legacy types")
+                .fmt(f),
+        }
+    }
+}
+
+impl FromStr for TapSighashType {
+    type Err = BitcoinError;
+
+    /// Parses the `Display` form.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "SIGHASH_DEFAULT" => Ok(TapSighashType::Default),
+            _ => s.parse::<SighashType>().map(TapSighashType::from),
+        }
+    }
+}
+
+// The `Display` name in human-readable formats, and the flag byte in binary ones.
+macro_rules! impl_sighash_serde {
+    ($name:ident, $expecting:expr) => {
+        impl Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                if serializer.is_human_readable() {
+                    serializer.collect_str(self)
+                } else {
+                    serializer.serialize_u8(self.to_u8())
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                use serde::de::Error;
+
+                if deserializer.is_human_readable() {
+                    let s = String::deserialize(deserializer)?;
+                    s.parse().map_err(|_| {
+                        D::Error::custom(format!(concat!("invalid ", $expecting, ": {:?}"), s))
+                    })
+                } else {
+                    let byte = u8::deserialize(deserializer)?;
+                    $name::from_u8(byte).map_err(|_| {
+                        D::Error::custom(format!(
+                            concat!("invalid ", $expecting, " byte: {:#04x}"),
+                            byte
+                        ))
+                    })
+                }
+            }
+        }
+    };
+}
+
+impl_sighash_serde!(SighashType, "sighash type");
+impl_sighash_serde!(TapSighashType, "taproot sighash type");
+
+// secp256k1 group order divided by two; signatures with a larger s are malleable.
+const HALF_ORDER: [u8; 32] = [
+    0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0x5D, 0x57, 0x6E, 0x73, 0x57, 0xA4, 0x50, 0x1D, 0xDF, 0xE9, 0x2F, 0x46, 0x68, 0x1B, 0x20, 0xA0,
+];
+
+/// A DER-encoded ECDSA signature followed by its sighash byte, as found in scriptSigs and
+/// witnesses. Only the encoding is checked; nothing here touches the curve.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Signature {
+    // DER encoding followed by the sighash byte.
+    bytes: Vec<u8>,
+    r: std::ops::Range<usize>,
+    s: std::ops::Range<usize>,
+}
+
+// Reads one DER INTEGER at `offset`, returning the range of its content bytes.
+fn der_integer(der: &[u8], offset: usize) -> Result<std::ops::Range<usize>, BitcoinError> {
+    let (&marker, rest) = der[offset..]
+        .split_first()
+        .ok_or(BitcoinError::InvalidSignature)?;
+    let (&len, rest) = rest.split_first().ok_or(BitcoinError::InvalidSignature)?;
+    let len = len as usize;
+    if marker != 0x02 || len == 0 || len > rest.len() {
+        return Err(BitcoinError::InvalidSignature);
+    }
+    // A set top bit makes a DER integer negative, which r and s never are.
+    if rest[0] & 0x80 != 0 {
+        return Err(BitcoinError::InvalidSignature);
+    }
+    Ok(offset + 2..offset + 2 + len)
+}
+
+impl Signature {
+    /// Parses `DER || sighash`. The DER must be a SEQUENCE of two positive INTEGERs whose
+    /// lengths add up exactly; zero padding is tolerated here and checked by
+    /// [`Signature::is_strict_der`]. Any sighash byte is accepted, as consensus does.
+    pub fn from_der_with_sighash(bytes: &[u8]) -> Result<Self, BitcoinError> {
+        let (_, der) = bytes.split_last().ok_or(BitcoinError::InvalidSignature)?;
+        if der.len() < 8 || der[0] != 0x30 || der[1] as usize != der.len() - 2 {
+            return Err(BitcoinError::InvalidSignature);
+        }
+        let r = der_integer(der, 2)?;
+        let s = der_integer(der, r.end)?;
+        if s.end != der.len() {
+            return Err(BitcoinError::InvalidSignature);
+        }
+        Ok(Signature {
+            bytes: bytes.to_vec(),
+            r,
+            s,
+        })
+    }
+
+    pub fn r(&self) -> &[u8] {
+        &self.bytes[self.r.clone()]
+    }
+
+    pub fn s(&self) -> &[u8] {
+        &self.bytes[self.s.clone()]
+    }
+
+    /// The trailing byte as written, which is what the signature hash commits to.
+    pub fn sighash_byte(&self) -> u8 {
+        self.bytes[self.bytes.len() - 1]
+    }
+
+    /// The type consensus signs with for [`Signature::sighash_byte`]; see
+    /// [`SighashType::from_u32`].
+    pub fn sighash_type(&self) -> SighashType {
+        SighashType::from_u32(u32::from(self.sighash_byte()))
+    }
+
+    /// The DER encoding without the sighash byte.
+    pub fn der(&self) -> &[u8] {
+        &self.bytes[..self.bytes.len() - 1]
+    }
+
+    /// The DER encoding followed by the sighash byte.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.bytes.clone()
+    }
+
+    /// True if `s` is at most half the curve order, as required by standardness (BIP146).
+    pub fn is_low_s(&self) -> bool {
+        let s = self.s();
+        let s = &s[s.iter().take_while(|&&byte| byte == 0).count()..];
+        s.len() < 32 || (s.len() == 32 && s <= &HALF_ORDER[..])
+    }
+
+    /// BIP66 strict DER: at most 72 bytes of DER and no unnecessary zero padding on either
+    /// integer.
+    pub fn is_strict_der(&self) -> bool {
+        let minimal = |int: &[u8]| int.len() == 1 || int[0] != 0 || int[1] & 0x80 != 0;
+        self.der().len() <= 72 && minimal(self.r()) && minimal(self.s())
+    }
+}
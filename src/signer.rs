@@ -0,0 +1,48 @@
+use crate::{
+    Amount, BitcoinError, BitcoinTransaction, PrivateKey, Script, ScriptBuilder, SighashType,
+    Witness,
+};
+
+/// Signs input `index` as a spend of the P2PKH output of `key`, replacing its scriptSig with
+/// `<signature> <public key>`. Nothing checks that the input really spends that output; see
+/// [`BitcoinTransaction::verify_input`].
+pub fn sign_input_p2pkh(
+    tx: &mut BitcoinTransaction,
+    index: usize,
+    key: &PrivateKey,
+    sighash_type: SighashType,
+) -> Result<(), BitcoinError> {
+    let pubkey = key.public_key()?;
+    let script_code = Script::new_p2pkh_from_key(&pubkey);
+    let sighash = tx.legacy_sighash(index, &script_code, sighash_type)?;
+    let signature = key.sign_ecdsa(&sighash.0, sighash_type)?;
+    tx.inputs[index].script_sig = ScriptBuilder::new()
+        .push_slice(&signature.to_bytes())
+        .push_slice(pubkey.as_bytes())
+        .into_script();
+    Ok(())
+}
+
+/// Signs input `index` as a spend of the P2WPKH output of `key` worth `value`, replacing its
+/// witness with `[signature, public key]` and emptying its scriptSig. Fails with
+/// `UncompressedKey` for keys segwit cannot use.
+pub fn sign_input_p2wpkh(
+    tx: &mut BitcoinTransaction,
+    index: usize,
+    key: &PrivateKey,
+    value: Amount,
+    sighash_type: SighashType,
+) -> Result<(), BitcoinError> {
+    let pubkey = key.public_key()?;
+    let script_code = Script::new_p2wpkh_from_key(&pubkey)?
+        .p2wpkh_script_code()
+        .expect("built as P2WPKH");
+    let sighash = tx.segwit_v0_sighash(index, &script_code, value, sighash_type)?;
+    let signature = key.sign_ecdsa(&sighash.0, sighash_type)?;
+    let input = &mut tx.inputs[index];
+    input.script_sig = Script::new(vec![]);
+    input.witness = Witness::new();
+    input.witness.push(signature.to_bytes());
+    input.witness.push(pubkey.as_bytes());
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use crate::{BitcoinError, CompactSize, Hash256, Script, XOnlyPublicKey, tagged_hash};
+
+/// The BIP342 tapscript leaf version.
+pub const TAPROOT_LEAF_TAPSCRIPT: u8 = 0xc0;
+
+const TAPROOT_CONTROL_BASE_SIZE: usize = 33;
+const TAPROOT_CONTROL_NODE_SIZE: usize = 32;
+const TAPROOT_CONTROL_MAX_NODE_COUNT: usize = 128;
+
+/// The last witness element of a taproot script-path spend.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ControlBlock {
+    leaf_version: u8,
+    output_key_parity: u8,
+    internal_key: XOnlyPublicKey,
+    merkle_branch: Vec<Hash256>,
+}
+
+impl ControlBlock {
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, BitcoinError> {
+        let (base, path) = bytes
+            .split_at_checked(TAPROOT_CONTROL_BASE_SIZE)
+            .ok_or(BitcoinError::InvalidFormat)?;
+        if path.len() % TAPROOT_CONTROL_NODE_SIZE != 0
+            || path.len() / TAPROOT_CONTROL_NODE_SIZE > TAPROOT_CONTROL_MAX_NODE_COUNT
+        {
+            return Err(BitcoinError::InvalidFormat);
+        }
+        let merkle_branch = path
+            .chunks_exact(TAPROOT_CONTROL_NODE_SIZE)
+            .map(|node| Hash256(node.try_into().expect("chunk is 32 bytes")))
+            .collect();
+        Ok(ControlBlock {
+            leaf_version: base[0] & 0xfe,
+            output_key_parity: base[0] & 0x01,
+            internal_key: XOnlyPublicKey::from_slice(&base[1..])?,
+            merkle_branch,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            TAPROOT_CONTROL_BASE_SIZE + TAPROOT_CONTROL_NODE_SIZE * self.merkle_branch.len(),
+        );
+        bytes.push(self.leaf_version | self.output_key_parity);
+        bytes.extend_from_slice(self.internal_key.as_bytes());
+        for node in &self.merkle_branch {
+            bytes.extend_from_slice(node.as_bytes());
+        }
+        bytes
+    }
+
+    pub fn leaf_version(&self) -> u8 {
+        self.leaf_version
+    }
+
+    /// 1 if the output key has an odd Y coordinate.
+    pub fn output_key_parity(&self) -> u8 {
+        self.output_key_parity
+    }
+
+    pub fn internal_key(&self) -> XOnlyPublicKey {
+        self.internal_key
+    }
+
+    /// Sibling hashes from the leaf up to the root.
+    pub fn merkle_branch(&self) -> &[Hash256] {
+        &self.merkle_branch
+    }
+
+    /// The script tree root committed to by the output key, assuming `leaf_script` is the
+    /// leaf this control block opens.
+    pub fn merkle_root(&self, leaf_script: &Script) -> Hash256 {
+        self.merkle_branch.iter().fold(
+            leaf_script.tap_leaf_hash(self.leaf_version),
+            |node, sibling| tap_branch_hash(&node, sibling),
+        )
+    }
+}
+
+impl TryFrom<&[u8]> for ControlBlock {
+    type Error = BitcoinError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        ControlBlock::from_slice(bytes)
+    }
+}
+
+// Children are hashed in lexicographic order, so the path needs no left/right bits.
+fn tap_branch_hash(a: &Hash256, b: &Hash256) -> Hash256 {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left.as_bytes());
+    data[32..].copy_from_slice(right.as_bytes());
+    Hash256(tagged_hash("TapBranch", &data))
+}
+
+impl Script {
+    /// `TapLeaf(leaf_version || CompactSize(len) || script)`.
+    pub fn tap_leaf_hash(&self, leaf_version: u8) -> Hash256 {
+        let len = CompactSize::try_from(self.len()).expect("script length fits in u64");
+        let mut data = Vec::with_capacity(1 + 9 + self.len());
+        data.push(leaf_version);
+        data.extend_from_slice(&len.to_bytes());
+        data.extend_from_slice(self);
+        Hash256(tagged_hash("TapLeaf", &data))
+    }
+}
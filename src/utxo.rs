@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use crate::{Amount, BitcoinError, BitcoinTransaction, OutPoint, TransactionOutput};
+
+/// Unspent outputs by the outpoint that created them, for replaying transactions.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct UtxoSet {
+    outputs: HashMap<OutPoint, TransactionOutput>,
+}
+
+impl UtxoSet {
+    pub fn new() -> Self {
+        UtxoSet::default()
+    }
+
+    /// Adds every output of `tx`, replacing any already stored under the same outpoint.
+    pub fn insert_tx(&mut self, tx: &BitcoinTransaction) {
+        let txid = tx.txid();
+        for (vout, output) in tx.outputs.iter().enumerate() {
+            self.outputs.insert(
+                OutPoint::from_txid(txid.clone(), vout as u32),
+                output.clone(),
+            );
+        }
+    }
+
+    /// Removes and returns the output, or `None` if it is not in the set: never created,
+    /// or already spent.
+    pub fn spend(&mut self, outpoint: &OutPoint) -> Option<TransactionOutput> {
+        self.outputs.remove(outpoint)
+    }
+
+    /// Spends every input of `tx`, then adds its outputs. Fails with `DuplicateInput` if
+    /// two inputs spend the same outpoint, or `MissingPrevout` for the first input not in
+    /// the set, leaving the set unchanged either way.
+    pub fn apply_tx(&mut self, tx: &BitcoinTransaction) -> Result<(), BitcoinError> {
+        if !tx.is_coinbase() {
+            let mut seen = HashMap::with_capacity(tx.inputs.len());
+            for (index, input) in tx.inputs.iter().enumerate() {
+                if let Some(&first) = seen.get(&input.previous_output) {
+                    return Err(BitcoinError::DuplicateInput { index, first });
+                }
+                if !self.contains(&input.previous_output) {
+                    return Err(BitcoinError::MissingPrevout(input.previous_output.clone()));
+                }
+                seen.insert(&input.previous_output, index);
+            }
+            for input in &tx.inputs {
+                self.spend(&input.previous_output);
+            }
+        }
+        self.insert_tx(tx);
+        Ok(())
+    }
+
+    pub fn contains(&self, outpoint: &OutPoint) -> bool {
+        self.outputs.contains_key(outpoint)
+    }
+
+    pub fn get(&self, outpoint: &OutPoint) -> Option<&TransactionOutput> {
+        self.outputs.get(outpoint)
+    }
+
+    pub fn len(&self) -> usize {
+        self.outputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.outputs.is_empty()
+    }
+
+    /// Errors if the sum exceeds `MAX_MONEY`.
+    pub fn total_value(&self) -> Result<Amount, BitcoinError> {
+        self.outputs
+            .values()
+            .try_fold(Amount::ZERO, |sum, output| sum.checked_add(output.value))
+    }
+}
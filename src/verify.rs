@@ -0,0 +1,254 @@
+use crate::{BitcoinError, BitcoinTransaction, ScriptType, TransactionOutput};
+#[cfg(feature = "secp256k1")]
+use crate::{Instruction, PublicKey, Script, Signature, TapSighashType, TransactionInput};
+
+// Script verification flags, numbered as in libbitcoinconsensus.
+pub const VERIFY_NONE: u32 = 0;
+pub const VERIFY_P2SH: u32 = 1 << 0;
+pub const VERIFY_DERSIG: u32 = 1 << 2;
+pub const VERIFY_NULLDUMMY: u32 = 1 << 4;
+pub const VERIFY_CHECKLOCKTIMEVERIFY: u32 = 1 << 9;
+pub const VERIFY_CHECKSEQUENCEVERIFY: u32 = 1 << 10;
+pub const VERIFY_WITNESS: u32 = 1 << 11;
+pub const VERIFY_TAPROOT: u32 = 1 << 17;
+pub const VERIFY_ALL: u32 = VERIFY_P2SH
+    | VERIFY_DERSIG
+    | VERIFY_NULLDUMMY
+    | VERIFY_CHECKLOCKTIMEVERIFY
+    | VERIFY_CHECKSEQUENCEVERIFY
+    | VERIFY_WITNESS
+    | VERIFY_TAPROOT;
+
+/// Why an input's signature was not accepted by [`BitcoinTransaction::verify_input`].
+///
+/// [`BitcoinTransaction::verify_input`]: crate::BitcoinTransaction::verify_input
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum VerifyError {
+    IndexOutOfRange {
+        index: usize,
+        len: usize,
+    },
+    /// The spent output is not a type this crate can check, or is a taproot output spent
+    /// through a script path.
+    UnsupportedScriptType(ScriptType),
+    /// A taproot signature commits to every spent output, and only one was given.
+    MissingPrevouts,
+    /// The scriptSig or witness does not have the shape the output type requires.
+    MalformedSpend,
+    InvalidSignatureEncoding,
+    /// The signature is not strict DER (BIP66).
+    NonStrictDer,
+    /// The signature's S value is in the upper half of the curve order.
+    HighS,
+    InvalidPublicKey,
+    /// The public key does not hash to the one the output commits to.
+    KeyMismatch,
+    /// The signature is well formed but does not verify.
+    InvalidSignature,
+    /// The signature hash could not be computed.
+    Sighash(BitcoinError),
+    /// A [`ScriptVerifier`] rejected the spend of input `index`.
+    ScriptFailed {
+        index: usize,
+    },
+    /// The library behind a [`ScriptVerifier`] failed with this error code before
+    /// evaluating the script.
+    Library(i32),
+}
+
+/// Full script interpretation supplied from outside the crate, such as libbitcoinconsensus,
+/// for what [`BitcoinTransaction::verify_input`] doesn't cover.
+///
+/// `tx_bytes` is the serialized spending transaction and `flags` a combination of the
+/// `VERIFY_*` constants.
+pub trait ScriptVerifier {
+    fn verify(
+        &self,
+        script_pubkey: &[u8],
+        amount: u64,
+        tx_bytes: &[u8],
+        input_index: usize,
+        flags: u32,
+    ) -> Result<(), VerifyError>;
+}
+
+impl BitcoinTransaction {
+    /// Runs `verifier` with `VERIFY_ALL` over every input, where `prevouts[i]` is the output
+    /// spent by input `i`, stopping at the first failure.
+    pub fn verify_with(
+        &self,
+        verifier: &dyn ScriptVerifier,
+        prevouts: &[TransactionOutput],
+    ) -> Result<(), VerifyError> {
+        if prevouts.len() != self.inputs.len() {
+            return Err(VerifyError::MissingPrevouts);
+        }
+        let tx_bytes = self.to_bytes();
+        for (index, prevout) in prevouts.iter().enumerate() {
+            verifier.verify(
+                prevout.script_pubkey.as_bytes(),
+                prevout.value.to_sat(),
+                &tx_bytes,
+                index,
+                VERIFY_ALL,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl BitcoinTransaction {
+    /// Checks the signature of input `index` spending `prevout`, for P2PKH, P2WPKH and
+    /// P2TR key path spends, including the low-S and strict DER policy rules for ECDSA.
+    ///
+    /// Taproot signatures commit to all spent outputs, so a P2TR input of a transaction
+    /// with more than one input fails with `MissingPrevouts`; use
+    /// [`BitcoinTransaction::verify_input_with_prevouts`] instead.
+    pub fn verify_input(
+        &self,
+        index: usize,
+        prevout: &TransactionOutput,
+    ) -> Result<(), VerifyError> {
+        if prevout.script_pubkey.classify() == ScriptType::P2TR && self.inputs.len() != 1 {
+            self.input(index)?;
+            return Err(VerifyError::MissingPrevouts);
+        }
+        self.verify_spend(index, prevout, std::slice::from_ref(prevout))
+    }
+
+    /// [`BitcoinTransaction::verify_input`] given the outputs spent by every input.
+    pub fn verify_input_with_prevouts(
+        &self,
+        index: usize,
+        prevouts: &[TransactionOutput],
+    ) -> Result<(), VerifyError> {
+        if prevouts.len() != self.inputs.len() {
+            return Err(VerifyError::MissingPrevouts);
+        }
+        self.input(index)?;
+        self.verify_spend(index, &prevouts[index], prevouts)
+    }
+
+    fn input(&self, index: usize) -> Result<&TransactionInput, VerifyError> {
+        self.inputs.get(index).ok_or(VerifyError::IndexOutOfRange {
+            index,
+            len: self.inputs.len(),
+        })
+    }
+
+    fn verify_spend(
+        &self,
+        index: usize,
+        prevout: &TransactionOutput,
+        prevouts: &[TransactionOutput],
+    ) -> Result<(), VerifyError> {
+        let input = self.input(index)?;
+        let script_pubkey = &prevout.script_pubkey;
+        match script_pubkey.classify() {
+            ScriptType::P2PKH => {
+                let pushes = input
+                    .script_sig
+                    .instructions()
+                    .map(|instruction| match instruction {
+                        Ok(Instruction::PushBytes(data)) => Ok(data),
+                        _ => Err(VerifyError::MalformedSpend),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let [signature, pubkey] = pushes[..] else {
+                    return Err(VerifyError::MalformedSpend);
+                };
+                let (signature, pubkey) = ecdsa_signature_and_key(signature, pubkey)?;
+                if Script::new_p2pkh_from_key(&pubkey) != *script_pubkey {
+                    return Err(VerifyError::KeyMismatch);
+                }
+                let sighash = self
+                    .legacy_sighash_raw(index, script_pubkey, u32::from(signature.sighash_byte()))
+                    .map_err(VerifyError::Sighash)?;
+                pubkey
+                    .verify_ecdsa(&sighash.0, &signature)
+                    .map_err(|_| VerifyError::InvalidSignature)
+            }
+            ScriptType::P2WPKH => {
+                if input.witness.len() != 2 || !input.script_sig.is_empty() {
+                    return Err(VerifyError::MalformedSpend);
+                }
+                let signature = input.witness.nth(0).expect("two elements");
+                let pubkey = input.witness.nth(1).expect("two elements");
+                let (signature, pubkey) = ecdsa_signature_and_key(signature, pubkey)?;
+                // Segwit v0 policy requires compressed keys, and only those hash to the program.
+                if Script::new_p2wpkh_from_key(&pubkey).ok().as_ref() != Some(script_pubkey) {
+                    return Err(VerifyError::KeyMismatch);
+                }
+                let script_code = script_pubkey
+                    .p2wpkh_script_code()
+                    .expect("classified P2WPKH");
+                let sighash = self
+                    .segwit_v0_sighash_raw(
+                        index,
+                        &script_code,
+                        prevout.value,
+                        u32::from(signature.sighash_byte()),
+                    )
+                    .map_err(VerifyError::Sighash)?;
+                pubkey
+                    .verify_ecdsa(&sighash.0, &signature)
+                    .map_err(|_| VerifyError::InvalidSignature)
+            }
+            ScriptType::P2TR => {
+                let annex = input.witness.taproot_annex();
+                let stack_len = input.witness.len() - usize::from(annex.is_some());
+                if !input.script_sig.is_empty() || stack_len == 0 {
+                    return Err(VerifyError::MalformedSpend);
+                }
+                if stack_len > 1 {
+                    return Err(VerifyError::UnsupportedScriptType(ScriptType::P2TR));
+                }
+                let signature = input
+                    .witness
+                    .nth(0)
+                    .expect("key path spend has a signature");
+                // A 65th byte names the sighash type, which must then not be the default.
+                let (signature, sighash_type) = match signature.len() {
+                    64 => (signature, TapSighashType::Default),
+                    65 => match TapSighashType::from_u8(signature[64]) {
+                        Ok(TapSighashType::Default) | Err(_) => {
+                            return Err(VerifyError::InvalidSignatureEncoding);
+                        }
+                        Ok(sighash_type) => (&signature[..64], sighash_type),
+                    },
+                    _ => return Err(VerifyError::InvalidSignatureEncoding),
+                };
+                let signature: &[u8; 64] = signature.try_into().expect("64 bytes");
+                let output_key = script_pubkey.p2tr_output_key().expect("classified P2TR");
+                let sighash = self
+                    .taproot_sighash(index, prevouts, sighash_type, None, annex)
+                    .map_err(VerifyError::Sighash)?;
+                output_key
+                    .verify_schnorr(&sighash.0, signature)
+                    .map_err(|error| match error {
+                        BitcoinError::InvalidPublicKey => VerifyError::InvalidPublicKey,
+                        _ => VerifyError::InvalidSignature,
+                    })
+            }
+            script_type => Err(VerifyError::UnsupportedScriptType(script_type)),
+        }
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+fn ecdsa_signature_and_key(
+    signature: &[u8],
+    pubkey: &[u8],
+) -> Result<(Signature, PublicKey), VerifyError> {
+    let signature = Signature::from_der_with_sighash(signature)
+        .map_err(|_| VerifyError::InvalidSignatureEncoding)?;
+    if !signature.is_strict_der() {
+        return Err(VerifyError::NonStrictDer);
+    }
+    if !signature.is_low_s() {
+        return Err(VerifyError::HighS);
+    }
+    let pubkey = PublicKey::from_slice(pubkey).map_err(|_| VerifyError::InvalidPublicKey)?;
+    Ok((signature, pubkey))
+}
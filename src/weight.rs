@@ -0,0 +1,28 @@
+use std::fmt;
+
+/// Transaction weight in weight units: 4 per non-witness byte, 1 per witness byte.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Weight(pub u64);
+
+impl Weight {
+    pub const ZERO: Weight = Weight(0);
+
+    pub const fn from_wu(wu: u64) -> Self {
+        Weight(wu)
+    }
+
+    pub const fn to_wu(self) -> u64 {
+        self.0
+    }
+
+    /// Virtual size as Core reports it: a weight of 609 is 153 vbytes.
+    pub const fn to_vbytes_ceil(self) -> u64 {
+        self.0.div_ceil(4)
+    }
+}
+
+impl fmt::Display for Weight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} WU", self.0)
+    }
+}
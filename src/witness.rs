@@ -0,0 +1,274 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BitcoinError, CompactSize, TAPROOT_LEAF_TAPSCRIPT, compact_size_len, read_vec, write_vec,
+};
+
+pub(crate) const TAPROOT_ANNEX_PREFIX: u8 = 0x50;
+
+/// The witness stack of one input. Elements are arbitrary byte strings; nothing here
+/// interprets them.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone)]
+pub struct Witness {
+    elements: Vec<Vec<u8>>,
+}
+
+impl Witness {
+    /// Policy limits, as in Bitcoin Core's `IsWitnessStandard`.
+    pub const MAX_STANDARD_STACK_ITEMS: usize = 100;
+    pub const MAX_STANDARD_ITEM_SIZE: usize = 80;
+    pub const MAX_STANDARD_SCRIPT_SIZE: usize = 3600;
+
+    pub fn new() -> Self {
+        Witness::default()
+    }
+
+    pub fn push<T: AsRef<[u8]>>(&mut self, element: T) {
+        self.elements.push(element.as_ref().to_vec());
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn element_count(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Length of [`Witness::to_bytes`], without building it.
+    pub fn total_size(&self) -> usize {
+        self.elements.iter().fold(
+            compact_size_len(self.elements.len() as u64),
+            |size, element| size + compact_size_len(element.len() as u64) + element.len(),
+        )
+    }
+
+    /// Standardness of the stack shape, judged without the spent output: no annex, a
+    /// witness script of at most 3600 bytes, and at most 100 items of at most 80 bytes
+    /// before it. A last element shaped like a control block marks a taproot script path,
+    /// where, as in Core, only the 80-byte item limit applies. Weight against the block
+    /// limit is left to callers.
+    pub fn is_within_limits(&self) -> bool {
+        if self.taproot_annex().is_some() {
+            return false;
+        }
+        let items = match self.elements.as_slice() {
+            [] => return true,
+            [items @ .., _script, control_block] if is_control_block_shaped(control_block) => items,
+            [items @ .., script] => {
+                if script.len() > Self::MAX_STANDARD_SCRIPT_SIZE
+                    || items.len() > Self::MAX_STANDARD_STACK_ITEMS
+                {
+                    return false;
+                }
+                items
+            }
+        };
+        items
+            .iter()
+            .all(|item| item.len() <= Self::MAX_STANDARD_ITEM_SIZE)
+    }
+
+    pub fn nth(&self, index: usize) -> Option<&[u8]> {
+        self.elements.get(index).map(Vec::as_slice)
+    }
+
+    pub fn last(&self) -> Option<&[u8]> {
+        self.elements.last().map(Vec::as_slice)
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &[u8]> {
+        self.elements.iter().map(Vec::as_slice)
+    }
+
+    /// The annex of a taproot spend: a last element starting with 0x50, present only when
+    /// the stack has at least two elements.
+    pub fn taproot_annex(&self) -> Option<&[u8]> {
+        match self.elements.as_slice() {
+            [_, .., last] if last.first() == Some(&TAPROOT_ANNEX_PREFIX) => Some(last),
+            _ => None,
+        }
+    }
+
+    /// The leaf script of a taproot script-path spend, assuming the input spends a P2TR
+    /// output. Key-path spends (one element, plus an optional annex) have none.
+    pub fn tapscript(&self) -> Option<&[u8]> {
+        match self.taproot_script_path()? {
+            [.., script, _control_block] => Some(script),
+            _ => None,
+        }
+    }
+
+    /// The control block of a taproot script-path spend, assuming the input spends a P2TR
+    /// output.
+    pub fn taproot_control_block(&self) -> Option<&[u8]> {
+        match self.taproot_script_path()? {
+            [.., _script, control_block] => Some(control_block),
+            _ => None,
+        }
+    }
+
+    /// The public key of a P2WPKH spend: exactly a signature and a compressed key.
+    pub fn p2wpkh_pubkey(&self) -> Option<&[u8]> {
+        match self.elements.as_slice() {
+            [_signature, key] if key.len() == 33 && matches!(key[0], 0x02 | 0x03) => Some(key),
+            _ => None,
+        }
+    }
+
+    // The stack without its annex, if it is long enough to be a script-path spend.
+    fn taproot_script_path(&self) -> Option<&[Vec<u8>]> {
+        let stack = match self.taproot_annex() {
+            Some(_) => &self.elements[..self.elements.len() - 1],
+            None => self.elements.as_slice(),
+        };
+        (stack.len() >= 2).then_some(stack)
+    }
+
+    pub fn to_vec(&self) -> Vec<Vec<u8>> {
+        self.elements.clone()
+    }
+
+    /// Element count, then each element with its CompactSize length.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_vec(&mut bytes, &self.elements, |out, element| {
+            let len = CompactSize::try_from(element.len()).expect("element length fits in u64");
+            out.extend_from_slice(&len.to_bytes());
+            out.extend_from_slice(element);
+        });
+        bytes
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), BitcoinError> {
+        let (witness, consumed) = Self::from_bytes(bytes)?;
+        Ok((witness, &bytes[consumed..]))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
+        // Every element takes at least its one-byte length prefix.
+        let max_elements = bytes.len().saturating_sub(1) as u64;
+        let (elements, consumed) = read_vec(bytes, max_elements, |bytes| {
+            let (len, rest) = CompactSize::parse(bytes)?;
+            let len = usize::try_from(len)?;
+            let element = rest.get(..len).ok_or(BitcoinError::InsufficientBytes)?;
+            Ok((element.to_vec(), bytes.len() - rest.len() + len))
+        })?;
+        Ok((Witness { elements }, consumed))
+    }
+}
+
+// 33 + 32m bytes with a tapscript leaf version: the undefined opcode 0xc0 can't start a
+// usable witness script.
+fn is_control_block_shaped(element: &[u8]) -> bool {
+    element.len() >= 33
+        && (element.len() - 33).is_multiple_of(32)
+        && element[0] & 0xfe == TAPROOT_LEAF_TAPSCRIPT
+}
+
+impl From<Vec<Vec<u8>>> for Witness {
+    fn from(elements: Vec<Vec<u8>>) -> Self {
+        Witness { elements }
+    }
+}
+
+impl<T: AsRef<[u8]>> FromIterator<T> for Witness {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let elements = iter
+            .into_iter()
+            .map(|element| element.as_ref().to_vec())
+            .collect();
+        Witness { elements }
+    }
+}
+
+// One stack element: a hex string in human-readable formats, raw bytes otherwise.
+struct Element<'a>(&'a [u8]);
+
+impl Serialize for Element<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&hex::encode(self.0))
+        } else {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+}
+
+struct ElementBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ElementBuf {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct ElementVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ElementVisitor {
+            type Value = ElementBuf;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a hex string or bytes")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                hex::decode(s).map(ElementBuf).map_err(E::custom)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+                Ok(ElementBuf(bytes.to_vec()))
+            }
+
+            fn visit_byte_buf<E: serde::de::Error>(self, bytes: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(ElementBuf(bytes))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut bytes = Vec::new();
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    bytes.push(byte);
+                }
+                Ok(ElementBuf(bytes))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(ElementVisitor)
+        } else {
+            deserializer.deserialize_byte_buf(ElementVisitor)
+        }
+    }
+}
+
+impl Serialize for Witness {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.iter().map(Element))
+    }
+}
+
+impl<'de> Deserialize<'de> for Witness {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let elements = Vec::<ElementBuf>::deserialize(deserializer)?;
+        Ok(Witness {
+            elements: elements.into_iter().map(|element| element.0).collect(),
+        })
+    }
+}
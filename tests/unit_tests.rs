@@ -1,5 +1,84 @@
 use rust_week_3_exercises::*;
 
+const SMALL_VARINT: [u8; 1] = CompactSize::encode_small(0xFC);
+const _: () = assert!(CompactSize::ONE.encoded_size() == 1);
+const _: () = assert!(CompactSize::new(0x10000).encoded_size() == 5);
+
+// Counts heap allocations made by the current thread, so tests can assert an operation
+// is allocation-free regardless of what other tests run concurrently.
+mod alloc_counter {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    pub struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+    }
+
+    pub fn allocations() -> usize {
+        ALLOCATIONS.with(Cell::get)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: alloc_counter::CountingAllocator = alloc_counter::CountingAllocator;
+
+// (scriptPubKey, leaf script, control block, merkle root). The first is BIP341 wallet
+// vector 1; the others were printed from Bitcoin Core's feature_taproot.py.
+const TAPROOT_SCRIPT_PATHS: [(&str, &str, &str, &str); 3] = [
+    (
+        "5120147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3",
+        "20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac",
+        "c1187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27",
+        "5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21",
+    ),
+    (
+        "5120228b94a4806254a38d6efa8a134c28ebc89546209559dfe40b2b0493bafacc5b",
+        "6a50",
+        "c0a0eb12e60a52614986c623cbb6621dcdba3a47e3be6b37e032b7a11c7b98f400\
+         9c9aed3dfd11ab0e78bf87ef3bf296269dc4b0f7712140386d6980992bab4b45",
+        "8571d415f70bade61c30e89c34e6513d996878f6fa785f581e41ad375ae27c74",
+    ),
+    (
+        "51205dc8e62b15e0ebdf44751676be35ba32eed2e84608b290d4061bbff136cd7ba9",
+        "6a",
+        "c1a9d6f66cd4b25004f526bfa873e56942f98e8e492bd79ed6532b966104817c2b\
+         da584e7d32612381cf88edc1c02e28a296e807c16ad22f591ee113946e48a71e\
+         0641e660d1e5392fb79d64838c2b84faf04b7f5f283c9d8bf83e39e177b64372\
+         a0cd22eeab7e093873e851e247714eff762d8a30be699ba4456cfe6491b282e1\
+         93a071350ae099005a5950d74f73ba13077a57bc478007fb0e4d1099ce9cf3d4",
+        "d3f76026af7aba068ec6db7664a2cdd9e746dd2569d72c719faaedec652ac6cc",
+    ),
+];
+
+// Two chained spends from block 702861: tx 5816c1ef… spends a P2WPKH output, and
+// e642af2e… spends that transaction's P2PKH output.
+#[cfg(any(feature = "secp256k1", feature = "bitcoinconsensus"))]
+const BLOCK_702861_P2WPKH_SPEND: &str = "02000000000101c4582375af87690745b97517e2bf6751fffdcd6c2400dd6416869c02ced58fe6\
+     0000000000feffffff02a7579b000000000017a9140b52e4d77c79580936f831259466cbaedd1589d787\
+     57ef0000000000001976a914b6d0a52c066bb33f9a957f5cfbeee440430068e988ac0247304402203bc0\
+     516d57d56304b4784f2e985937755d38bef594788d363d80da532bf5310502204eb9efafb5a75262d196\
+     8bc9783f08270817ee51f78c5f5eeb901a3344adad260121027c293cee75991482c499434200b2efbb13\
+     7ca7e1e1c2817e41e35a8d002cfcee8bb90a00";
+#[cfg(any(feature = "secp256k1", feature = "bitcoinconsensus"))]
+const BLOCK_702861_P2PKH_SPEND: &str = "01000000019dd240f06c01fd0ede0a1e36bc465f603ad23a245f5fc779166d07aaefc11658\
+     010000006b4830450221008ce7f8c740bbd65deeeead0ae763c1aa01e50d11d03879df166c8c619ccdcf\
+     e902205300db1c6fdc5b8cad65d4c2c35f2709e115014cafff27845ca6d091b21d6985012102efae1803\
+     c7c63d59bbe7a6cba7e20d7ea02efa1fc9cbf9d4b71adcf8c2da686bffffffff010bdc00000000000017\
+     a914b896fb38447b0a460fb18f0370389ca93f0b29dc8700000000";
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -10,6 +89,91 @@ mod tests {
         txid
     }
 
+    #[test]
+    fn test_hash_digest_vectors() {
+        assert_eq!(
+            sha256(b"").to_string(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256d(b"").to_string(),
+            "5df6e0e2761359d30a8275058e299fcc0381534545f55cf43e41983f5d4c9456"
+        );
+        assert_eq!(
+            sha256d(b"hello").to_string(),
+            "9595c9df90075148eb06860365df33584b75bff782a510c6cd4883a419833d50"
+        );
+        assert_eq!(
+            hash160(b"").to_string(),
+            "b472a266d0bd89c13706a4132ccfb16f7c3b9fcb"
+        );
+        let pubkey =
+            hex::decode("0250863ad64a87ae8a2fe83c1af1a8403cb53f53e486d8511dad8a04887e5b2352")
+                .unwrap();
+        assert_eq!(
+            hash160(&pubkey).to_string(),
+            "f54a5851e9372b87810a8e60cdd2e7cfd80b6e31"
+        );
+    }
+
+    #[test]
+    fn test_tagged_hash() {
+        // TapTweak of the first BIP341 wallet test vector (key-path only, no script tree).
+        let internal_key =
+            hex::decode("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d")
+                .unwrap();
+        assert_eq!(
+            hex::encode(tagged_hash("TapTweak", &internal_key)),
+            "b86e7be8f39bab32a6f2c0443abbc210f0edac0e2c53d501b36b64437d9c6c70"
+        );
+
+        assert_eq!(
+            hex::encode(tagged_hash("BIP0340/challenge", b"")),
+            "c216d352f5818b7b4beacd4ae0a26fe888080823d2a598856661bcd54f1b3713"
+        );
+        assert_eq!(
+            hex::encode(tagged_hash("CustomTag", b"hello")),
+            "eb10558c692277474777bf76b922f8778dc85469a6f1d586f020e66b9201bff2"
+        );
+
+        let mut leaf = vec![0xC0, 0x22, 0x20];
+        leaf.extend_from_slice(&[0x11; 32]);
+        leaf.push(0xAC);
+        assert_eq!(
+            hex::encode(tagged_hash("TapLeaf", &leaf)),
+            "37086e5786e6e5b5316b6261c0e465f83901561878fcd2f233e04d5122696ac1"
+        );
+        // The cached midstate must not be disturbed by earlier calls.
+        assert_eq!(tagged_hash("TapLeaf", &leaf), tagged_hash("TapLeaf", &leaf));
+
+        let mut branch = [0u8; 64];
+        branch[32..].fill(0x01);
+        assert_eq!(
+            hex::encode(tagged_hash("TapBranch", &branch)),
+            "f4c726824c00da5b61a25587621008d7a6d46a4d7e9f8f50faf018f066482312"
+        );
+    }
+
+    #[test]
+    fn test_hash_newtypes_hex_and_serde() {
+        let hash = hash160(b"abc");
+        assert_eq!(hash.to_string().parse::<Hash160>().unwrap(), hash);
+        assert_eq!(hash.to_string(), hash.to_hex_internal());
+        assert_eq!(
+            Hash160::try_from(&[0u8; 21][..]),
+            Err(BitcoinError::InvalidFormat)
+        );
+
+        let json = serde_json::to_string(&hash).unwrap();
+        assert_eq!(json, format!("\"{}\"", hash));
+        assert_eq!(serde_json::from_str::<Hash160>(&json).unwrap(), hash);
+
+        let digest = sha256d(b"abc");
+        let encoded = bincode::serialize(&digest).unwrap();
+        assert_eq!(encoded, digest.0.to_vec());
+        assert_eq!(bincode::deserialize::<Hash256>(&encoded).unwrap(), digest);
+    }
+
     #[test]
     fn test_compact_size_serialization() {
         let tests = vec![
@@ -34,6 +198,456 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_compact_size_strict_decoding() {
+        let minimal = vec![
+            (252u64, vec![0xFC]),
+            (253u64, vec![0xFD, 0xFD, 0x00]),
+            (0xFFFFu64, vec![0xFD, 0xFF, 0xFF]),
+            (0x10000u64, vec![0xFE, 0x00, 0x00, 0x01, 0x00]),
+            (0xFFFFFFFFu64, vec![0xFE, 0xFF, 0xFF, 0xFF, 0xFF]),
+            (
+                0x100000000u64,
+                vec![0xFF, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00],
+            ),
+        ];
+        for (value, bytes) in minimal {
+            let (decoded, consumed) = CompactSize::from_bytes_strict(&bytes).unwrap();
+            assert_eq!(decoded.value, value);
+            assert_eq!(consumed, bytes.len());
+        }
+
+        let padded = vec![
+            (252u64, vec![0xFD, 0xFC, 0x00]),
+            (253u64, vec![0xFE, 0xFD, 0x00, 0x00, 0x00]),
+            (0xFFFFu64, vec![0xFE, 0xFF, 0xFF, 0x00, 0x00]),
+            (
+                0x10000u64,
+                vec![0xFF, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00],
+            ),
+            (
+                0xFFFFFFFFu64,
+                vec![0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00],
+            ),
+        ];
+        for (value, bytes) in padded {
+            let (lenient, _) = CompactSize::from_bytes(&bytes).unwrap();
+            assert_eq!(lenient.value, value);
+            assert_eq!(
+                CompactSize::from_bytes_strict(&bytes),
+                Err(BitcoinError::NonMinimalCompactSize)
+            );
+        }
+    }
+
+    #[test]
+    fn test_compact_size_io_roundtrip() {
+        for value in [0u64, 252, 253, 0xFFFF, 0x10000, 0xFFFFFFFF, 0x100000000] {
+            let cs = CompactSize::new(value);
+            let mut written = Vec::new();
+            let n = cs.write_to(&mut written).unwrap();
+            assert_eq!(written, cs.to_bytes());
+            assert_eq!(n, written.len());
+
+            let mut reader = std::io::Cursor::new(written.clone());
+            assert_eq!(CompactSize::read_from(&mut reader).unwrap(), cs);
+            assert_eq!(reader.position() as usize, written.len());
+        }
+    }
+
+    #[test]
+    fn test_compact_size_read_from_truncated() {
+        let mut empty: &[u8] = &[];
+        assert_eq!(
+            CompactSize::read_from(&mut empty),
+            Err(BitcoinError::InsufficientBytes)
+        );
+
+        let mut truncated: &[u8] = &[0xFE, 0x01, 0x02];
+        assert_eq!(
+            CompactSize::read_from(&mut truncated),
+            Err(BitcoinError::InsufficientBytes)
+        );
+    }
+
+    #[test]
+    fn test_compact_size_encoded_size() {
+        let tests = vec![
+            (0u64, 1),
+            (252u64, 1),
+            (253u64, 3),
+            (0xFFFFu64, 3),
+            (0x10000u64, 5),
+            (0xFFFFFFFFu64, 5),
+            (0x100000000u64, 9),
+            (u64::MAX, 9),
+        ];
+        for (value, size) in tests {
+            let cs = CompactSize::new(value);
+            assert_eq!(cs.encoded_size(), size);
+            assert_eq!(cs.encoded_size(), cs.to_bytes().len());
+            assert_eq!(compact_size_len(value), size);
+        }
+    }
+
+    #[test]
+    fn test_to_bytes_preallocates_exact_capacity() {
+        let script = Script::new(vec![0xAB; 300]);
+        let bytes = script.to_bytes();
+        assert_eq!(bytes.capacity(), bytes.len());
+
+        let inputs = vec![
+            TransactionInput::new(OutPoint::new(dummy_txid(1), 0), script, 0xFFFFFFFF),
+            TransactionInput::new(
+                OutPoint::new(dummy_txid(2), 1),
+                Script::new(vec![]),
+                0xFFFFFFFE,
+            ),
+        ];
+        let bytes = BitcoinTransaction::new(2, inputs, 0).to_bytes();
+        assert_eq!(bytes.capacity(), bytes.len());
+    }
+
+    #[test]
+    fn test_compact_size_encode_into() {
+        for value in [0u64, 252, 253, 0xFFFF, 0x10000, 0xFFFFFFFF, 0x100000000] {
+            let cs = CompactSize::new(value);
+            let mut buf = [0xAAu8; 16];
+            let written = cs.encode_into(&mut buf).unwrap();
+            assert_eq!(&buf[..written], cs.to_bytes().as_slice());
+            assert!(buf[written..].iter().all(|&b| b == 0xAA));
+
+            let (array, size) = cs.to_array();
+            assert_eq!(size, written);
+            assert_eq!(&array[..size], &buf[..written]);
+        }
+    }
+
+    #[test]
+    fn test_compact_size_encode_into_short_buffer() {
+        let mut buf = [0xAAu8; 4];
+        assert_eq!(
+            CompactSize::new(0x10000).encode_into(&mut buf),
+            Err(BitcoinError::InsufficientBytes)
+        );
+        assert_eq!(buf, [0xAA; 4]);
+        assert_eq!(CompactSize::new(0xFFFF).encode_into(&mut buf), Ok(3));
+    }
+
+    #[test]
+    fn test_compact_size_conversions() {
+        assert_eq!(CompactSize::from(0xABu8).value, 0xAB);
+        assert_eq!(CompactSize::from(0xABCDu16).value, 0xABCD);
+        assert_eq!(CompactSize::from(0xABCDEF01u32).value, 0xABCDEF01);
+        assert_eq!(CompactSize::from(u64::MAX).value, u64::MAX);
+        assert_eq!(CompactSize::try_from(300usize).unwrap().value, 300);
+        assert_eq!(CompactSize::try_from(42i64).unwrap().value, 42);
+        assert_eq!(
+            CompactSize::try_from(-1i64),
+            Err(BitcoinError::IntegerOverflow)
+        );
+
+        assert_eq!(u64::from(CompactSize::new(7)), 7);
+        assert_eq!(usize::try_from(CompactSize::new(7)), Ok(7));
+    }
+
+    #[test]
+    fn test_compact_size_narrowing_overflow() {
+        // Same failure path `TryFrom<CompactSize> for usize` takes when usize is 4 bytes.
+        let big = CompactSize::new(u32::MAX as u64 + 1);
+        assert_eq!(
+            u32::try_from(big.clone()),
+            Err(BitcoinError::IntegerOverflow)
+        );
+        assert_eq!(
+            u32::try_from(CompactSize::new(u32::MAX as u64)),
+            Ok(u32::MAX)
+        );
+
+        #[cfg(target_pointer_width = "32")]
+        assert_eq!(usize::try_from(big), Err(BitcoinError::IntegerOverflow));
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(usize::try_from(big), Ok(u32::MAX as usize + 1));
+    }
+
+    #[test]
+    fn test_compact_size_hex_roundtrip() {
+        let tests = vec![
+            (0x20u64, "20"),
+            (256u64, "fd0001"),
+            (0x10000u64, "fe00000100"),
+            (0x100000000u64, "ff0000000001000000"),
+        ];
+        for (value, hex) in tests {
+            let cs = CompactSize::new(value);
+            assert_eq!(cs.to_string(), hex);
+            assert_eq!(format!("{:x}", cs), hex);
+            assert_eq!(hex.parse::<CompactSize>().unwrap(), cs);
+        }
+        assert_eq!(format!("{:#x}", CompactSize::new(1)), "0x01");
+    }
+
+    #[test]
+    fn test_compact_size_from_str_errors() {
+        assert_eq!(
+            "fd01".parse::<CompactSize>(),
+            Err(BitcoinError::InsufficientBytes)
+        );
+        assert_eq!(
+            "0102".parse::<CompactSize>(),
+            Err(BitcoinError::InvalidFormat)
+        );
+        assert_eq!(
+            "zz".parse::<CompactSize>(),
+            Err(BitcoinError::InvalidFormat)
+        );
+        assert_eq!(
+            "".parse::<CompactSize>(),
+            Err(BitcoinError::InsufficientBytes)
+        );
+    }
+
+    #[test]
+    fn test_compact_size_serde_json() {
+        let cs = CompactSize::new(70000);
+        let json = serde_json::to_string(&cs).unwrap();
+        assert_eq!(json, "70000");
+        assert_eq!(serde_json::from_str::<CompactSize>(&json).unwrap(), cs);
+    }
+
+    #[test]
+    fn test_compact_size_serde_bincode() {
+        for value in [0u64, 253, 0x10000, 0x100000000] {
+            let cs = CompactSize::new(value);
+            let encoded = bincode::serialize(&cs).unwrap();
+            // bincode prefixes byte strings with their u64 length.
+            assert_eq!(&encoded[..8], &(cs.encoded_size() as u64).to_le_bytes());
+            assert_eq!(&encoded[8..], cs.to_bytes().as_slice());
+            assert_eq!(bincode::deserialize::<CompactSize>(&encoded).unwrap(), cs);
+        }
+    }
+
+    #[test]
+    fn test_compact_size_checked_new() {
+        assert_eq!(CompactSize::checked_new(10, 10).unwrap().value, 10);
+        assert_eq!(
+            CompactSize::checked_new(11, 10),
+            Err(BitcoinError::ValueTooLarge { value: 11, max: 10 })
+        );
+
+        let bytes = CompactSize::new(0x10000).to_bytes();
+        assert_eq!(
+            CompactSize::from_bytes_with_max(&bytes, 0xFFFF),
+            Err(BitcoinError::ValueTooLarge {
+                value: 0x10000,
+                max: 0xFFFF
+            })
+        );
+        assert_eq!(
+            CompactSize::from_bytes_with_max(&bytes, 0x10000),
+            Ok((CompactSize::new(0x10000), 5))
+        );
+    }
+
+    #[test]
+    fn test_compact_size_from_iter() {
+        let bytes = [0xFD, 0x00, 0x01, 0xAA, 0xBB];
+        let mut iter = bytes.iter().copied();
+        assert_eq!(CompactSize::from_iter(&mut iter).unwrap().value, 256);
+        assert_eq!(iter.collect::<Vec<_>>(), vec![0xAA, 0xBB]);
+
+        let bytes = [0x05, 0x06];
+        let mut iter = bytes.iter().copied();
+        assert_eq!(CompactSize::from_iter(&mut iter).unwrap().value, 5);
+        assert_eq!(iter.next(), Some(0x06));
+
+        for value in [0xFFFFFFFFu64, 0x100000000] {
+            let encoded = CompactSize::new(value).to_bytes();
+            let mut iter = encoded.iter().copied();
+            assert_eq!(CompactSize::from_iter(&mut iter).unwrap().value, value);
+            assert_eq!(iter.next(), None);
+        }
+    }
+
+    #[test]
+    fn test_compact_size_from_iter_truncated() {
+        let mut empty = std::iter::empty();
+        assert_eq!(
+            CompactSize::from_iter(&mut empty),
+            Err(BitcoinError::InsufficientBytes)
+        );
+        let mut short = [0xFEu8, 0x01, 0x02].into_iter();
+        assert_eq!(
+            CompactSize::from_iter(&mut short),
+            Err(BitcoinError::InsufficientBytes)
+        );
+    }
+
+    #[test]
+    fn test_compact_size_const_helpers() {
+        assert_eq!(SMALL_VARINT, [0xFC]);
+        assert_eq!(CompactSize::ZERO.to_bytes(), vec![0x00]);
+        assert_eq!(CompactSize::ONE.to_bytes(), vec![0x01]);
+        assert_eq!(
+            CompactSize::encode_small(7).to_vec(),
+            CompactSize::new(7).to_bytes()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_compact_size_encode_small_rejects_large_value() {
+        let value = std::hint::black_box(253u8);
+        CompactSize::encode_small(value);
+    }
+
+    #[test]
+    fn test_length_prefixed_vec_helpers() {
+        let encode = |out: &mut Vec<u8>, item: &u16| out.extend_from_slice(&item.to_le_bytes());
+        let decode = |bytes: &[u8]| match bytes {
+            [a, b, ..] => Ok((u16::from_le_bytes([*a, *b]), 2)),
+            _ => Err(BitcoinError::InsufficientBytes),
+        };
+
+        let mut empty = Vec::new();
+        write_vec::<u16, _>(&mut empty, &[], encode);
+        assert_eq!(empty, vec![0x00]);
+        assert_eq!(read_vec(&empty, 10, decode).unwrap(), (vec![], 1));
+
+        let items: Vec<u16> = (0..253).collect();
+        let mut bytes = Vec::new();
+        write_vec(&mut bytes, &items, encode);
+        assert_eq!(&bytes[..3], &[0xFD, 0xFD, 0x00]);
+        assert_eq!(bytes.len(), 3 + 253 * 2);
+        assert_eq!(
+            read_vec(&bytes, 1000, decode).unwrap(),
+            (items.clone(), bytes.len())
+        );
+
+        assert_eq!(
+            read_vec(&bytes, 252, decode),
+            Err(BitcoinError::ValueTooLarge {
+                value: 253,
+                max: 252
+            })
+        );
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert_eq!(
+            read_vec(truncated, 1000, decode),
+            Err(BitcoinError::InsufficientBytes)
+        );
+    }
+
+    #[test]
+    fn test_txid_display_reversed_order() {
+        // Genesis block coinbase transaction.
+        let display = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+        let internal = "3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a";
+
+        let txid: Txid = display.parse().unwrap();
+        assert_eq!(txid.to_hex_internal(), internal);
+        assert_eq!(txid.0[0], 0x3b);
+        assert_eq!(txid.to_string(), display);
+        assert_eq!(Txid::from_hex_internal(internal).unwrap(), txid);
+    }
+
+    #[test]
+    fn test_txid_from_str_errors() {
+        assert_eq!("abcd".parse::<Txid>(), Err(BitcoinError::InvalidFormat));
+        assert_eq!(
+            "zz".repeat(32).parse::<Txid>(),
+            Err(BitcoinError::InvalidFormat)
+        );
+        assert_eq!(
+            Txid::from_hex_internal(&"00".repeat(33)),
+            Err(BitcoinError::InvalidFormat)
+        );
+    }
+
+    #[test]
+    fn test_txid_serde_json_backward_compatible() {
+        let txid = Txid(dummy_txid(0xAB));
+        let json = serde_json::to_string(&txid).unwrap();
+        assert_eq!(json, format!("\"{}ab\"", "00".repeat(31)));
+        assert_eq!(serde_json::from_str::<Txid>(&json).unwrap(), txid);
+        assert!(serde_json::from_str::<Txid>("\"abcd\"").is_err());
+    }
+
+    #[test]
+    fn test_txid_serde_bincode() {
+        let txid = Txid(dummy_txid(0xAB));
+        let encoded = bincode::serialize(&txid).unwrap();
+        assert_eq!(encoded.len(), 32);
+        assert_eq!(encoded, txid.0.to_vec());
+        assert_eq!(bincode::deserialize::<Txid>(&encoded).unwrap(), txid);
+
+        let outpoint = OutPoint::new(dummy_txid(0xCD), 9);
+        let encoded = bincode::serialize(&outpoint).unwrap();
+        assert_eq!(encoded.len(), 36);
+        assert_eq!(
+            bincode::deserialize::<OutPoint>(&encoded).unwrap(),
+            outpoint
+        );
+    }
+
+    #[test]
+    fn test_txid_constructors_and_accessors() {
+        let bytes = dummy_txid(0x42);
+        let txid = Txid::from_bytes(bytes);
+        assert_eq!(txid, Txid::from(bytes));
+        assert_eq!(txid.as_bytes(), &bytes);
+        assert_eq!(txid.to_vec(), bytes.to_vec());
+        assert_eq!(txid.as_ref(), &bytes[..]);
+        assert_eq!(Txid::try_from(&bytes[..]).unwrap(), txid);
+        assert_eq!(Txid::from_hex(&txid.to_string()).unwrap(), txid);
+        assert_eq!(
+            OutPoint::from_txid(txid.clone(), 3),
+            OutPoint::new(bytes, 3)
+        );
+    }
+
+    #[test]
+    fn test_txid_try_from_wrong_length() {
+        assert_eq!(
+            Txid::try_from(&[0u8; 31][..]),
+            Err(BitcoinError::InvalidFormat)
+        );
+        assert_eq!(
+            Txid::try_from(&[0u8; 33][..]),
+            Err(BitcoinError::InvalidFormat)
+        );
+        assert_eq!(
+            Txid::from_hex(&"00".repeat(31)),
+            Err(BitcoinError::InvalidFormat)
+        );
+        assert_eq!(
+            Txid::from_hex(&"00".repeat(33)),
+            Err(BitcoinError::InvalidFormat)
+        );
+    }
+
+    #[test]
+    fn test_debug_prints_hex() {
+        let txid = Txid(dummy_txid(0xAB));
+        assert_eq!(
+            format!("{:?}", txid),
+            format!("Txid(ab{})", "00".repeat(31))
+        );
+        assert_eq!(
+            format!("{:?}", hash160(b"")),
+            "Hash160(b472a266d0bd89c13706a4132ccfb16f7c3b9fcb)"
+        );
+
+        let script = Script::new(vec![0x76, 0xA9, 0x14]);
+        assert_eq!(format!("{:?}", script), "Script(76a914)");
+
+        let outpoint = OutPoint::new(dummy_txid(1), 2);
+        let debug = format!("{:?}", outpoint);
+        assert!(debug.contains(&format!("txid: Txid(01{})", "00".repeat(31))));
+        assert!(debug.contains("vout: 2"));
+    }
+
     #[test]
     fn test_outpoint_roundtrip() {
         let txid = dummy_txid(0xCC);
@@ -45,6 +659,110 @@ mod tests {
         assert_eq!(consumed, bytes.len());
     }
 
+    #[test]
+    fn test_outpoint_ordering_and_hashing() {
+        use std::collections::{BTreeSet, HashMap};
+
+        let mut high = [0u8; 32];
+        high[0] = 0x01;
+        let set: BTreeSet<OutPoint> = [
+            OutPoint::new(high, 0),
+            OutPoint::new(dummy_txid(2), 1),
+            OutPoint::new(dummy_txid(2), 0),
+            OutPoint::new(dummy_txid(1), 5),
+        ]
+        .into_iter()
+        .collect();
+        let ordered: Vec<_> = set.into_iter().collect();
+        assert_eq!(
+            ordered,
+            vec![
+                OutPoint::new(dummy_txid(1), 5),
+                OutPoint::new(dummy_txid(2), 0),
+                OutPoint::new(dummy_txid(2), 1),
+                OutPoint::new(high, 0),
+            ]
+        );
+
+        let mut map = HashMap::new();
+        map.insert(OutPoint::new(dummy_txid(3), 0), "utxo");
+        assert_eq!(map.get(&OutPoint::new(dummy_txid(3), 0)), Some(&"utxo"));
+
+        let mut by_txid = HashMap::new();
+        by_txid.insert(Txid(dummy_txid(3)), 1);
+        assert_eq!(by_txid.get(&Txid(dummy_txid(3))), Some(&1));
+
+        let mut sizes = vec![
+            CompactSize::new(300),
+            CompactSize::new(1),
+            CompactSize::new(70),
+        ];
+        sizes.sort();
+        assert_eq!(
+            sizes,
+            vec![
+                CompactSize::new(1),
+                CompactSize::new(70),
+                CompactSize::new(300)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_outpoint_display_and_from_str() {
+        let s = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b:0";
+        let outpoint: OutPoint = s.parse().unwrap();
+        assert_eq!(outpoint.vout, 0);
+        assert_eq!(outpoint.txid.0[0], 0x3b);
+        assert_eq!(outpoint.to_string(), s);
+
+        let outpoint = OutPoint::new(dummy_txid(7), 4294967295);
+        assert_eq!(outpoint.to_string().parse::<OutPoint>().unwrap(), outpoint);
+    }
+
+    #[test]
+    fn test_outpoint_from_str_errors() {
+        let txid = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+        let cases = [
+            txid.to_string(),
+            format!("{}:", txid),
+            format!("{}:-1", txid),
+            format!("{}:4294967296", txid),
+            format!("{}:1:2", txid),
+            format!("{}:0", &txid[..62]),
+            format!("{}00:0", txid),
+            format!("{}:0", "zz".repeat(32)),
+        ];
+        for case in cases {
+            assert_eq!(
+                case.parse::<OutPoint>(),
+                Err(BitcoinError::InvalidFormat),
+                "{case}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_outpoint_serde_formats() {
+        let outpoint = OutPoint::new(dummy_txid(0xEE), 513);
+
+        let encoded = bincode::serialize(&outpoint).unwrap();
+        assert_eq!(encoded.len(), 36);
+        assert_eq!(encoded, outpoint.to_bytes());
+        assert_eq!(
+            bincode::deserialize::<OutPoint>(&encoded).unwrap(),
+            outpoint
+        );
+        assert!(bincode::deserialize::<OutPoint>(&encoded[..35]).is_err());
+
+        let json = serde_json::to_string(&outpoint).unwrap();
+        assert_eq!(
+            json,
+            format!("{{\"txid\":\"{}ee\",\"vout\":513}}", "00".repeat(31))
+        );
+        assert_eq!(serde_json::from_str::<OutPoint>(&json).unwrap(), outpoint);
+    }
+
     #[test]
     fn test_script_roundtrip() {
         let script_data = vec![0x76, 0xA9, 0x14, 0x88, 0xAC];
@@ -56,41 +774,5403 @@ mod tests {
     }
 
     #[test]
-    fn test_tx_input_roundtrip() {
-        let outpoint = OutPoint::new(dummy_txid(1), 0);
-        let script = Script::new(vec![0x01, 0x02]);
-        let input = TransactionInput::new(outpoint.clone(), script.clone(), 0xFFFFFFFF);
-        let bytes = input.to_bytes();
-        let (parsed, consumed) = TransactionInput::from_bytes(&bytes).unwrap();
-        assert_eq!(parsed, input);
-        assert_eq!(consumed, bytes.len());
+    fn test_script_from_bytes_huge_length_prefix() {
+        let bytes = [0xFF; 12];
+        assert_eq!(
+            Script::from_bytes(&bytes),
+            Err(BitcoinError::InsufficientBytes)
+        );
     }
 
     #[test]
-    fn test_bitcoin_tx_roundtrip() {
-        let inputs = vec![TransactionInput::new(
-            OutPoint::new(dummy_txid(1), 0),
-            Script::new(vec![0x01, 0x02]),
-            0xFFFFFFFF,
-        )];
-        let tx = BitcoinTransaction::new(2, inputs.clone(), 1000);
-        let bytes = tx.to_bytes();
-        let (parsed, consumed) = BitcoinTransaction::from_bytes(&bytes).unwrap();
-        assert_eq!(parsed, tx);
-        assert_eq!(consumed, bytes.len());
+    fn test_script_byte_accessors() {
+        use std::borrow::Borrow;
+
+        let script = Script::new(vec![0x51, 0x52, 0x53]);
+        let slice: &[u8] = &script;
+        assert_eq!(slice, &[0x51, 0x52, 0x53]);
+        assert_eq!(script.as_bytes(), slice);
+        assert_eq!(AsRef::<[u8]>::as_ref(&script), slice);
+        assert_eq!(Borrow::<[u8]>::borrow(&script), slice);
+        assert_eq!(script.last(), Some(&0x53));
+        assert_eq!(script.into_bytes(), vec![0x51, 0x52, 0x53]);
     }
 
     #[test]
-    fn test_bitcoin_tx_json_serialization() {
-        let input = TransactionInput::new(
-            OutPoint::new(dummy_txid(0xAB), 3),
-            Script::new(vec![0xDE, 0xAD, 0xBE, 0xEF]),
-            0xABCDEF01,
-        );
-        let tx = BitcoinTransaction::new(1, vec![input], 999);
+    fn test_script_hex_roundtrip() {
+        let hex = "76a914f54a5851e9372b87810a8e60cdd2e7cfd80b6e3188ac";
+        let script: Script = hex.parse().unwrap();
+        assert_eq!(script.len(), 25);
+        assert_eq!(script.to_string(), hex);
+        assert_eq!(script.to_hex(), hex);
+        assert_eq!(format!("{:x}", script), hex);
+        assert_eq!(format!("{:X}", script), hex.to_uppercase());
+        assert_eq!(format!("{:#x}", script), format!("0x{}", hex));
+        assert_eq!(Script::from_hex(hex).unwrap(), script);
+        assert_eq!(Script::from_hex("").unwrap(), Script::new(vec![]));
+    }
 
-        let json = serde_json::to_string_pretty(&tx).unwrap();
-        let parsed: BitcoinTransaction = serde_json::from_str(&json).unwrap();
+    #[test]
+    fn test_script_from_hex_errors() {
+        assert_eq!(Script::from_hex("76a"), Err(BitcoinError::InvalidFormat));
+        assert_eq!(Script::from_hex("76zz"), Err(BitcoinError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_script_serde_json() {
+        let script =
+            Script::from_hex("76a914f54a5851e9372b87810a8e60cdd2e7cfd80b6e3188ac").unwrap();
+        let json = serde_json::to_string(&script).unwrap();
+        assert_eq!(
+            json,
+            "\"76a914f54a5851e9372b87810a8e60cdd2e7cfd80b6e3188ac\""
+        );
+        assert_eq!(serde_json::from_str::<Script>(&json).unwrap(), script);
+
+        let err = serde_json::from_str::<Script>("\"76zz\"").unwrap_err();
+        assert!(err.to_string().contains("invalid script hex"));
+    }
+
+    #[test]
+    fn test_script_serde_bincode() {
+        let script = Script::new(vec![0x6A; 300]);
+        let encoded = bincode::serialize(&script).unwrap();
+        assert_eq!(&encoded[8..], script.to_bytes().as_slice());
+        assert_eq!(bincode::deserialize::<Script>(&encoded).unwrap(), script);
+
+        // bincode lays a Vec<u8> out like a byte string; here the inner CompactSize
+        // claims one more byte than is present.
+        let mut bad = script.to_bytes();
+        bad[1] = 0x2D;
+        let encoded = bincode::serialize(&bad).unwrap();
+        assert!(bincode::deserialize::<Script>(&encoded).is_err());
+    }
+
+    #[test]
+    fn test_script_instructions_p2pkh() {
+        use rust_week_3_exercises::opcodes::*;
+
+        let script =
+            Script::from_hex("76a914f54a5851e9372b87810a8e60cdd2e7cfd80b6e3188ac").unwrap();
+        let hash = hex::decode("f54a5851e9372b87810a8e60cdd2e7cfd80b6e31").unwrap();
+        let instructions: Vec<_> = script.instructions().collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            instructions,
+            vec![
+                Instruction::Op(OP_DUP),
+                Instruction::Op(OP_HASH160),
+                Instruction::PushBytes(&hash),
+                Instruction::Op(OP_EQUALVERIFY),
+                Instruction::Op(OP_CHECKSIG),
+            ]
+        );
+        assert_eq!(OP_CHECKSIG.name(), "OP_CHECKSIG");
+        assert_eq!(OP_PUSHBYTES_20.to_u8(), 0x14);
+        assert_eq!(Opcode::from(0xBBu8).to_string(), "OP_UNKNOWN");
+    }
+
+    #[test]
+    fn test_script_instructions_op_return_and_pushdata() {
+        use rust_week_3_exercises::opcodes::*;
+
+        let script = Script::from_hex("6a0b68656c6c6f20776f726c64").unwrap();
+        let instructions: Vec<_> = script.instructions().collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            instructions,
+            vec![
+                Instruction::Op(OP_RETURN),
+                Instruction::PushBytes(b"hello world")
+            ]
+        );
+
+        let mut bytes = vec![0x00, 0x4C, 0x02, 0xAA, 0xBB, 0x4D, 0x01, 0x00, 0xCC];
+        bytes.extend_from_slice(&[0x4E, 0x00, 0x00, 0x00, 0x00, 0x51]);
+        let script = Script::new(bytes);
+        let instructions: Vec<_> = script.instructions().collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            instructions,
+            vec![
+                Instruction::Op(OP_0),
+                Instruction::PushBytes(&[0xAA, 0xBB]),
+                Instruction::PushBytes(&[0xCC]),
+                Instruction::PushBytes(&[]),
+                Instruction::Op(OP_1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_script_instructions_truncated_push() {
+        for hex in ["14aabb", "4c", "4c05aa", "4d01", "4dffff00", "4e01000000"] {
+            let script = Script::from_hex(hex).unwrap();
+            let mut instructions = script.instructions();
+            assert_eq!(instructions.next(), Some(Err(BitcoinError::InvalidFormat)));
+            assert_eq!(instructions.next(), None);
+        }
+
+        let script = Script::from_hex("76a914aabb").unwrap();
+        let results: Vec<_> = script.instructions().collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[2].is_err());
+    }
+
+    #[test]
+    fn test_script_builder_p2pkh() {
+        use rust_week_3_exercises::opcodes::*;
+
+        let hash = hex::decode("f54a5851e9372b87810a8e60cdd2e7cfd80b6e31").unwrap();
+        let script = ScriptBuilder::new()
+            .push_opcode(OP_DUP)
+            .push_opcode(OP_HASH160)
+            .push_slice(&hash)
+            .push_opcode(OP_EQUALVERIFY)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        assert_eq!(
+            script.to_hex(),
+            "76a914f54a5851e9372b87810a8e60cdd2e7cfd80b6e3188ac"
+        );
+    }
+
+    #[test]
+    fn test_script_builder_minimal_pushes() {
+        let push = |len: usize| {
+            ScriptBuilder::new()
+                .push_slice(&vec![0xAB; len])
+                .into_script()
+        };
+
+        assert_eq!(push(0).as_bytes(), &[0x00]);
+        assert_eq!(&push(75)[..1], &[75]);
+        assert_eq!(&push(76)[..2], &[0x4C, 76]);
+        assert_eq!(&push(255)[..2], &[0x4C, 0xFF]);
+        assert_eq!(&push(256)[..3], &[0x4D, 0x00, 0x01]);
+        assert_eq!(push(256).len(), 259);
+        assert_eq!(&push(65536)[..5], &[0x4E, 0x00, 0x00, 0x01, 0x00]);
+
+        for len in [1, 75, 76, 255, 256, 520] {
+            let data = vec![0xAB; len];
+            let script = push(len);
+            let mut instructions = script.instructions();
+            assert_eq!(instructions.next(), Some(Ok(Instruction::PushBytes(&data))));
+            assert_eq!(instructions.next(), None);
+        }
+    }
+
+    #[test]
+    fn test_script_builder_push_int() {
+        let int = |value: i64| ScriptBuilder::new().push_int(value).into_script().to_hex();
+
+        assert_eq!(int(0), "00");
+        assert_eq!(int(-1), "4f");
+        assert_eq!(int(1), "51");
+        assert_eq!(int(16), "60");
+        assert_eq!(int(17), "0111");
+        assert_eq!(int(-2), "0182");
+        assert_eq!(int(127), "017f");
+        assert_eq!(int(128), "028000");
+        assert_eq!(int(-128), "028080");
+        assert_eq!(int(255), "02ff00");
+        assert_eq!(int(500_000), "0320a107");
+        assert_eq!(int(i64::MIN), "09000000000000008080");
+    }
+
+    const GENESIS_PUBKEY: &str = "04678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5f";
+
+    #[test]
+    fn test_script_classify_mainnet_templates() {
+        let cases = [
+            (format!("41{GENESIS_PUBKEY}ac"), ScriptType::P2PK),
+            // 1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa
+            (
+                "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac".to_string(),
+                ScriptType::P2PKH,
+            ),
+            // 3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy
+            (
+                "a914b472a266d0bd89c13706a4132ccfb16f7c3b9fcb87".to_string(),
+                ScriptType::P2SH,
+            ),
+            // bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4
+            (
+                "0014751e76e8199196d454941c45d1b3a323f1433bd6".to_string(),
+                ScriptType::P2WPKH,
+            ),
+            // bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3
+            (
+                "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262".to_string(),
+                ScriptType::P2WSH,
+            ),
+            // bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr
+            (
+                "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c".to_string(),
+                ScriptType::P2TR,
+            ),
+            (
+                "6a0b68656c6c6f20776f726c64".to_string(),
+                ScriptType::OpReturn,
+            ),
+            ("6a".to_string(), ScriptType::OpReturn),
+            (
+                format!("5141{GENESIS_PUBKEY}21{}52ae", "02".repeat(33)),
+                ScriptType::Multisig,
+            ),
+            ("5202beef".to_string(), ScriptType::WitnessUnknown),
+        ];
+        for (hex, expected) in cases {
+            let script = Script::from_hex(&hex).unwrap();
+            assert_eq!(script.classify(), expected, "{hex}");
+        }
+
+        let p2pkh = Script::from_hex("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac").unwrap();
+        assert!(p2pkh.is_p2pkh() && !p2pkh.is_p2sh() && !p2pkh.is_witness_program());
+        let p2tr = Script::from_hex(
+            "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+        )
+        .unwrap();
+        assert!(p2tr.is_witness_program() && !p2tr.is_op_return());
+    }
+
+    #[test]
+    fn test_script_classify_near_misses() {
+        let cases = [
+            // P2PKH with the wrong final opcode, and with a 19-byte hash.
+            "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ad",
+            "76a91362e907b15cbf27d5425399ebf6f0fb50ebb88f88ac",
+            // P2SH pushing the hash with OP_PUSHDATA1.
+            "a94c14b472a266d0bd89c13706a4132ccfb16f7c3b9fcb87",
+            // v0 programs must be 20 or 32 bytes.
+            "0015751e76e8199196d454941c45d1b3a323f1433bd600",
+            // Witness program pushed with OP_PUSHDATA1, and a one-byte program.
+            "004c14751e76e8199196d454941c45d1b3a323f1433bd6",
+            "510101",
+            // P2PK with a 33-byte key that has an uncompressed prefix.
+            "210411111111111111111111111111111111111111111111111111111111111111ac",
+            // OP_RETURN followed by a non-push opcode, and a truncated push.
+            "6a76",
+            "6a05aabb",
+            // 2-of-1 multisig.
+            "5221020202020202020202020202020202020202020202020202020202020202020251ae",
+            "",
+        ];
+        for hex in cases {
+            let script = Script::from_hex(hex).unwrap();
+            assert_eq!(script.classify(), ScriptType::NonStandard, "{hex}");
+        }
+        assert!(Script::from_hex("6a76").unwrap().is_op_return());
+    }
+
+    #[test]
+    fn test_script_standard_constructors() {
+        let key_hash =
+            Hash160::from_hex_internal("62e907b15cbf27d5425399ebf6f0fb50ebb88f18").unwrap();
+        let p2pkh = Script::new_p2pkh(&key_hash);
+        assert_eq!(
+            p2pkh.to_hex(),
+            "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac"
+        );
+        assert_eq!(p2pkh.classify(), ScriptType::P2PKH);
+
+        let script_hash =
+            Hash160::from_hex_internal("b472a266d0bd89c13706a4132ccfb16f7c3b9fcb").unwrap();
+        let p2sh = Script::new_p2sh(&script_hash);
+        assert_eq!(
+            p2sh.to_hex(),
+            "a914b472a266d0bd89c13706a4132ccfb16f7c3b9fcb87"
+        );
+        assert_eq!(p2sh.classify(), ScriptType::P2SH);
+
+        // BIP173 examples: both commit to the generator point as a compressed key.
+        let pubkey =
+            hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+        let p2wpkh = Script::new_p2wpkh(&hash160(&pubkey));
+        assert_eq!(
+            p2wpkh.to_hex(),
+            "0014751e76e8199196d454941c45d1b3a323f1433bd6"
+        );
+        assert_eq!(p2wpkh.classify(), ScriptType::P2WPKH);
+
+        let witness_script = ScriptBuilder::new()
+            .push_slice(&pubkey)
+            .push_opcode(opcodes::OP_CHECKSIG)
+            .into_script();
+        let p2wsh = Script::new_p2wsh(&witness_script.wscript_hash());
+        assert_eq!(
+            p2wsh.to_hex(),
+            "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262"
+        );
+        assert_eq!(p2wsh.classify(), ScriptType::P2WSH);
+        assert_eq!(
+            witness_script.script_hash().to_hex_internal(),
+            "23b0ad3477f2178bc0b3eed26e4e6316f4e83aa1"
+        );
+
+        let output_key = XOnlyPublicKey::from_hex(
+            "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+        )
+        .unwrap();
+        let p2tr = Script::new_p2tr(&output_key);
+        assert_eq!(
+            p2tr.to_hex(),
+            "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+        );
+        assert_eq!(p2tr.classify(), ScriptType::P2TR);
+    }
+
+    #[test]
+    fn test_script_new_op_return() {
+        let script = Script::new_op_return(b"hello world").unwrap();
+        assert_eq!(script.to_hex(), "6a0b68656c6c6f20776f726c64");
+        assert_eq!(script.classify(), ScriptType::OpReturn);
+
+        let max = Script::new_op_return(&[0xAB; 80]).unwrap();
+        assert_eq!(&max[..3], &[0x6A, 0x4C, 80]);
+        assert_eq!(max.classify(), ScriptType::OpReturn);
+
+        assert_eq!(
+            Script::new_op_return(&[0xAB; 81]),
+            Err(BitcoinError::ValueTooLarge { value: 81, max: 80 })
+        );
+    }
+
+    #[test]
+    fn test_script_asm_roundtrip() {
+        let p2pkh = Script::from_hex("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac").unwrap();
+        let asm =
+            "OP_DUP OP_HASH160 62e907b15cbf27d5425399ebf6f0fb50ebb88f18 OP_EQUALVERIFY OP_CHECKSIG";
+        assert_eq!(p2pkh.to_asm_string(), asm);
+        assert_eq!(p2pkh.display_asm().to_string(), asm);
+        assert_eq!(Script::from_asm_str(asm).unwrap(), p2pkh);
+
+        let keys = [
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+        ];
+        let asm = format!("OP_2 {} OP_3 OP_CHECKMULTISIG", keys.join(" "));
+        let multisig = Script::from_asm_str(&asm).unwrap();
+        assert_eq!(
+            multisig.to_hex(),
+            format!("5221{}21{}21{}53ae", keys[0], keys[1], keys[2])
+        );
+        assert_eq!(multisig.classify(), ScriptType::Multisig);
+        assert_eq!(multisig.to_asm_string(), asm);
+    }
+
+    #[test]
+    fn test_script_asm_edge_cases() {
+        assert_eq!(
+            Script::new(vec![0x6A, 0x02, 0xAA]).to_asm_string(),
+            "OP_RETURN [truncated push]"
+        );
+        assert_eq!(
+            Script::new(vec![0x4C, 0x00, 0xBB]).to_asm_string(),
+            "OP_0 OP_UNKNOWN"
+        );
+        assert_eq!(Script::new(vec![]).to_asm_string(), "");
+
+        assert_eq!(
+            Script::from_asm_str("OP_FALSE OP_TRUE OP_NOP2 OP_CSV")
+                .unwrap()
+                .to_hex(),
+            "0051b1b2"
+        );
+        assert_eq!(
+            "OP_CHECKSIGADD".parse::<Opcode>(),
+            Ok(opcodes::OP_CHECKSIGADD)
+        );
+        assert!(Script::from_asm_str("OP_UNKNOWN").is_err());
+        assert!(Script::from_asm_str("OP_DUP zz").is_err());
+        assert!(Script::from_asm_str("abc").is_err());
+    }
+
+    #[test]
+    fn test_script_witness_program() {
+        let p2wpkh = Script::from_hex("0014751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        assert_eq!(p2wpkh.witness_version(), Some(0));
+        assert_eq!(
+            p2wpkh.witness_program(),
+            Some(&hex::decode("751e76e8199196d454941c45d1b3a323f1433bd6").unwrap()[..])
+        );
+
+        let p2wsh = Script::from_hex(
+            "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+        )
+        .unwrap();
+        assert_eq!(p2wsh.witness_version(), Some(0));
+        assert_eq!(p2wsh.witness_program().map(<[u8]>::len), Some(32));
+
+        let p2tr = Script::from_hex(
+            "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+        )
+        .unwrap();
+        assert_eq!(p2tr.witness_version(), Some(1));
+        assert_eq!(p2tr.witness_program(), Some(&p2tr[2..]));
+
+        let v16 = Script::from_hex("6002aabb").unwrap();
+        assert_eq!(v16.witness_version(), Some(16));
+
+        let rejected = [
+            // v0 with a 25-byte program.
+            format!("0019{}", "ab".repeat(25)),
+            // Too short, too long, and a 22-byte script that is not a single push.
+            "5101ab".to_string(),
+            format!("5129{}", "ab".repeat(41)),
+            format!("0013{}ac", "ab".repeat(19)),
+            // Program pushed with OP_PUSHDATA1, and a non-version first opcode.
+            format!("004c14{}", "ab".repeat(20)),
+            format!("4f14{}", "ab".repeat(20)),
+        ];
+        for hex in rejected {
+            let script = Script::from_hex(&hex).unwrap();
+            assert_eq!(script.witness_version(), None, "{hex}");
+            assert_eq!(script.witness_program(), None, "{hex}");
+            assert!(!script.is_witness_program());
+        }
+    }
+
+    #[test]
+    fn test_script_num_core_vectors() {
+        let vectors: [(i64, &str); 12] = [
+            (0, ""),
+            (1, "01"),
+            (-1, "81"),
+            (127, "7f"),
+            (128, "8000"),
+            (255, "ff00"),
+            (256, "0001"),
+            (-255, "ff80"),
+            (-256, "0081"),
+            (2147483647, "ffffff7f"),
+            (-2147483647, "ffffffff"),
+            (-2147483648, "0000008080"),
+        ];
+        for (value, hex) in vectors {
+            let bytes = hex::decode(hex).unwrap();
+            assert_eq!(ScriptNum::new(value).encode(), bytes, "{value}");
+            assert_eq!(
+                ScriptNum::decode(&bytes, ScriptNum::LOCKTIME_MAX_LEN),
+                Ok(ScriptNum::new(value))
+            );
+        }
+
+        // -2^31 needs a fifth byte for the sign, so only the locktime limit admits it.
+        assert_eq!(
+            ScriptNum::decode(&[0x00, 0x00, 0x00, 0x80, 0x80], ScriptNum::DEFAULT_MAX_LEN),
+            Err(BitcoinError::ValueTooLarge { value: 5, max: 4 })
+        );
+        assert_eq!(
+            ScriptNum::decode(&[0xFF, 0xFF, 0xFF, 0xFF, 0x7F], ScriptNum::LOCKTIME_MAX_LEN)
+                .map(i64::from),
+            Ok(549755813887)
+        );
+    }
+
+    #[test]
+    fn test_script_num_rejects_non_minimal() {
+        for hex in ["00", "80", "0100", "ff0000", "0080", "010080"] {
+            let bytes = hex::decode(hex).unwrap();
+            assert_eq!(
+                ScriptNum::decode(&bytes, ScriptNum::DEFAULT_MAX_LEN),
+                Err(BitcoinError::NonMinimalScriptNum),
+                "{hex}"
+            );
+        }
+        // A padding byte that carries the sign is minimal.
+        assert_eq!(
+            ScriptNum::decode(&[0xFF, 0x00], 4).map(ScriptNum::value),
+            Ok(255)
+        );
+        assert_eq!(
+            ScriptNum::decode(&[0x80, 0x80], 4).map(ScriptNum::value),
+            Ok(-128)
+        );
+    }
+
+    #[test]
+    fn test_script_count_sigops() {
+        let keys = [
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+        ];
+        let multisig =
+            Script::from_asm_str(&format!("OP_2 {} OP_3 OP_CHECKMULTISIG", keys.join(" ")))
+                .unwrap();
+        assert_eq!(multisig.count_sigops(false), 20);
+        assert_eq!(multisig.count_sigops(true), 3);
+
+        let p2pkh = Script::from_hex("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac").unwrap();
+        assert_eq!(p2pkh.count_sigops(true), 1);
+
+        // 0xac inside a push is data, and a multisig after a push is not accurate-countable.
+        let script =
+            Script::from_asm_str("acacac OP_CHECKSIGVERIFY acad OP_CHECKMULTISIGVERIFY").unwrap();
+        assert_eq!(script.count_sigops(true), 21);
+        // Counting stops at the truncated push.
+        assert_eq!(
+            Script::new(vec![0xAC, 0xAE, 0x05, 0xAC]).count_sigops(false),
+            21
+        );
+    }
+
+    #[test]
+    fn test_legacy_sigop_count() {
+        let mut tx = BitcoinTransaction::new(
+            1,
+            vec![
+                TransactionInput::new(
+                    OutPoint::new(dummy_txid(1), 0),
+                    Script::new(vec![0xAC, 0xAD]),
+                    0xFFFFFFFF,
+                ),
+                TransactionInput::new(
+                    OutPoint::new(dummy_txid(2), 0),
+                    Script::new(vec![0x52, 0xAE]),
+                    0xFFFFFFFF,
+                ),
+            ],
+            0,
+        );
+        assert_eq!(tx.legacy_sigop_count(), 22);
+        // Output scripts count too: a P2PK output has one CHECKSIG, P2PKH one more.
+        tx.outputs.push(TransactionOutput::new(
+            Amount::from_sat(1_000),
+            Script::from_hex(&format!("21{G_COMPRESSED}ac")).unwrap(),
+        ));
+        tx.outputs.push(TransactionOutput::new(
+            Amount::from_sat(1_000),
+            Script::new_p2pkh(&Hash160([0x11; 20])),
+        ));
+        assert_eq!(tx.legacy_sigop_count(), 24);
+        tx.inputs.clear();
+        assert_eq!(tx.legacy_sigop_count(), 2);
+        tx.outputs.clear();
+        assert_eq!(tx.legacy_sigop_count(), 0);
+    }
+
+    #[test]
+    fn test_script_new_multisig() {
+        let keys: Vec<Vec<u8>> = [
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+        ]
+        .iter()
+        .map(|key| hex::decode(key).unwrap())
+        .collect();
+
+        let script = Script::new_multisig(2, &keys).unwrap();
+        assert_eq!(
+            script.to_hex(),
+            "52\
+             210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+             2102c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5\
+             2102f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9\
+             53ae"
+        );
+        assert_eq!(script.classify(), ScriptType::Multisig);
+        assert_eq!(script.count_sigops(true), 3);
+        let (required, parsed) = script.parse_multisig().unwrap();
+        assert_eq!(required, 2);
+        assert_eq!(parsed, keys.iter().map(Vec::as_slice).collect::<Vec<_>>());
+
+        // BIP67 2-of-2 vector, redeemed by 39bgKC7RFbpoCRbtD5KEdkYKtNyhpsNa3Z.
+        let bip67 = Script::new_multisig(
+            2,
+            &[
+                hex::decode("02fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f")
+                    .unwrap(),
+                hex::decode("02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8")
+                    .unwrap(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            bip67.script_hash().to_hex_internal(),
+            "56be8ea93912f37685542a2a864a5600f88a6754"
+        );
+    }
+
+    #[test]
+    fn test_script_new_multisig_errors() {
+        let key = [0x02; 33];
+        assert_eq!(
+            Script::new_multisig(3, &[key, key]),
+            Err(BitcoinError::InvalidFormat)
+        );
+        assert_eq!(
+            Script::new_multisig(0, &[key]),
+            Err(BitcoinError::InvalidFormat)
+        );
+        assert_eq!(
+            Script::new_multisig(1, &[[0x02; 32]]),
+            Err(BitcoinError::InvalidFormat)
+        );
+        assert_eq!(
+            Script::new_multisig(1, &[key; 21]),
+            Err(BitcoinError::ValueTooLarge { value: 21, max: 20 })
+        );
+
+        assert_eq!(Script::new_op_return(b"").unwrap().parse_multisig(), None);
+    }
+
+    #[test]
+    fn test_script_multisig_over_16_keys_round_trip() {
+        let keys: Vec<[u8; 33]> = (1..=20u8)
+            .map(|i| {
+                let mut key = [i; 33];
+                key[0] = 0x02;
+                key
+            })
+            .collect();
+        for (required, total) in [(17, 17), (1, 17), (16, 20), (20, 20)] {
+            let script = Script::new_multisig(required, &keys[..total]).unwrap();
+            // Counts above 16 are minimal one-byte pushes.
+            if required > 16 {
+                assert_eq!(&script[..2], &[0x01, required as u8]);
+            }
+            let len = script.len();
+            assert_eq!(&script[len - 3..], &[0x01, total as u8, 0xae]);
+            let (parsed_required, parsed_keys) = script.parse_multisig().unwrap();
+            assert_eq!(parsed_required, required);
+            assert_eq!(
+                parsed_keys,
+                keys[..total].iter().map(|key| &key[..]).collect::<Vec<_>>()
+            );
+            // The template stops at 16 keys.
+            assert_eq!(script.classify(), ScriptType::NonStandard);
+        }
+
+        // Non-minimal pushes of the counts, and counts above 20, don't parse.
+        let mut script = Script::new_multisig(17, &keys[..17]).unwrap().to_vec();
+        let len = script.len();
+        script.splice(len - 3..len - 2, [0x4c, 0x01]);
+        assert_eq!(Script::new(script).parse_multisig(), None);
+        let mut builder = ScriptBuilder::new().push_int(1);
+        for key in keys.iter().chain(&keys[..1]) {
+            builder = builder.push_slice(key);
+        }
+        let too_many = builder
+            .push_int(21)
+            .push_opcode(opcodes::OP_CHECKMULTISIG)
+            .into_script();
+        assert_eq!(too_many.parse_multisig(), None);
+        let mut five = Script::new_multisig(1, &keys[..5]).unwrap().to_vec();
+        let len = five.len();
+        five.splice(len - 2..len - 1, [0x01, 0x05]);
+        assert_eq!(Script::new(five).parse_multisig(), None);
+    }
+
+    #[test]
+    fn test_script_op_return_data() {
+        // Omni Layer simple send of 0.5 USDT (property 31).
+        let omni = Script::from_hex("6a146f6d6e69000000000000001f000000002faf0800").unwrap();
+        let data = omni.op_return_data().unwrap();
+        assert_eq!(data.len(), 1);
+        assert_eq!(&data[0][..4], b"omni");
+        assert!(omni.is_standard_op_return());
+
+        assert_eq!(
+            Script::from_hex("6a").unwrap().op_return_data(),
+            Some(vec![])
+        );
+        assert_eq!(
+            Script::from_hex("6a02aabb0051604f")
+                .unwrap()
+                .op_return_data(),
+            Some(vec![&[0xAA, 0xBB][..], &[], &[1], &[16], &[0x81]])
+        );
+
+        // Anything that is not a well-formed push makes the payload unreadable.
+        assert_eq!(Script::from_hex("6a01aa76").unwrap().op_return_data(), None);
+        assert_eq!(Script::from_hex("6a50").unwrap().op_return_data(), None);
+        assert_eq!(Script::from_hex("6a05aa").unwrap().op_return_data(), None);
+        assert_eq!(Script::from_hex("0014aa").unwrap().op_return_data(), None);
+    }
+
+    #[test]
+    fn test_script_is_standard_op_return() {
+        let max = Script::new_op_return(&[0xAB; 80]).unwrap();
+        assert_eq!(max.len(), 83);
+        assert!(max.is_standard_op_return());
+
+        let mut over = vec![0x6A, 0x4C, 81];
+        over.extend_from_slice(&[0xAB; 81]);
+        assert!(!Script::new(over).is_standard_op_return());
+        assert!(Script::from_hex("6a").unwrap().is_standard_op_return());
+        assert!(!Script::from_hex("6a76").unwrap().is_standard_op_return());
+        assert!(!Script::from_hex("76a9").unwrap().is_standard_op_return());
+    }
+
+    #[test]
+    fn test_script_is_push_only() {
+        // P2SH 2-of-2 spend: OP_0 <sig> <sig> <redeem script>.
+        let redeem = Script::from_hex(
+            "522102fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f\
+             2102ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f852ae",
+        )
+        .unwrap();
+        let p2sh_spend = ScriptBuilder::new()
+            .push_opcode(opcodes::OP_0)
+            .push_slice(&[0x30; 71])
+            .push_slice(&[0x30; 72])
+            .push_slice(&redeem)
+            .into_script();
+        assert_eq!(p2sh_spend.len(), 146 + 1 + 71);
+        assert!(p2sh_spend.is_push_only());
+
+        let legacy =
+            Script::from_hex("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac").unwrap();
+        assert!(!legacy.is_push_only());
+        assert!(Script::from_hex("4f5160").unwrap().is_push_only());
+        assert!(Script::new(vec![]).is_push_only());
+        assert!(!Script::from_hex("50").unwrap().is_push_only());
+        assert!(!Script::from_hex("4d0100").unwrap().is_push_only());
+
+        let input = |script_sig: &Script| {
+            TransactionInput::new(OutPoint::new(dummy_txid(1), 0), script_sig.clone(), 0)
+        };
+        assert!(input(&p2sh_spend).has_push_only_script_sig());
+        let mut tx = BitcoinTransaction::new(1, vec![input(&p2sh_spend)], 0);
+        assert!(tx.all_script_sigs_push_only());
+        tx.inputs.push(input(&legacy));
+        assert!(!tx.all_script_sigs_push_only());
+    }
+
+    #[test]
+    fn test_script_size_limits() {
+        let at_limit = Script::new_checked(vec![0x61; 10_000]).unwrap();
+        assert!(at_limit.is_within_consensus_size());
+        assert_eq!(
+            Script::new_checked(vec![0x61; 10_001]),
+            Err(BitcoinError::ScriptTooLarge { size: 10_001 })
+        );
+        // Decoding stays permissive.
+        let oversized = Script::new(vec![0x61; 10_001]);
+        assert!(!oversized.is_within_consensus_size());
+        assert_eq!(
+            Script::from_bytes(&oversized.to_bytes()).unwrap().0,
+            oversized
+        );
+
+        let push = |len: usize| {
+            ScriptBuilder::new()
+                .push_slice(&vec![0xAB; len])
+                .into_script()
+        };
+        assert!(!push(520).has_oversized_element());
+        assert!(push(521).has_oversized_element());
+        assert!(
+            ScriptBuilder::new()
+                .push_opcode(opcodes::OP_DROP)
+                .push_slice(&[0xAB; 521])
+                .into_script()
+                .has_oversized_element()
+        );
+        // A truncated 521-byte push is malformed, not oversized.
+        let mut truncated = push(521).into_bytes();
+        truncated.pop();
+        assert!(!Script::new(truncated).has_oversized_element());
+    }
+
+    #[test]
+    fn test_script_ref_zero_copy() {
+        let p2wpkh = Script::from_hex("0014751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        let p2pkh = Script::from_hex("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac").unwrap();
+        let mut buffer = p2wpkh.to_bytes();
+        buffer.extend_from_slice(&p2pkh.to_bytes());
+
+        let before = alloc_counter::allocations();
+        let (first, rest) = ScriptRef::parse(&buffer).unwrap();
+        let (second, rest) = ScriptRef::parse(rest).unwrap();
+        let first_type = first.classify();
+        let second_type = second.classify();
+        let program = first.witness_program();
+        let sigops = second.count_sigops(true);
+        let pushes = second
+            .instructions()
+            .filter(|i| matches!(i, Ok(Instruction::PushBytes(_))))
+            .count();
+        assert_eq!(alloc_counter::allocations(), before);
+
+        assert!(rest.is_empty());
+        assert_eq!(first_type, ScriptType::P2WPKH);
+        assert_eq!(second_type, ScriptType::P2PKH);
+        assert_eq!(program, Some(&buffer[3..23]));
+        assert_eq!((sigops, pushes), (1, 1));
+        assert_eq!(first.len(), 22);
+        assert_eq!(first.to_hex(), p2wpkh.to_hex());
+    }
+
+    #[test]
+    fn test_script_ref_conversions() {
+        let script = Script::from_hex("6a0b68656c6c6f20776f726c64").unwrap();
+        let script_ref = script.as_ref_script();
+        assert_eq!(script, script_ref);
+        assert_eq!(Script::from(script_ref), script);
+        assert_eq!(format!("{script_ref}"), "6a0b68656c6c6f20776f726c64");
+        assert_eq!(
+            format!("{script_ref:?}"),
+            "ScriptRef(6a0b68656c6c6f20776f726c64)"
+        );
+        assert!(script_ref.is_push_only() == script.is_push_only());
+        assert_eq!(
+            ScriptRef::parse(&[0x05, 0xAA]),
+            Err(BitcoinError::InsufficientBytes)
+        );
+    }
+
+    #[test]
+    fn test_nested_segwit_detection() {
+        // BIP143 P2SH-P2WPKH example.
+        let script_sig =
+            Script::from_hex("16001479091972186c449eb1ded22b78e40d009bdf0089").unwrap();
+        let prev_script_pubkey =
+            Script::from_hex("a9144733f37cf4db86fbc2efed2500b4f4e49f31202387").unwrap();
+        let redeem_script = script_sig.redeem_script().unwrap();
+        assert_eq!(redeem_script.classify(), ScriptType::P2WPKH);
+
+        let input = TransactionInput::new(OutPoint::new(dummy_txid(1), 1), script_sig, 0xFFFFFFFE);
+        assert!(input.is_nested_segwit(&prev_script_pubkey));
+        // The same scriptSig against an output it does not commit to, or a non-P2SH output.
+        assert!(!input.is_nested_segwit(&Script::new_p2sh(&Hash160::ZERO)));
+        assert!(!input.is_nested_segwit(
+            &Script::from_hex("0014751e76e8199196d454941c45d1b3a323f1433bd6").unwrap()
+        ));
+
+        // A plain P2SH multisig spend reveals a redeem script that is not a witness program.
+        let multisig = Script::new_multisig(1, &[[0x02; 33]]).unwrap();
+        let script_sig = ScriptBuilder::new()
+            .push_opcode(opcodes::OP_0)
+            .push_slice(&[0x30; 71])
+            .push_slice(&multisig)
+            .into_script();
+        assert_eq!(script_sig.redeem_script(), Some(multisig.as_ref_script()));
+        let input = TransactionInput::new(OutPoint::new(dummy_txid(2), 0), script_sig, 0);
+        assert!(!input.is_nested_segwit(&Script::new_p2sh(&multisig.script_hash())));
+
+        assert_eq!(Script::from_hex("0051").unwrap().redeem_script(), None);
+        assert_eq!(Script::from_hex("01aa76").unwrap().redeem_script(), None);
+        assert_eq!(Script::new(vec![]).redeem_script(), None);
+    }
+
+    #[test]
+    fn test_script_to_minimal() {
+        let hash = "62e907b15cbf27d5425399ebf6f0fb50ebb88f18";
+        let non_minimal = Script::from_hex(&format!("76a94c14{hash}88ac")).unwrap();
+        let minimal = Script::from_hex(&format!("76a914{hash}88ac")).unwrap();
+        assert_ne!(non_minimal, minimal);
+        assert!(!non_minimal.is_minimal());
+        assert!(minimal.is_minimal());
+        assert_eq!(non_minimal.to_minimal().unwrap(), minimal);
+        assert_eq!(minimal.to_minimal().unwrap(), minimal);
+
+        // Single-byte numbers and empty pushes collapse into their opcodes.
+        let script = Script::from_hex("0105018101104c00011100").unwrap();
+        assert!(!script.is_minimal());
+        assert_eq!(script.to_minimal().unwrap().to_hex(), "554f6000011100");
+        assert!(script.to_minimal().unwrap().is_minimal());
+
+        let wide = Script::new([vec![0x4D, 0xFF, 0x00], vec![0xAB; 255]].concat());
+        assert_eq!(&wide.to_minimal().unwrap()[..2], &[0x4C, 0xFF]);
+        assert_eq!(
+            Script::from_hex("4c05aa").unwrap().to_minimal(),
+            Err(BitcoinError::InvalidFormat)
+        );
+        assert!(!Script::from_hex("4c05aa").unwrap().is_minimal());
+    }
+
+    #[test]
+    fn test_instruction_is_minimal_push() {
+        let minimal = |hex: &str| {
+            let script = Script::from_hex(hex).unwrap();
+            let encoded = script.instructions().next_with_opcode().unwrap().unwrap();
+            encoded.is_minimal_push()
+        };
+        let data = hex::encode([0xAB; 20]);
+        assert!(minimal(&format!("14{data}")));
+        assert!(!minimal(&format!("4c14{data}")));
+        assert!(minimal(&format!("4c4c{}", hex::encode([0xAB; 76]))));
+        assert!(!minimal("0107"));
+        assert!(minimal("57"));
+        assert!(minimal("76"));
+
+        let script = Script::from_hex("4c0101").unwrap();
+        let mut instructions = script.instructions();
+        let encoded = instructions.next_with_opcode().unwrap().unwrap();
+        assert_eq!(encoded.opcode(), opcodes::OP_PUSHDATA1);
+        assert_eq!(encoded.instruction(), Instruction::PushBytes(&[0x01]));
+        assert!(!encoded.is_minimal_push());
+    }
+
+    #[test]
+    fn test_script_locktime_requirement_htlc() {
+        let key = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        let hash = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        let htlc = Script::from_asm_str(&format!(
+            "OP_IF OP_SHA256 {hash} OP_EQUALVERIFY {key} \
+             OP_ELSE 20a107 OP_CHECKLOCKTIMEVERIFY OP_DROP {key} \
+             OP_ENDIF OP_CHECKSIG"
+        ))
+        .unwrap();
+        assert_eq!(
+            htlc.locktime_requirement(),
+            Some(LockTimeRequirement::Absolute(500_000))
+        );
+
+        let csv_vault = Script::from_asm_str(&format!(
+            "9000 OP_CHECKSEQUENCEVERIFY OP_DROP {key} OP_CHECKSIG"
+        ))
+        .unwrap();
+        assert_eq!(
+            csv_vault.locktime_requirement(),
+            Some(LockTimeRequirement::Relative(144))
+        );
+    }
+
+    #[test]
+    fn test_script_locktime_requirement_operands() {
+        let cltv_vault = Script::from_asm_str(
+            "OP_16 OP_CLTV OP_DROP OP_IF 80f0fa02 OP_CLTV OP_DROP OP_ENDIF OP_5 OP_CSV",
+        )
+        .unwrap();
+        assert_eq!(
+            cltv_vault.locktime_requirement(),
+            Some(LockTimeRequirement::Absolute(50_000_000))
+        );
+
+        let five_byte = ScriptBuilder::new()
+            .push_int(0x7F_FFFF_FFFF)
+            .push_opcode(opcodes::OP_CLTV)
+            .into_script();
+        assert_eq!(
+            five_byte.locktime_requirement(),
+            Some(LockTimeRequirement::Absolute(0x7F_FFFF_FFFF))
+        );
+
+        // Six-byte and non-minimal operands, a leading CLTV, and no timelock at all.
+        for asm in [
+            "ffffffffff00 OP_CLTV",
+            "0100 OP_CLTV",
+            "OP_CLTV",
+            "OP_DUP OP_DROP",
+        ] {
+            let script = Script::from_asm_str(asm).unwrap();
+            assert_eq!(script.locktime_requirement(), None, "{asm}");
+        }
+    }
+
+    #[test]
+    fn test_base58_vectors() {
+        assert_eq!(base58::encode(b""), "");
+        assert_eq!(base58::encode(b"hello world"), "StV1DL6CwTryKyV");
+        assert_eq!(base58::encode(&[0, 0, 0x28, 0x7F, 0xB4, 0xCD]), "11233QC4");
+        assert_eq!(
+            base58::decode("11233QC4").unwrap(),
+            [0, 0, 0x28, 0x7F, 0xB4, 0xCD]
+        );
+        assert_eq!(base58::decode("StV1DL6CwTryKyV").unwrap(), b"hello world");
+        assert_eq!(base58::decode("1").unwrap(), [0]);
+
+        assert_eq!(
+            base58::decode("0OIl"),
+            Err(BitcoinError::InvalidBase58Character('0'))
+        );
+        assert_eq!(
+            base58::decode("abc!"),
+            Err(BitcoinError::InvalidBase58Character('!'))
+        );
+
+        let zero_hash = base58::encode_check(&[0u8; 21]);
+        assert_eq!(zero_hash, "1111111111111111111114oLvT2");
+        assert_eq!(base58::decode_check(&zero_hash).unwrap(), [0u8; 21]);
+        assert_eq!(
+            base58::decode_check("1111111111111111111114oLvT3"),
+            Err(BitcoinError::InvalidChecksum)
+        );
+        assert_eq!(
+            base58::decode_check("1"),
+            Err(BitcoinError::InsufficientBytes)
+        );
+    }
+
+    #[test]
+    fn test_address_base58_roundtrip() {
+        let cases = [
+            (
+                "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+                Network::Mainnet,
+                "76a91477bff20c60e522dfaa3350c39b030a5d004e839a88ac",
+            ),
+            (
+                "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+                Network::Mainnet,
+                "a914b472a266d0bd89c13706a4132ccfb16f7c3b9fcb87",
+            ),
+            (
+                "mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn",
+                Network::Testnet,
+                "76a914243f1394f44554f4ce3fd68649c19adc483ce92488ac",
+            ),
+            (
+                "2MzQwSSnBHWHqSAqtTVQ6v47XtaisrJa1Vc",
+                Network::Testnet,
+                "a9144e9f39ca4688ff102128ea4ccda34105324305b087",
+            ),
+        ];
+        for (text, network, script_hex) in cases {
+            let address: Address = text.parse().unwrap();
+            assert_eq!(address.to_string(), text);
+            assert_eq!(address.script_pubkey().to_hex(), script_hex);
+
+            let script = Script::from_hex(script_hex).unwrap();
+            assert_eq!(Address::from_script(&script, network).unwrap(), address);
+        }
+
+        // Signet and regtest share the testnet prefixes.
+        let script =
+            Script::from_hex("76a914243f1394f44554f4ce3fd68649c19adc483ce92488ac").unwrap();
+        let signet = Address::from_script(&script, Network::Signet).unwrap();
+        assert_eq!(signet.to_string(), "mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn");
+    }
+
+    #[test]
+    fn test_address_base58_errors() {
+        assert_eq!(
+            "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3".parse::<Address>(),
+            Err(BitcoinError::InvalidChecksum)
+        );
+        assert_eq!(
+            "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN0".parse::<Address>(),
+            Err(BitcoinError::InvalidBase58Character('0'))
+        );
+        // Valid Base58Check with an unknown version byte, and with a 19-byte hash.
+        let unknown_version = base58::encode_check(&[[0x30].as_slice(), &[0xAB; 20]].concat());
+        assert_eq!(
+            unknown_version.parse::<Address>(),
+            Err(BitcoinError::InvalidFormat)
+        );
+        let short = base58::encode_check(&[0x00; 20]);
+        assert_eq!(short.parse::<Address>(), Err(BitcoinError::InvalidFormat));
+
+        let op_return = Script::new_op_return(b"data").unwrap();
+        assert_eq!(
+            Address::from_script(&op_return, Network::Mainnet),
+            Err(BitcoinError::UnsupportedScript)
+        );
+    }
+
+    #[test]
+    fn test_address_bech32_valid_vectors() {
+        // BIP173 and BIP350 valid segwit addresses with their scriptPubKeys.
+        let vectors = [
+            (
+                "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+                "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+            ),
+            (
+                "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+                "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+            ),
+            (
+                "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y",
+                "5128751e76e8199196d454941c45d1b3a323f1433bd6751e76e8199196d454941c45d1b3a323f1433bd6",
+            ),
+            ("BC1SW50QGDZ25J", "6002751e"),
+            (
+                "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs",
+                "5210751e76e8199196d454941c45d1b3a323",
+            ),
+            (
+                "tb1qqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesrxh6hy",
+                "0020000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433",
+            ),
+            (
+                "tb1pqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesf3hn0c",
+                "5120000000c4a5cad46221b2a187905e5266362b99d5e91c6ce24d165dab93e86433",
+            ),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+                "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            ),
+        ];
+        for (text, script_hex) in vectors {
+            let address: Address = text.parse().unwrap();
+            assert_eq!(address.script_pubkey().to_hex(), script_hex, "{text}");
+            assert_eq!(address.to_string(), text.to_ascii_lowercase());
+
+            let network = if text.to_ascii_lowercase().starts_with("bc") {
+                Network::Mainnet
+            } else {
+                Network::Testnet
+            };
+            let script = Script::from_hex(script_hex).unwrap();
+            assert_eq!(Address::from_script(&script, network).unwrap(), address);
+        }
+
+        let script = Script::from_hex("0014751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        let regtest = Address::from_script(&script, Network::Regtest).unwrap();
+        let text = regtest.to_string();
+        assert!(text.starts_with("bcrt1q"));
+        assert_eq!(text.parse::<Address>().unwrap(), regtest);
+    }
+
+    #[test]
+    fn test_address_bech32_invalid_vectors() {
+        use BitcoinError::*;
+
+        // BIP173 and BIP350 invalid segwit addresses.
+        let vectors = [
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+                InvalidChecksum,
+            ),
+            (
+                "tb1z0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqglt7rf",
+                InvalidChecksum,
+            ),
+            (
+                "BC1S0XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ54WELL",
+                InvalidChecksum,
+            ),
+            (
+                "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh",
+                InvalidChecksum,
+            ),
+            (
+                "tb1q0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq24jc47",
+                InvalidChecksum,
+            ),
+            (
+                "bc1p38j9r5y49hruaue7wxjce0updqjuyyx0kh56v8s25huc6995vvpql3jow4",
+                InvalidBech32Character('o'),
+            ),
+            (
+                "BC130XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ7ZWS8R",
+                InvalidWitnessVersion(17),
+            ),
+            ("bc1pw5dgrnzv", InvalidWitnessProgramLength(1)),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v8n0nx0muaewav253zgeav",
+                InvalidWitnessProgramLength(41),
+            ),
+            (
+                "BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P",
+                InvalidWitnessProgramLength(16),
+            ),
+            (
+                "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq47Zagq",
+                MixedCase,
+            ),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v07qwwzcrf",
+                InvalidFormat,
+            ),
+            (
+                "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vpggkg4j",
+                InvalidFormat,
+            ),
+            ("bc1zw508d6qejxtdg4y5r3zarvaryvqyzf3du", InvalidFormat),
+            (
+                "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3pjxtptv",
+                InvalidFormat,
+            ),
+            ("bc1gmk9yu", InvalidFormat),
+        ];
+        for (text, error) in vectors {
+            assert_eq!(text.parse::<Address>(), Err(error), "{text}");
+        }
+        // Unknown HRP.
+        assert!(
+            "tc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq5zuyut"
+                .parse::<Address>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_bech32_generic_roundtrip() {
+        let data = bech32::convert_bits(b"hello", 8, 5, true).unwrap();
+        let encoded = bech32::encode("test", &data, bech32::Variant::Bech32m);
+        let (hrp, decoded, variant) = bech32::decode(&encoded).unwrap();
+        assert_eq!((hrp.as_str(), variant), ("test", bech32::Variant::Bech32m));
+        assert_eq!(
+            bech32::convert_bits(&decoded, 5, 8, false).unwrap(),
+            b"hello"
+        );
+
+        // BIP173 valid bech32 strings.
+        assert!(bech32::decode("A12UEL5L").is_ok());
+        assert!(bech32::decode("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw").is_ok());
+        assert_eq!(bech32::decode("1nwldj5"), Err(BitcoinError::InvalidFormat));
+        assert_eq!(
+            bech32::decode("a12uel5l").map(|(_, _, v)| v),
+            Ok(bech32::Variant::Bech32)
+        );
+    }
+
+    const G_COMPRESSED: &str = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+    const G_UNCOMPRESSED: &str = "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+
+    #[test]
+    fn test_public_key_addresses() {
+        let compressed: PublicKey = G_COMPRESSED.parse().unwrap();
+        assert!(compressed.is_compressed());
+        assert_eq!(compressed.to_string(), G_COMPRESSED);
+        assert_eq!(
+            compressed.pubkey_hash().to_hex_internal(),
+            "751e76e8199196d454941c45d1b3a323f1433bd6"
+        );
+        let p2pkh = Script::new_p2pkh_from_key(&compressed);
+        assert_eq!(
+            Address::from_script(&p2pkh, Network::Mainnet)
+                .unwrap()
+                .to_string(),
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+        );
+        let p2wpkh = Script::new_p2wpkh_from_key(&compressed).unwrap();
+        assert_eq!(
+            Address::from_script(&p2wpkh, Network::Mainnet)
+                .unwrap()
+                .to_string(),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+
+        let uncompressed: PublicKey = G_UNCOMPRESSED.parse().unwrap();
+        assert!(!uncompressed.is_compressed());
+        assert_eq!(uncompressed.to_bytes().len(), 65);
+        let p2pkh = Script::new_p2pkh_from_key(&uncompressed);
+        assert_eq!(
+            Address::from_script(&p2pkh, Network::Mainnet)
+                .unwrap()
+                .to_string(),
+            "1EHNa6Q4Jz2uvNExL497mE43ikXhwF6kZm"
+        );
+        assert_eq!(
+            Script::new_p2wpkh_from_key(&uncompressed),
+            Err(BitcoinError::UncompressedKey)
+        );
+
+        // Keys plug straight into multisig construction.
+        let multisig = Script::new_multisig(1, &[compressed, uncompressed]).unwrap();
+        assert_eq!(
+            multisig.parse_multisig().unwrap().1[1],
+            uncompressed.as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_public_key_validation_and_serde() {
+        let mut bytes = hex::decode(G_COMPRESSED).unwrap();
+        bytes[0] = 0x04;
+        assert_eq!(
+            PublicKey::from_slice(&bytes),
+            Err(BitcoinError::InvalidPublicKey)
+        );
+        let mut hybrid = hex::decode(G_UNCOMPRESSED).unwrap();
+        hybrid[0] = 0x06;
+        assert_eq!(
+            PublicKey::from_slice(&hybrid),
+            Err(BitcoinError::InvalidPublicKey)
+        );
+        assert_eq!(
+            PublicKey::from_slice(&[0x02; 32]),
+            Err(BitcoinError::InvalidPublicKey)
+        );
+        assert_eq!(
+            PublicKey::from_slice(&[]),
+            Err(BitcoinError::InvalidPublicKey)
+        );
+        assert_eq!(
+            "02zz".parse::<PublicKey>(),
+            Err(BitcoinError::InvalidFormat)
+        );
+
+        let key: PublicKey = G_COMPRESSED.parse().unwrap();
+        assert_eq!(format!("{key:?}"), format!("PublicKey({G_COMPRESSED})"));
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(json, format!("\"{G_COMPRESSED}\""));
+        assert_eq!(serde_json::from_str::<PublicKey>(&json).unwrap(), key);
+        assert!(serde_json::from_str::<PublicKey>("\"0479\"").is_err());
+
+        let encoded = bincode::serialize(&key).unwrap();
+        assert_eq!(encoded.len(), 8 + 33);
+        assert_eq!(bincode::deserialize::<PublicKey>(&encoded).unwrap(), key);
+    }
+
+    #[test]
+    fn test_xonly_public_key_p2tr() {
+        // BIP341 wallet test vector: key-path-only output for internal key d6889cb0...
+        let output_key = XOnlyPublicKey::from_hex(
+            "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343",
+        )
+        .unwrap();
+        let script = Script::new_p2tr(&output_key);
+        assert_eq!(
+            script.to_hex(),
+            "512053a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343"
+        );
+        assert_eq!(
+            Address::from_script(&script, Network::Mainnet)
+                .unwrap()
+                .to_string(),
+            "bc1p2wsldez5mud2yam29q22wgfh9439spgduvct83k3pm50fcxa5dps59h4z5"
+        );
+        assert_eq!(script.p2tr_output_key(), Some(output_key));
+        assert_eq!(
+            XOnlyPublicKey::from_slice(&script[2..]).unwrap().to_bytes(),
+            output_key.0
+        );
+
+        let p2wsh = Script::new_p2wsh(&Hash256::from_bytes(output_key.0));
+        assert_eq!(p2wsh.p2tr_output_key(), None);
+        assert_eq!(
+            XOnlyPublicKey::from_slice(&[0x02; 33]),
+            Err(BitcoinError::InvalidPublicKey)
+        );
+
+        let json = serde_json::to_string(&output_key).unwrap();
+        assert_eq!(
+            json,
+            "\"53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343\""
+        );
+        assert_eq!(
+            serde_json::from_str::<XOnlyPublicKey>(&json).unwrap(),
+            output_key
+        );
+    }
+
+    // Input signature of the block 170 transaction f4184fc5..., with SIGHASH_ALL.
+    const BLOCK_170_SIG: &str = "304402204e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd410220181522ec8eca07de4860a4acdd12909d831cc56cbbac4622082221a8768d1d0901";
+    // Transaction 1 of mainnet block 702861: one P2WPKH input paying a P2WSH and a P2WPKH output.
+    const BLOCK_702861_P2WPKH_TX: &str = "0200000000010153b15e6e3000174bb3d6a71c7531be172dd77b1d0f5a9e6eed6a9d345c37d5520100000000\
+        feffffff021b74060000000000220020de4d09dc9cb0fca2e71f96b79871fc991310bc0c9eba10e93ca49469\
+        6face92ad30e23000000000016001430691905e1f530940c645d767013f931cc97c8bb0247304402202d39fc\
+        e145204e88fa3a8d398b9ff2ec4c4c8bf02da3b751650a80ddb26fcb310220689651c87e08674627d82612a3\
+        b4040e42991240792743a2cd9b9942482265b9012102dfaba46d2417eee4661d45a6ab44f15cf2c77377045c\
+        678c926142b6b611ab9e8cb90a00";
+    // BLOCK_702861_P2WPKH_TX in `decoderawtransaction`'s format, written out by hand rather
+    // than captured from a node; the descriptor checksums come from BIP380's reference code.
+    const BLOCK_702861_P2WPKH_TX_RPC: &str = r#"{
+      "txid": "7bf717689b9033eafb2f3272719989b304bb7db616c2bfb5ded2e1b76d50a4f0",
+      "hash": "16280b1cc1ed358983b12745b1a90a9eb1e9bf060f8c7d5ea1f2ebc58be9f3cc",
+      "version": 2,
+      "size": 234,
+      "vsize": 153,
+      "weight": 609,
+      "locktime": 702860,
+      "vin": [
+        {
+          "txid": "52d5375c349d6aed6e9e5a0f1d7bd72d17be31751ca7d6b34b1700306e5eb153",
+          "vout": 1,
+          "scriptSig": {
+            "asm": "",
+            "hex": ""
+          },
+          "txinwitness": [
+            "304402202d39fce145204e88fa3a8d398b9ff2ec4c4c8bf02da3b751650a80ddb26fcb310220689651c87e08674627d82612a3b4040e42991240792743a2cd9b9942482265b901",
+            "02dfaba46d2417eee4661d45a6ab44f15cf2c77377045c678c926142b6b611ab9e"
+          ],
+          "sequence": 4294967294
+        }
+      ],
+      "vout": [
+        {
+          "value": 0.00422939,
+          "n": 0,
+          "scriptPubKey": {
+            "asm": "0 de4d09dc9cb0fca2e71f96b79871fc991310bc0c9eba10e93ca494696face92a",
+            "desc": "addr(bc1qmexsnhyukr729eclj6mesu0unyf3p0qvn6app6fu5j2xjmavay4qrx02ge)#muhj86y4",
+            "hex": "0020de4d09dc9cb0fca2e71f96b79871fc991310bc0c9eba10e93ca494696face92a",
+            "address": "bc1qmexsnhyukr729eclj6mesu0unyf3p0qvn6app6fu5j2xjmavay4qrx02ge",
+            "type": "witness_v0_scripthash"
+          }
+        },
+        {
+          "value": 0.02297555,
+          "n": 1,
+          "scriptPubKey": {
+            "asm": "0 30691905e1f530940c645d767013f931cc97c8bb",
+            "desc": "addr(bc1qxp53jp0p75cfgrryt4m8qylex8xf0j9mum8gpl)#knchnlfj",
+            "hex": "001430691905e1f530940c645d767013f931cc97c8bb",
+            "address": "bc1qxp53jp0p75cfgrryt4m8qylex8xf0j9mum8gpl",
+            "type": "witness_v0_keyhash"
+          }
+        }
+      ]
+    }"#;
+    // The block 170 transaction f4184fc5..., paying 10 BTC to Hal Finney and 40 BTC back.
+    const BLOCK_170_TX: &str = "0100000001c997a5e56e104102fa209c6a852dd90660a20b2d9c352423edce25857fcd37040000\
+        00004847304402204e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd410220181522ec\
+        8eca07de4860a4acdd12909d831cc56cbbac4622082221a8768d1d0901ffffffff0200ca9a3b0000000043410\
+        4ae1a62fe09c5f51b13905f07f06b99a2f7159b2225f374cd378d71302fa28414e7aab37397f554a7df5f142c21c\
+        1b7303b8a0626f1baded5c72a704f7e6cd84cac00286bee0000000043410411db93e1dcdb8a016b49840f8c53bc1e\
+        b68a382e97b1482ecad7b148a6909a5cb2e0eaddfb84ccf9744464f82e160bfa9b8b64f9d4c03f999b8643f656b41\
+        2a3ac00000000";
+
+    #[test]
+    fn test_signature_from_der_with_sighash() {
+        let bytes = hex::decode(BLOCK_170_SIG).unwrap();
+        let sig = Signature::from_der_with_sighash(&bytes).unwrap();
+        assert_eq!(
+            hex::encode(sig.r()),
+            "4e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd41"
+        );
+        assert_eq!(
+            hex::encode(sig.s()),
+            "181522ec8eca07de4860a4acdd12909d831cc56cbbac4622082221a8768d1d09"
+        );
+        assert_eq!(sig.sighash_type(), SighashType::All);
+        assert_eq!(sig.der(), &bytes[..bytes.len() - 1]);
+        assert_eq!(sig.to_bytes(), bytes);
+        assert!(sig.is_low_s());
+        assert!(sig.is_strict_der());
+
+        // Same signature with the high-S twin n - s.
+        let high_s = "304502204e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd41\
+                      022100e7eadd137135f821b79f5b5322ed6f6137921779f39c5a19b7b03ce459a9243801";
+        let sig = Signature::from_der_with_sighash(&hex::decode(high_s).unwrap()).unwrap();
+        assert!(!sig.is_low_s());
+        assert!(sig.is_strict_der());
+    }
+
+    #[test]
+    fn test_signature_rejects_malformed_der() {
+        let parse = |hex: String| Signature::from_der_with_sighash(&hex::decode(hex).unwrap());
+        let r = "4e45e16932b8af514961a1d3a1a25fdf3f4f7732e9d624c6c61548ab5fb8cd41";
+        let s = "181522ec8eca07de4860a4acdd12909d831cc56cbbac4622082221a8768d1d09";
+
+        // A 33-byte r with its top bit set is a negative integer.
+        let negative_r = format!("3045022180{r}0220{s}01");
+        assert_eq!(
+            parse(negative_r).unwrap_err(),
+            BitcoinError::InvalidSignature
+        );
+        // Wrong sequence tag, sequence length, integer tag, and trailing garbage.
+        for der in [
+            format!("31440220{r}0220{s}"),
+            format!("30450220{r}0220{s}"),
+            format!("30440320{r}0220{s}"),
+            format!("30460220{r}0220{s}0000"),
+            "3024".to_string(),
+        ] {
+            assert_eq!(
+                parse(format!("{der}01")).unwrap_err(),
+                BitcoinError::InvalidSignature,
+                "{der}"
+            );
+        }
+        // Consensus accepts undefined sighash bytes: the byte is kept, and its low bits
+        // select the type.
+        let undefined = parse(format!("30440220{r}0220{s}04")).unwrap();
+        assert_eq!(undefined.sighash_byte(), 0x04);
+        assert_eq!(undefined.sighash_type(), SighashType::All);
+        let undefined = parse(format!("30440220{r}0220{s}c2")).unwrap();
+        assert_eq!(undefined.sighash_byte(), 0xc2);
+        assert_eq!(undefined.sighash_type(), SighashType::NonePlusAnyoneCanPay);
+
+        // Excess zero padding parses but is not strict DER.
+        let padded = parse(format!("3045022100{r}0220{s}81")).unwrap();
+        assert!(!padded.is_strict_der());
+        assert_eq!(padded.sighash_type(), SighashType::AllPlusAnyoneCanPay);
+        assert!(padded.sighash_type().is_anyone_can_pay());
+    }
+
+    #[test]
+    fn test_sighash_type_bytes() {
+        for byte in [0x01, 0x02, 0x03, 0x81, 0x82, 0x83] {
+            assert_eq!(SighashType::from_u8(byte).unwrap().to_u8(), byte);
+        }
+        assert_eq!(
+            SighashType::from_u8(0x00),
+            Err(BitcoinError::InvalidSighashType(0))
+        );
+        assert_eq!(
+            SighashType::SinglePlusAnyoneCanPay.to_string(),
+            "SIGHASH_SINGLE|ANYONECANPAY"
+        );
+    }
+
+    #[test]
+    fn test_sighash_type_u32_masking() {
+        // 0x83 is standard; with the unused 0x40 bit or high bits set it is only
+        // consensus-valid, and signs as SINGLE|ANYONECANPAY.
+        assert_eq!(
+            SighashType::from_u32(0x83),
+            SighashType::SinglePlusAnyoneCanPay
+        );
+        assert_eq!(
+            SighashType::from_standard_u32(0x83),
+            Ok(SighashType::SinglePlusAnyoneCanPay)
+        );
+        for n in [0xc3, 0x183, 0x8000_0083] {
+            assert_eq!(
+                SighashType::from_u32(n),
+                SighashType::SinglePlusAnyoneCanPay
+            );
+            assert_eq!(
+                SighashType::from_standard_u32(n),
+                Err(BitcoinError::NonStandardSighashType(n))
+            );
+        }
+        // Undefined base types count as ALL.
+        assert_eq!(SighashType::from_u32(0x00), SighashType::All);
+        assert_eq!(
+            SighashType::from_u32(0x84),
+            SighashType::AllPlusAnyoneCanPay
+        );
+        assert!(SighashType::from_standard_u32(0x00).is_err());
+        for byte in [0x01, 0x02, 0x03, 0x81, 0x82, 0x83] {
+            let sighash_type = SighashType::from_u8(byte).unwrap();
+            assert_eq!(SighashType::from_u32(sighash_type.to_u32()), sighash_type);
+        }
+    }
+
+    #[test]
+    fn test_sighash_type_strings() {
+        for byte in [0x01, 0x02, 0x03, 0x81, 0x82, 0x83] {
+            let sighash_type = SighashType::from_u8(byte).unwrap();
+            assert_eq!(sighash_type.to_string().parse(), Ok(sighash_type));
+            let json = serde_json::to_string(&sighash_type).unwrap();
+            assert_eq!(json, format!("\"{sighash_type}\""));
+            assert_eq!(
+                serde_json::from_str::<SighashType>(&json).unwrap(),
+                sighash_type
+            );
+        }
+        assert_eq!(SighashType::All.to_string(), "SIGHASH_ALL");
+        assert_eq!(
+            "SIGHASH_NONE|ANYONECANPAY".parse(),
+            Ok(SighashType::NonePlusAnyoneCanPay)
+        );
+        assert_eq!(
+            "ALL".parse::<SighashType>(),
+            Err(BitcoinError::InvalidFormat)
+        );
+        assert!(serde_json::from_str::<SighashType>("\"SIGHASH_DEFAULT\"").is_err());
+
+        assert_eq!(TapSighashType::default(), TapSighashType::Default);
+        assert_eq!(TapSighashType::Default.to_string(), "SIGHASH_DEFAULT");
+        assert_eq!(
+            TapSighashType::SinglePlusAnyoneCanPay.to_string(),
+            "SIGHASH_SINGLE|ANYONECANPAY"
+        );
+        for byte in [0x00, 0x01, 0x02, 0x03, 0x81, 0x82, 0x83] {
+            let sighash_type = TapSighashType::from_u8(byte).unwrap();
+            assert_eq!(sighash_type.to_u8(), byte);
+            assert_eq!(sighash_type.to_string().parse(), Ok(sighash_type));
+            let json = serde_json::to_value(sighash_type).unwrap();
+            assert_eq!(
+                serde_json::from_value::<TapSighashType>(json).unwrap(),
+                sighash_type
+            );
+        }
+        assert_eq!(
+            TapSighashType::from_u8(0x84),
+            Err(BitcoinError::InvalidSighashType(0x84))
+        );
+    }
+
+    #[test]
+    fn test_private_key_wif() {
+        let key = hex::decode("0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d")
+            .unwrap();
+        let cases = [
+            (
+                "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ",
+                Network::Mainnet,
+                false,
+            ),
+            (
+                "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617",
+                Network::Mainnet,
+                true,
+            ),
+            (
+                "91gGn1HgSap6CbU12F6z3pJri26xzp7Ay1VW6NHCoEayNXwRpu2",
+                Network::Testnet,
+                false,
+            ),
+            (
+                "cMzLdeGd5vEqxB8B6VFQoRopQ3sLAAvEzDAoQgvX54xwofSWj1fx",
+                Network::Testnet,
+                true,
+            ),
+        ];
+        for (wif, network, compressed) in cases {
+            let parsed = PrivateKey::from_wif(wif).unwrap();
+            assert_eq!(parsed.network(), network);
+            assert_eq!(parsed.compressed(), compressed);
+            assert_eq!(parsed.as_bytes().as_slice(), key.as_slice());
+            assert_eq!(parsed.to_wif(network), wif);
+        }
+
+        let uncompressed = PrivateKey::from_wif(cases[0].0).unwrap();
+        assert_eq!(uncompressed.to_wif(Network::Signet), cases[2].0);
+        assert!(!format!("{:?}", uncompressed).contains("0c28fca3"));
+    }
+
+    #[test]
+    fn test_private_key_rejects_bad_wif() {
+        // 31-byte key, and a 33-byte payload whose suffix is not 0x01.
+        assert_eq!(
+            PrivateKey::from_wif("yPoVP5njSzmEVK4VJGRWWAwqnwCyLPRcMm5XyrKgY1DE64xhu"),
+            Err(BitcoinError::InvalidPrivateKey)
+        );
+        assert_eq!(
+            PrivateKey::from_wif("KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvWxyf5d"),
+            Err(BitcoinError::InvalidPrivateKey)
+        );
+        // Valid checksum over a P2SH version byte.
+        assert_eq!(
+            PrivateKey::from_wif("Ap5VkGD32Z6edUxtbsArn1eRKzvt2vGqSvK1KKSBu2DtzaGDYY"),
+            Err(BitcoinError::InvalidFormat)
+        );
+        assert_eq!(
+            PrivateKey::from_wif("5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTK"),
+            Err(BitcoinError::InvalidChecksum)
+        );
+    }
+
+    #[test]
+    fn test_address_type_and_network() {
+        let cases = [
+            (
+                "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+                AddressType::P2pkh,
+                Network::Mainnet,
+            ),
+            (
+                "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+                AddressType::P2sh,
+                Network::Mainnet,
+            ),
+            (
+                "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+                AddressType::P2wpkh,
+                Network::Mainnet,
+            ),
+            (
+                "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3",
+                AddressType::P2wsh,
+                Network::Mainnet,
+            ),
+            (
+                "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr",
+                AddressType::P2tr,
+                Network::Mainnet,
+            ),
+            (
+                "mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn",
+                AddressType::P2pkh,
+                Network::Testnet,
+            ),
+            (
+                "2MzQwSSnBHWHqSAqtTVQ6v47XtaisrJa1Vc",
+                AddressType::P2sh,
+                Network::Testnet,
+            ),
+            (
+                "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+                AddressType::P2wpkh,
+                Network::Testnet,
+            ),
+            (
+                "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+                AddressType::P2wsh,
+                Network::Testnet,
+            ),
+            (
+                "tb1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqp3mvzv",
+                AddressType::P2tr,
+                Network::Testnet,
+            ),
+        ];
+        for (s, address_type, network) in cases {
+            let address: Address = s.parse().unwrap();
+            assert_eq!(address.address_type(), address_type, "{}", s);
+            assert_eq!(address.network(), network, "{}", s);
+            assert!(address.is_valid_for_network(network));
+            assert!(address.is_valid_for_network(Network::Signet) != (network == Network::Mainnet));
+            assert_eq!(Address::from_str_for_network(s, network).unwrap(), address);
+        }
+
+        let witness_v2: Address = "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs".parse().unwrap();
+        assert_eq!(witness_v2.address_type(), AddressType::WitnessUnknown);
+    }
+
+    #[test]
+    fn test_address_network_mismatch() {
+        assert_eq!(
+            Address::from_str_for_network("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2", Network::Testnet),
+            Err(BitcoinError::NetworkMismatch {
+                expected: Network::Testnet,
+                found: Network::Mainnet,
+            })
+        );
+        assert_eq!(
+            Address::from_str_for_network(
+                "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+                Network::Mainnet
+            ),
+            Err(BitcoinError::NetworkMismatch {
+                expected: Network::Mainnet,
+                found: Network::Testnet,
+            })
+        );
+        // Regtest has its own HRP but shares the testnet base58 prefixes.
+        assert!(
+            Address::from_str_for_network(
+                "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+                Network::Regtest
+            )
+            .is_err()
+        );
+        let regtest =
+            Address::from_str_for_network("mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn", Network::Regtest)
+                .unwrap();
+        assert_eq!(regtest.network(), Network::Regtest);
+        let bcrt: Address = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080"
+            .parse()
+            .unwrap();
+        assert!(bcrt.is_valid_for_network(Network::Regtest));
+        assert!(!bcrt.is_valid_for_network(Network::Testnet));
+    }
+
+    #[test]
+    fn test_input_address_uses_prev_script() {
+        // BIP143 P2SH-P2WPKH example: the scriptSig is a witness program, but the input is
+        // attributed to the P2SH output it spends.
+        let script_sig =
+            Script::from_hex("16001479091972186c449eb1ded22b78e40d009bdf0089").unwrap();
+        let prev_script =
+            Script::from_hex("a9144733f37cf4db86fbc2efed2500b4f4e49f31202387").unwrap();
+        let input = TransactionInput::new(OutPoint::new(dummy_txid(1), 1), script_sig, 0xFFFFFFFE);
+        let address = input.input_address(&prev_script, Network::Mainnet).unwrap();
+        assert_eq!(address.to_string(), "38BW8nqpHSWpkf5sXrQd2xYwvnPJwP59ic");
+
+        // Bare pay-to-pubkey outputs, like the genesis coinbase, have no address.
+        let genesis_p2pk = ScriptBuilder::new()
+            .push_slice(&hex::decode(GENESIS_PUBKEY).unwrap())
+            .push_opcode(opcodes::OP_CHECKSIG)
+            .into_script();
+        assert_eq!(input.input_address(&genesis_p2pk, Network::Mainnet), None);
+        assert_eq!(
+            input.input_address(&Script::new(vec![0x51]), Network::Mainnet),
+            None
+        );
+    }
+
+    #[test]
+    fn test_key_and_script_address_helpers() {
+        let compressed = G_COMPRESSED.parse::<PublicKey>().unwrap();
+        let uncompressed = G_UNCOMPRESSED.parse::<PublicKey>().unwrap();
+        for network in [Network::Mainnet, Network::Testnet] {
+            for key in [compressed, uncompressed] {
+                assert_eq!(
+                    key.p2pkh_address(network),
+                    Address::from_script(&Script::new_p2pkh_from_key(&key), network).unwrap()
+                );
+            }
+            assert_eq!(
+                compressed.p2wpkh_address(network).unwrap(),
+                Address::from_script(&Script::new_p2wpkh_from_key(&compressed).unwrap(), network)
+                    .unwrap()
+            );
+
+            let witness_script = Script::from_hex(&format!("21{}ac", G_COMPRESSED)).unwrap();
+            assert_eq!(
+                witness_script.p2sh_address(network),
+                Address::from_script(&Script::new_p2sh(&witness_script.script_hash()), network)
+                    .unwrap()
+            );
+            assert_eq!(
+                witness_script.p2wsh_address(network),
+                Address::from_script(&Script::new_p2wsh(&witness_script.wscript_hash()), network)
+                    .unwrap()
+            );
+        }
+
+        assert_eq!(
+            uncompressed.p2pkh_address(Network::Mainnet).to_string(),
+            "1EHNa6Q4Jz2uvNExL497mE43ikXhwF6kZm"
+        );
+        assert_eq!(
+            compressed
+                .p2wpkh_address(Network::Mainnet)
+                .unwrap()
+                .to_string(),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(
+            uncompressed.p2wpkh_address(Network::Mainnet),
+            Err(BitcoinError::UncompressedKey)
+        );
+        let witness_script = Script::from_hex(&format!("21{}ac", G_COMPRESSED)).unwrap();
+        assert_eq!(
+            witness_script.p2wsh_address(Network::Mainnet).to_string(),
+            "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3"
+        );
+    }
+
+    #[test]
+    fn test_witness_roundtrip() {
+        let sig = hex::decode(BLOCK_170_SIG).unwrap();
+        let pubkey = hex::decode(G_COMPRESSED).unwrap();
+        let mut witness = Witness::default();
+        witness.push(&sig);
+        witness.push(&pubkey);
+        assert_eq!(witness.len(), 2);
+        assert_eq!(witness.nth(0), Some(&sig[..]));
+        assert_eq!(witness.last(), Some(&pubkey[..]));
+        assert_eq!(witness.nth(2), None);
+
+        let bytes = witness.to_bytes();
+        assert_eq!(bytes[..2], [0x02, 0x47]);
+        assert_eq!(bytes[2 + 0x47], 0x21);
+        assert_eq!(bytes.len(), 1 + 1 + sig.len() + 1 + pubkey.len());
+        assert_eq!(
+            Witness::from_bytes(&bytes).unwrap(),
+            (witness.clone(), bytes.len())
+        );
+        assert_eq!(
+            Witness::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(BitcoinError::InsufficientBytes)
+        );
+
+        let empty = Witness::default();
+        assert!(empty.is_empty() && empty.last().is_none());
+        assert_eq!(empty.to_bytes(), vec![0x00]);
+        assert_eq!(Witness::from_bytes(&[0x00, 0xFF]).unwrap(), (empty, 1));
+
+        let json = serde_json::to_string(&witness).unwrap();
+        assert_eq!(
+            json,
+            format!("[\"{}\",\"{}\"]", BLOCK_170_SIG, G_COMPRESSED)
+        );
+        assert_eq!(serde_json::from_str::<Witness>(&json).unwrap(), witness);
+        let encoded = bincode::serialize(&witness).unwrap();
+        assert_eq!(bincode::deserialize::<Witness>(&encoded).unwrap(), witness);
+    }
+
+    #[test]
+    fn test_witness_p2wpkh_and_p2wsh_helpers() {
+        // Mainnet block 702861: input 0 of tx 1 (P2WPKH) and input 0 of tx 7 (2-of-3 P2WSH).
+        let p2wpkh: Witness = [
+            "304402202d39fce145204e88fa3a8d398b9ff2ec4c4c8bf02da3b751650a80ddb26fcb310220689651c87e08674627d82612a3b4040e42991240792743a2cd9b9942482265b901",
+            "02dfaba46d2417eee4661d45a6ab44f15cf2c77377045c678c926142b6b611ab9e",
+        ]
+        .iter()
+        .map(|element| hex::decode(element).unwrap())
+        .collect();
+        assert_eq!(
+            p2wpkh.p2wpkh_pubkey().map(hex::encode).as_deref(),
+            Some("02dfaba46d2417eee4661d45a6ab44f15cf2c77377045c678c926142b6b611ab9e")
+        );
+        assert_eq!(p2wpkh.taproot_annex(), None);
+
+        let p2wsh: Witness = [
+            "",
+            "30440220187916e02f2d469bb227f0181adfed3b51f8dbf8d7c3f10c5da8afba867f3a4802200f7e5243903f76af726a369b02da794180c3690734c463209698a197b820b07001",
+            "304402203536b455f48827b2efccc3614ebd9866e2fe5e79733de3c93a2a727f561b40a002204961b18b96a3646ce2921f5ca6a2c814c830e6e669c9c765c873ff86d26bf17501",
+            "52210375e00eb72e29da82b89367947f29ef34afb75e8654f6ea368e0acdfd92976b7c2103a1b26313f430c4b15bb1fdce663207659d8cac749a0e53d70eff01874496feff2103c96d495bfdd5ba4145e3e046fee45e84a8a48ad05bd8dbb395c011a32cf9f88053ae",
+        ]
+        .iter()
+        .map(|element| hex::decode(element).unwrap())
+        .collect();
+        assert_eq!(p2wsh.p2wpkh_pubkey(), None);
+        assert_eq!(p2wsh.taproot_annex(), None);
+        let witness_script = Script::new(p2wsh.last().unwrap().to_vec());
+        assert_eq!(witness_script.classify(), ScriptType::Multisig);
+        assert_eq!(
+            witness_script
+                .parse_multisig()
+                .map(|(m, keys)| (m, keys.len())),
+            Some((2, 3))
+        );
+        assert!(p2wsh.is_within_limits());
+        // The taproot helpers only look at the stack's shape, so on a P2WSH stack they
+        // return the last signature and the witness script.
+        assert_eq!(p2wsh.tapscript(), p2wsh.nth(2));
+        assert_eq!(
+            p2wsh.taproot_control_block(),
+            Some(witness_script.as_bytes())
+        );
+
+        let mut uncompressed = Witness::new();
+        uncompressed.push(p2wpkh.nth(0).unwrap());
+        uncompressed.push(hex::decode(G_UNCOMPRESSED).unwrap());
+        assert_eq!(uncompressed.p2wpkh_pubkey(), None);
+    }
+
+    #[test]
+    fn test_witness_taproot_helpers() {
+        // Leaf script and control block from Bitcoin Core's feature_taproot.py, spending
+        // 5120567666e7df90e0450bb608e17c01ed3fbcfa5355a5f8273e34e583bfaa70ce09.
+        let sig = hex::decode(
+            "e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca8215\
+             25f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0",
+        )
+        .unwrap();
+        let script =
+            hex::decode("203455139bf238a3067bd72ed77e0ab8db590330f55ed58dba7366b53bf4734279ac")
+                .unwrap();
+        let control_block =
+            hex::decode("c1a0eb12e60a52614986c623cbb6621dcdba3a47e3be6b37e032b7a11c7b98f400")
+                .unwrap();
+        let annex = [0x50, 0x01, 0x02];
+
+        let script_path: Witness = [&sig[..], &script, &control_block].into_iter().collect();
+        assert_eq!(script_path.tapscript(), Some(&script[..]));
+        assert_eq!(
+            script_path.taproot_control_block(),
+            Some(&control_block[..])
+        );
+        assert_eq!(script_path.taproot_annex(), None);
+
+        let with_annex: Witness = [&sig[..], &script, &control_block, &annex]
+            .into_iter()
+            .collect();
+        assert_eq!(with_annex.tapscript(), Some(&script[..]));
+        assert_eq!(with_annex.taproot_control_block(), Some(&control_block[..]));
+        assert_eq!(with_annex.taproot_annex(), Some(&annex[..]));
+
+        let key_path: Witness = [&sig].into_iter().collect();
+        assert_eq!(key_path.tapscript(), None);
+        assert_eq!(key_path.taproot_control_block(), None);
+        assert_eq!(key_path.taproot_annex(), None);
+
+        let key_path_annex: Witness = [&sig[..], &annex].into_iter().collect();
+        assert_eq!(key_path_annex.taproot_annex(), Some(&annex[..]));
+        assert_eq!(key_path_annex.tapscript(), None);
+        assert_eq!(key_path_annex.taproot_control_block(), None);
+
+        // A lone element starting with 0x50 is a signature, not an annex.
+        let lone: Witness = [&annex].into_iter().collect();
+        assert_eq!(lone.taproot_annex(), None);
+        assert!(Witness::new().tapscript().is_none());
+    }
+
+    #[test]
+    fn test_control_block_merkle_root() {
+        for (spk, script, control_block, root) in TAPROOT_SCRIPT_PATHS {
+            let bytes = hex::decode(control_block).unwrap();
+            let parsed = ControlBlock::from_slice(&bytes).unwrap();
+            assert_eq!(parsed.leaf_version(), TAPROOT_LEAF_TAPSCRIPT);
+            assert_eq!(parsed.output_key_parity(), bytes[0] & 1);
+            assert_eq!(parsed.internal_key().as_bytes()[..], bytes[1..33]);
+            assert_eq!(parsed.merkle_branch().len(), (bytes.len() - 33) / 32);
+            assert_eq!(parsed.to_bytes(), bytes);
+
+            let script = Script::new(hex::decode(script).unwrap());
+            assert_eq!(parsed.merkle_root(&script).to_string(), root);
+            assert!(Script::from_hex(spk).unwrap().p2tr_output_key().is_some());
+        }
+
+        // BIP341 wallet vector 1 is a single leaf: the root is the leaf hash.
+        let (_, script, _, root) = TAPROOT_SCRIPT_PATHS[0];
+        let script = Script::new(hex::decode(script).unwrap());
+        assert_eq!(
+            script.tap_leaf_hash(TAPROOT_LEAF_TAPSCRIPT).to_string(),
+            root
+        );
+    }
+
+    #[test]
+    fn test_control_block_rejects_bad_lengths() {
+        let (_, _, control_block, _) = TAPROOT_SCRIPT_PATHS[0];
+        let base = hex::decode(control_block).unwrap();
+        for bad in [&base[..32], &[base.clone(), vec![0; 31]].concat()[..], &[]] {
+            assert_eq!(
+                ControlBlock::from_slice(bad),
+                Err(BitcoinError::InvalidFormat)
+            );
+        }
+
+        let deepest = [base.clone(), vec![0x11; 32 * 128]].concat();
+        assert_eq!(
+            ControlBlock::try_from(&deepest[..])
+                .unwrap()
+                .merkle_branch()
+                .len(),
+            128
+        );
+        let too_deep = [deepest, vec![0x11; 32]].concat();
+        assert_eq!(
+            ControlBlock::from_slice(&too_deep),
+            Err(BitcoinError::InvalidFormat)
+        );
+    }
+
+    #[test]
+    fn test_witness_size_and_limits() {
+        let sig = hex::decode(BLOCK_170_SIG).unwrap();
+        let key = hex::decode(G_COMPRESSED).unwrap();
+        let p2wpkh: Witness = [&sig, &key].into_iter().collect();
+        assert_eq!(p2wpkh.element_count(), 2);
+        assert!(p2wpkh.is_within_limits());
+        let large: Witness = [vec![0x01; 253], vec![]].into_iter().collect();
+        for witness in [&p2wpkh, &large, &Witness::new()] {
+            assert_eq!(witness.total_size(), witness.to_bytes().len());
+        }
+        assert!(Witness::new().is_within_limits());
+
+        let script = vec![0x51; Witness::MAX_STANDARD_SCRIPT_SIZE];
+        let item = vec![0x01; Witness::MAX_STANDARD_ITEM_SIZE];
+        let p2wsh = |items: usize, item: &[u8], script: &[u8]| -> Witness {
+            std::iter::repeat_n(item, items).chain([script]).collect()
+        };
+        assert!(p2wsh(100, &item, &script).is_within_limits());
+        assert!(!p2wsh(101, &item, &script).is_within_limits());
+        assert!(!p2wsh(1, &[0x01; 81], &script).is_within_limits());
+        assert!(!p2wsh(1, &item, &[0x51; 3601]).is_within_limits());
+
+        // A tapscript leaf may exceed the witness script limit; its inputs may not exceed 80.
+        let (_, _, control_block, _) = TAPROOT_SCRIPT_PATHS[1];
+        let control_block = hex::decode(control_block).unwrap();
+        let tapscript: Witness = [&item[..], &[0x51; 4000], &control_block]
+            .into_iter()
+            .collect();
+        assert!(tapscript.is_within_limits());
+        let oversized: Witness = [&[0x01; 81][..], &[0x51], &control_block]
+            .into_iter()
+            .collect();
+        assert!(!oversized.is_within_limits());
+        // Tapscript inputs have no item-count cap.
+        let many_items: Witness = std::iter::repeat_n(&[0x01][..], 500)
+            .chain([&[0x51][..], &control_block])
+            .collect();
+        assert!(many_items.is_within_limits());
+        let annexed: Witness = [&sig[..64], &[0x50]].into_iter().collect();
+        assert!(!annexed.is_within_limits());
+    }
+
+    #[test]
+    fn test_witness_hostile_count_fails_fast() {
+        let mut bytes = vec![0xFF];
+        bytes.extend_from_slice(&u64::MAX.to_le_bytes());
+        bytes.extend_from_slice(&[0x00; 8]);
+
+        let before = alloc_counter::allocations();
+        let result = Witness::from_bytes(&bytes);
+        assert_eq!(alloc_counter::allocations(), before);
+        assert_eq!(
+            result,
+            Err(BitcoinError::ValueTooLarge {
+                value: u64::MAX,
+                max: 16
+            })
+        );
+
+        // Counts are bounded by the bytes left, one length prefix per element.
+        assert_eq!(
+            Witness::from_bytes(&[0x03, 0x00]),
+            Err(BitcoinError::ValueTooLarge { value: 3, max: 1 })
+        );
+        assert_eq!(
+            Witness::from_bytes(&[0x02, 0x00, 0x01]),
+            Err(BitcoinError::InsufficientBytes)
+        );
+    }
+
+    #[test]
+    fn test_has_witness() {
+        let input = TransactionInput::new(
+            OutPoint::new(dummy_txid(1), 0),
+            Script::new(vec![]),
+            0xFFFFFFFF,
+        );
+        let mut tx = BitcoinTransaction::new(2, vec![input.clone(), input], 0);
+        assert!(!tx.inputs[1].has_witness());
+        assert!(!tx.has_witness());
+        tx.inputs[1].witness.push([0x01]);
+        assert!(tx.inputs[1].has_witness());
+        assert!(tx.has_witness());
+    }
+
+    #[test]
+    fn test_input_witness_not_in_legacy_encoding() {
+        let mut input = TransactionInput::new(
+            OutPoint::new(dummy_txid(1), 0),
+            Script::new(vec![]),
+            0xFFFFFFFF,
+        );
+        assert!(input.witness.is_empty());
+        let legacy = input.to_bytes();
+        input.witness.push([0x01; 72]);
+        assert_eq!(input.to_bytes(), legacy);
+
+        let (parsed, _) = TransactionInput::from_bytes(&legacy).unwrap();
+        assert!(parsed.witness.is_empty());
+    }
+
+    #[test]
+    fn test_sequence_flags() {
+        assert!(Sequence::MAX.is_final());
+        assert!(!Sequence::MAX.is_rbf() && !Sequence::MAX.enables_absolute_lock_time());
+        let no_rbf = Sequence::ENABLE_LOCKTIME_NO_RBF;
+        assert!(!no_rbf.is_rbf() && no_rbf.enables_absolute_lock_time());
+        let rbf = Sequence::ENABLE_RBF_NO_LOCKTIME;
+        assert!(rbf.is_rbf() && rbf.enables_absolute_lock_time() && !rbf.is_final());
+        assert!(Sequence::ZERO.is_rbf());
+        assert_eq!(Sequence::default(), Sequence::MAX);
+        assert_eq!(u32::from(Sequence::from(0xFFFFFFFD)), 0xFFFFFFFD);
+    }
+
+    #[test]
+    fn test_sequence_relative_lock_time() {
+        // Disable bit set: no relative lock, whatever the other bits say.
+        assert_eq!(Sequence(1 << 31).relative_lock_time(), None);
+        assert_eq!(
+            Sequence((1 << 31) | (1 << 22) | 10).relative_lock_time(),
+            None
+        );
+        assert_eq!(Sequence::MAX.relative_lock_time(), None);
+        assert_eq!(Sequence::ENABLE_RBF_NO_LOCKTIME.relative_lock_time(), None);
+
+        assert_eq!(
+            Sequence(0).relative_lock_time(),
+            Some(RelativeLockTime::Blocks(0))
+        );
+        assert_eq!(
+            Sequence(144).relative_lock_time(),
+            Some(RelativeLockTime::Blocks(144))
+        );
+        assert_eq!(
+            Sequence((1 << 22) | 3).relative_lock_time(),
+            Some(RelativeLockTime::Time(3))
+        );
+        // Bits 16..22 and 23..31 are ignored by BIP68.
+        assert_eq!(
+            Sequence(0x7F3F_FFFF).relative_lock_time(),
+            Some(RelativeLockTime::Blocks(0xFFFF))
+        );
+        assert_eq!(
+            Sequence(0x0040_0000 | 0x0001_0000 | 0x1234).relative_lock_time(),
+            Some(RelativeLockTime::Time(0x1234))
+        );
+    }
+
+    #[test]
+    fn test_sequence_serialization_unchanged() {
+        let input = TransactionInput::new(
+            OutPoint::new(dummy_txid(1), 0),
+            Script::new(vec![]),
+            0xFFFFFFFD,
+        );
+        assert_eq!(input.sequence, Sequence::ENABLE_RBF_NO_LOCKTIME);
+        let bytes = input.to_bytes();
+        assert_eq!(bytes[bytes.len() - 4..], [0xFD, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(TransactionInput::from_bytes_exact(&bytes).unwrap(), input);
+
+        let json = serde_json::to_value(&input).unwrap();
+        assert_eq!(json["sequence"], 0xFFFFFFFDu32);
+    }
+    #[test]
+    fn test_lock_time_height_time_split() {
+        assert_eq!(
+            LockTime::from_consensus(499_999_999),
+            LockTime::Blocks(499_999_999)
+        );
+        assert_eq!(
+            LockTime::from_consensus(500_000_000),
+            LockTime::Seconds(500_000_000)
+        );
+        assert!(LockTime::from_consensus(0).is_block_height());
+        assert!(!LockTime::from_consensus(u32::MAX).is_block_height());
+        assert_eq!(LockTime::default(), LockTime::ZERO);
+        for value in [0, 499_999_999, 500_000_000, u32::MAX] {
+            assert_eq!(LockTime::from_consensus(value).to_consensus_u32(), value);
+        }
+
+        assert_eq!(
+            LockTime::from_height(499_999_999),
+            Ok(LockTime::Blocks(499_999_999))
+        );
+        assert_eq!(
+            LockTime::from_height(500_000_000),
+            Err(BitcoinError::InvalidLockTime(500_000_000))
+        );
+        assert_eq!(
+            LockTime::from_time(500_000_000),
+            Ok(LockTime::Seconds(500_000_000))
+        );
+        assert_eq!(
+            LockTime::from_time(499_999_999),
+            Err(BitcoinError::InvalidLockTime(499_999_999))
+        );
+    }
+
+    #[test]
+    fn test_lock_time_comparison_and_satisfaction() {
+        let height = LockTime::Blocks(499_999_999);
+        let time = LockTime::Seconds(500_000_000);
+        assert!(!height.is_same_unit(&time));
+        assert_eq!(height.partial_cmp_same_unit(&time), None);
+        assert_eq!(
+            LockTime::Blocks(10).partial_cmp_same_unit(&LockTime::Blocks(20)),
+            Some(std::cmp::Ordering::Less)
+        );
+        assert_eq!(
+            time.partial_cmp_same_unit(&LockTime::Seconds(500_000_000)),
+            Some(std::cmp::Ordering::Equal)
+        );
+
+        assert!(LockTime::ZERO.is_satisfied_by(0, 0));
+        assert!(LockTime::Blocks(100).is_satisfied_by(100, 0));
+        assert!(!LockTime::Blocks(100).is_satisfied_by(99, u32::MAX));
+        assert!(time.is_satisfied_by(0, 500_000_000));
+        assert!(!time.is_satisfied_by(u32::MAX, 499_999_999));
+    }
+
+    #[test]
+    fn test_transaction_lock_time_accessors() {
+        let mut tx = BitcoinTransaction::new(2, vec![], 499_999_999);
+        assert_eq!(tx.lock_time(), LockTime::Blocks(499_999_999));
+        let bytes = tx.to_bytes();
+        tx.set_lock_time(LockTime::from_time(1_718_000_000).unwrap());
+        assert_eq!(tx.lock_time, 1_718_000_000);
+        assert_eq!(tx.lock_time(), LockTime::Seconds(1_718_000_000));
+        tx.set_lock_time(LockTime::from_consensus(499_999_999));
+        assert_eq!(tx.to_bytes(), bytes);
+    }
+    #[test]
+    fn test_transaction_is_final() {
+        let input = |sequence| {
+            TransactionInput::new(
+                OutPoint::new(dummy_txid(1), 0),
+                Script::new(vec![]),
+                sequence,
+            )
+        };
+        let mut tx = BitcoinTransaction::new(2, vec![input(0xFFFFFFFE), input(0)], 0);
+        assert!(tx.is_final(0, 0));
+
+        tx.lock_time = 100;
+        assert!(!tx.is_final(100, u32::MAX));
+        assert!(tx.is_final(101, 0));
+
+        // A time lock equal to the block time is not yet final.
+        tx.lock_time = 1_718_000_000;
+        assert!(!tx.is_final(u32::MAX, 1_718_000_000));
+        assert!(tx.is_final(0, 1_718_000_001));
+        tx.lock_time = 500_000_000;
+        assert!(!tx.is_final(500_000_001, 500_000_000));
+        assert!(tx.is_final(0, 500_000_001));
+
+        // Final sequences disable the lock time altogether.
+        tx.inputs = vec![input(0xFFFFFFFF), input(0xFFFFFFFF)];
+        assert!(tx.is_final(0, 0));
+        tx.inputs[1].sequence = Sequence::ENABLE_LOCKTIME_NO_RBF;
+        assert!(!tx.is_final(0, 0));
+    }
+    #[test]
+    fn test_check_sequence_locks() {
+        let input = |sequence| {
+            TransactionInput::new(
+                OutPoint::new(dummy_txid(1), 0),
+                Script::new(vec![]),
+                sequence,
+            )
+        };
+        // A 10-block lock, and a one-unit (512 second) time lock.
+        let mut tx = BitcoinTransaction::new(2, vec![input(10), input((1 << 22) | 1)], 0);
+        let old_enough = InputAge::new(0, 512);
+        assert_eq!(
+            tx.check_sequence_locks(true, &[InputAge::new(10, 0), old_enough]),
+            Ok(())
+        );
+        assert_eq!(
+            tx.check_sequence_locks(true, &[InputAge::new(9, u32::MAX), old_enough]),
+            Err(SequenceLockError::BlocksRemaining {
+                index: 0,
+                blocks: 1
+            })
+        );
+        assert_eq!(
+            tx.check_sequence_locks(true, &[InputAge::new(10, 0), InputAge::new(u32::MAX, 511)]),
+            Err(SequenceLockError::SecondsRemaining {
+                index: 1,
+                seconds: 1
+            })
+        );
+        assert_eq!(
+            tx.check_sequence_locks(true, &[InputAge::new(3, 0), InputAge::new(0, 0)]),
+            Err(SequenceLockError::BlocksRemaining {
+                index: 0,
+                blocks: 7
+            })
+        );
+        assert_eq!(
+            tx.check_sequence_locks(true, &[old_enough]),
+            Err(SequenceLockError::InputAgesMismatch { inputs: 2, ages: 1 })
+        );
+
+        // Version 1 transactions, and inputs with the disable flag, have no relative locks.
+        let young = [InputAge::new(0, 0), InputAge::new(0, 0)];
+        tx.version = 1;
+        assert_eq!(tx.check_sequence_locks(true, &young), Ok(()));
+        assert!(tx.check_sequence_locks(false, &young).is_err());
+        tx.inputs[0].sequence = Sequence(Sequence::LOCK_TIME_DISABLE_FLAG | 10);
+        tx.inputs[1].sequence = Sequence::MAX;
+        assert_eq!(tx.check_sequence_locks(false, &young), Ok(()));
+    }
+    #[test]
+    fn test_median_time_past() {
+        let timestamps = [
+            1_700_000_600,
+            1_700_000_000,
+            1_700_001_000,
+            1_700_000_300,
+            1_700_000_900,
+            1_700_000_100,
+            1_700_000_700,
+            1_700_000_400,
+            1_700_000_200,
+            1_700_000_800,
+            1_700_000_500,
+        ];
+        assert_eq!(median_time_past(&timestamps), 1_700_000_500);
+        assert_eq!(
+            median_time_past_from_slice(&timestamps),
+            Some(1_700_000_500)
+        );
+        // Only the last 11 count, and a young chain uses what it has.
+        let mut longer = vec![0; 5];
+        longer.extend_from_slice(&timestamps);
+        assert_eq!(median_time_past_from_slice(&longer), Some(1_700_000_500));
+        assert_eq!(median_time_past_from_slice(&[30, 10, 20]), Some(20));
+        assert_eq!(median_time_past_from_slice(&[30, 10]), Some(30));
+        assert_eq!(median_time_past_from_slice(&[7]), Some(7));
+        assert_eq!(median_time_past_from_slice(&[]), None);
+    }
+
+    #[test]
+    fn test_timelocks_at_chain_context() {
+        let ctx = ChainContext::new(800_000, 1_700_000_000);
+        let mut tx = BitcoinTransaction::new(
+            2,
+            vec![TransactionInput::new(
+                OutPoint::new(dummy_txid(1), 0),
+                Script::new(vec![]),
+                10,
+            )],
+            800_000,
+        );
+        assert!(tx.is_final_at(&ctx));
+        tx.lock_time = 800_001;
+        assert!(!tx.is_final_at(&ctx));
+        tx.lock_time = 1_700_000_000;
+        assert!(!tx.is_final_at(&ctx));
+        tx.lock_time = 1_699_999_999;
+        assert!(tx.is_final_at(&ctx));
+
+        let confirmed_on = ChainContext::new(799_990, 1_699_990_000);
+        assert_eq!(
+            InputAge::between(&confirmed_on, &ctx),
+            InputAge::new(10, 10_000)
+        );
+        assert_eq!(
+            tx.check_sequence_locks_at(&ctx, true, &[confirmed_on]),
+            Ok(())
+        );
+        assert_eq!(
+            tx.check_sequence_locks_at(&ChainContext::new(799_999, 0), true, &[confirmed_on]),
+            Err(SequenceLockError::BlocksRemaining {
+                index: 0,
+                blocks: 1
+            })
+        );
+    }
+    #[test]
+    fn test_is_valid_at() {
+        let input = |sequence| {
+            TransactionInput::new(
+                OutPoint::new(dummy_txid(1), 0),
+                Script::new(vec![]),
+                sequence,
+            )
+        };
+        let ctx = ChainContext::new(850_000, 1_718_000_000);
+        let old = [InputAge::new(1_000, 1_000_000)];
+
+        // A time lock already behind the wall clock still waits for the median-time-past.
+        let mut cltv = BitcoinTransaction::new(2, vec![input(0xFFFFFFFE)], 1_718_000_000);
+        assert_eq!(
+            cltv.is_valid_at(&ctx, &old),
+            Err(TimelockError::AbsoluteTime {
+                earliest_median_time_past: 1_718_000_001
+            })
+        );
+        assert_eq!(
+            cltv.is_valid_at(&ChainContext::new(850_000, 1_718_000_001), &old),
+            Ok(())
+        );
+        cltv.lock_time = 850_001;
+        assert_eq!(
+            cltv.is_valid_at(&ctx, &old),
+            Err(TimelockError::AbsoluteHeight {
+                earliest_height: 850_002
+            })
+        );
+        cltv.lock_time = 850_000;
+        assert_eq!(cltv.is_valid_at(&ctx, &old), Ok(()));
+
+        // A 144-block CSV on a prevout 100 blocks old, and a 2 * 512 second one.
+        let mut csv = BitcoinTransaction::new(2, vec![input(144)], 0);
+        assert_eq!(
+            csv.is_valid_at(&ctx, &[InputAge::new(100, 0)]),
+            Err(TimelockError::RelativeHeight {
+                index: 0,
+                earliest_height: 850_045
+            })
+        );
+        assert_eq!(csv.is_valid_at(&ctx, &[InputAge::new(144, 0)]), Ok(()));
+        csv.inputs[0].sequence = Sequence((1 << 22) | 2);
+        assert_eq!(
+            csv.is_valid_at(&ctx, &[InputAge::new(0, 1_000)]),
+            Err(TimelockError::RelativeTime {
+                index: 0,
+                earliest_median_time_past: 1_718_000_024
+            })
+        );
+        assert_eq!(
+            csv.is_valid_at(&ctx, &[]),
+            Err(TimelockError::InputAgesMismatch { inputs: 1, ages: 0 })
+        );
+    }
+
+    #[test]
+    fn test_transaction_builder_bytes() {
+        let mut witness = Witness::default();
+        witness.push([0xAA]);
+        let tx = TransactionBuilder::new()
+            .lock_time(100)
+            .add_input(OutPoint::new([0x11; 32], 0))
+            .add_input(
+                InputBuilder::new(OutPoint::new([0x22; 32], 1))
+                    .script_sig(Script::new(vec![0x51]))
+                    .sequence(Sequence::MAX)
+                    .witness(witness.clone()),
+            )
+            .build()
+            .unwrap();
+        let expected = format!(
+            "02000000\
+             02\
+             {}00000000\
+             00\
+             fdffffff\
+             {}01000000\
+             0151\
+             ffffffff\
+             00\
+             64000000",
+            "11".repeat(32),
+            "22".repeat(32)
+        );
+        assert_eq!(hex::encode(tx.to_bytes_legacy()), expected);
+        assert_eq!(
+            hex::encode(tx.to_bytes()),
+            format!(
+                "020000000001{}000101aa64000000",
+                &expected[8..expected.len() - 8]
+            )
+        );
+        assert_eq!(tx.inputs[0].sequence, Sequence::ENABLE_RBF_NO_LOCKTIME);
+        assert_eq!(tx.inputs[1].witness, witness);
+
+        let tx = TransactionBuilder::new()
+            .version(1)
+            .add_input(OutPoint::new([0x11; 32], 0))
+            .build()
+            .unwrap();
+        assert_eq!(tx.version, 1);
+        assert_eq!(tx.lock_time, 0);
+    }
+
+    #[test]
+    fn test_transaction_builder_outputs() {
+        let script_pubkey = Script::new_p2wpkh(&Hash160([0x33; 20]));
+        let tx = TransactionBuilder::new()
+            .add_input(OutPoint::new([0x11; 32], 0))
+            .add_output(Amount::from_sat(50_000), script_pubkey.clone())
+            .add_output(Amount::ZERO, Script::new(vec![0x6a]))
+            .build()
+            .unwrap();
+        assert_eq!(
+            tx.outputs,
+            [
+                TransactionOutput::new(Amount::from_sat(50_000), script_pubkey),
+                TransactionOutput::new(Amount::ZERO, Script::new(vec![0x6a])),
+            ]
+        );
+        let bytes = tx.to_bytes();
+        let outputs_start = 4 + 1 + 41;
+        assert_eq!(
+            hex::encode(&bytes[outputs_start..bytes.len() - 4]),
+            format!(
+                "02\
+                 50c3000000000000160014{}\
+                 0000000000000000016a",
+                "33".repeat(20)
+            )
+        );
+        assert_eq!(BitcoinTransaction::from_bytes_exact(&bytes).unwrap(), tx);
+    }
+
+    #[test]
+    fn test_transaction_builder_requires_inputs() {
+        assert_eq!(
+            TransactionBuilder::new().lock_time(1).build(),
+            Err(BitcoinError::NoInputs)
+        );
+    }
+
+    #[test]
+    fn test_coinbase_height_roundtrip() {
+        let cases: [(u32, &str); 4] = [(1, "51"), (16, "60"), (17, "0111"), (500_000, "0320a107")];
+        for (height, prefix) in cases {
+            let input = TransactionInput::new_coinbase(height, b"/pool/").unwrap();
+            assert!(input.previous_output.is_null());
+            assert_eq!(input.sequence, Sequence::MAX);
+            assert_eq!(
+                input.script_sig.to_hex(),
+                format!("{}{}", prefix, hex::encode(b"/pool/"))
+            );
+            assert_eq!(input.coinbase_height(), Some(height));
+        }
+
+        // A one-byte push is also read for heights the minimal form writes as opcodes.
+        let input = TransactionInput::new(OutPoint::null(), Script::new(vec![0x01, 0x05]), 0);
+        assert_eq!(input.coinbase_height(), Some(5));
+        // Non-minimal encoding of 17, and a non-coinbase input.
+        let input = TransactionInput::new(OutPoint::null(), Script::new(vec![0x02, 0x11, 0x00]), 0);
+        assert_eq!(input.coinbase_height(), None);
+        let input =
+            TransactionInput::new(OutPoint::new(dummy_txid(1), 0), Script::new(vec![0x51]), 0);
+        assert_eq!(input.coinbase_height(), None);
+    }
+
+    #[test]
+    fn test_coinbase_height_from_mainnet_block() {
+        // Coinbase scriptSig of mainnet block 702861.
+        let script_sig = Script::from_hex(
+            "038db90a0475a45561fabe6d6db43c2ece440513219decd96f67a31bf0191f9a5f2d6c952e5029005e\
+             3d30f562040000001e34c5f062696e616e63652f6672323134818226021704159799809b19f82f7807\
+             000000000000",
+        )
+        .unwrap();
+        let input = TransactionInput::new(OutPoint::null(), script_sig, 0xFFFFFFFF);
+        assert_eq!(input.coinbase_height(), Some(702_861));
+    }
+
+    #[test]
+    fn test_coinbase_script_sig_length() {
+        assert_eq!(
+            TransactionInput::new_coinbase(1, &[]),
+            Err(BitcoinError::InvalidCoinbaseLength(1))
+        );
+        assert!(TransactionInput::new_coinbase(1, &[0x00]).is_ok());
+        assert!(TransactionInput::new_coinbase(500_000, &[0xAA; 96]).is_ok());
+        assert_eq!(
+            TransactionInput::new_coinbase(500_000, &[0xAA; 97]),
+            Err(BitcoinError::InvalidCoinbaseLength(101))
+        );
+    }
+
+    #[test]
+    fn test_input_weight_estimates() {
+        assert_eq!(
+            TransactionInput::estimated_weight_for(InputType::P2pkh),
+            592
+        );
+        assert_eq!(
+            TransactionInput::estimated_weight_for(InputType::P2wpkh),
+            272
+        );
+        assert_eq!(
+            TransactionInput::estimated_weight_for(InputType::P2shP2wpkh),
+            364
+        );
+        assert_eq!(
+            TransactionInput::estimated_weight_for(InputType::P2wshMultisig {
+                required: 2,
+                total: 3
+            }),
+            418
+        );
+        assert_eq!(
+            TransactionInput::estimated_weight_for(InputType::P2trKeySpend),
+            230
+        );
+    }
+
+    #[test]
+    fn test_input_weight_matches_signed_inputs() {
+        // 71-byte signature (70-byte DER), so real inputs come in at most a few WU lighter.
+        let sig = hex::decode(BLOCK_170_SIG).unwrap();
+        let pubkey = hex::decode(G_COMPRESSED).unwrap();
+        let outpoint = OutPoint::new(dummy_txid(1), 0);
+        let witness_input = |script_sig: Script, witness: Witness| {
+            let mut input = TransactionInput::new(outpoint.clone(), script_sig, 0xFFFFFFFD);
+            input.witness = witness;
+            input
+        };
+        let check = |input: &TransactionInput, input_type: InputType, signatures: usize| {
+            let estimate = TransactionInput::estimated_weight_for(input_type);
+            let actual = input.weight();
+            let slack = if input.witness.is_empty() { 4 } else { 1 };
+            assert!(
+                actual <= estimate && estimate - actual <= slack * signatures,
+                "{:?}: actual {} vs estimate {}",
+                input_type,
+                actual,
+                estimate
+            );
+        };
+
+        let script_sig = ScriptBuilder::new()
+            .push_slice(&sig)
+            .push_slice(&pubkey)
+            .into_script();
+        let p2pkh = witness_input(script_sig, Witness::default());
+        assert_eq!(p2pkh.serialized_size(), 147);
+        assert_eq!(p2pkh.weight(), 588);
+        check(&p2pkh, InputType::P2pkh, 1);
+
+        let key_witness: Witness = [&sig, &pubkey].into_iter().collect();
+        let p2wpkh = witness_input(Script::new(vec![]), key_witness.clone());
+        check(&p2wpkh, InputType::P2wpkh, 1);
+
+        // BIP143 P2SH-P2WPKH scriptSig.
+        let nested = Script::from_hex("16001479091972186c449eb1ded22b78e40d009bdf0089").unwrap();
+        let p2sh_p2wpkh = witness_input(nested, key_witness);
+        check(&p2sh_p2wpkh, InputType::P2shP2wpkh, 1);
+
+        let keys = [G_COMPRESSED, G_COMPRESSED, G_COMPRESSED].map(|key| hex::decode(key).unwrap());
+        let witness_script = Script::new_multisig(2, &keys).unwrap();
+        let multisig_witness: Witness = [&[][..], &sig, &sig, witness_script.as_bytes()]
+            .into_iter()
+            .collect();
+        let p2wsh = witness_input(Script::new(vec![]), multisig_witness);
+        check(
+            &p2wsh,
+            InputType::P2wshMultisig {
+                required: 2,
+                total: 3,
+            },
+            2,
+        );
+
+        let mut taproot_witness = Witness::default();
+        taproot_witness.push([0x5A; 64]);
+        let p2tr = witness_input(Script::new(vec![]), taproot_witness);
+        assert_eq!(p2tr.weight(), 230);
+    }
+
+    #[test]
+    fn test_transaction_input_surgery() {
+        let tx = TransactionBuilder::new()
+            .add_input(
+                InputBuilder::new(OutPoint::new([0x11; 32], 0)).script_sig(Script::new(vec![0x51])),
+            )
+            .add_input(OutPoint::new([0x22; 32], 1))
+            .build()
+            .unwrap();
+        let original = tx.to_bytes();
+        let mut tx = BitcoinTransaction::from_bytes_exact(&original).unwrap();
+
+        // Version, count, first outpoint, then the one-byte scriptSig `51`.
+        let script_offset = 4 + 1 + 36;
+        assert_eq!(original[script_offset..script_offset + 2], [0x01, 0x51]);
+        tx.replace_script_sig(0, Script::new(vec![0x52])).unwrap();
+        let bytes = tx.to_bytes();
+        assert_eq!(bytes.len(), original.len());
+        let changed: Vec<usize> = (0..bytes.len())
+            .filter(|&i| bytes[i] != original[i])
+            .collect();
+        assert_eq!(changed, vec![script_offset + 1]);
+
+        tx.input_mut(1).unwrap().sequence = Sequence::MAX;
+        let bytes = tx.to_bytes();
+        // Sequence, empty output count, lock time.
+        assert_eq!(bytes[bytes.len() - 9..bytes.len() - 5], [0xFF; 4]);
+
+        tx.clear_script_sigs();
+        assert!(tx.inputs.iter().all(|input| input.script_sig.is_empty()));
+        assert_eq!(tx.to_bytes().len(), original.len() - 1);
+
+        let removed = tx.remove_input(0).unwrap();
+        assert_eq!(removed.previous_output, OutPoint::new([0x11; 32], 0));
+        assert_eq!(tx.inputs.len(), 1);
+        tx.push_input(removed);
+        assert_eq!(tx.inputs[1].previous_output.vout, 0);
+        assert_eq!(tx.to_bytes()[4], 0x02);
+    }
+
+    #[test]
+    fn test_transaction_input_index_out_of_range() {
+        let mut tx = BitcoinTransaction::new(2, vec![], 0);
+        assert!(tx.input_mut(0).is_none());
+        assert_eq!(
+            tx.replace_script_sig(0, Script::new(vec![])),
+            Err(BitcoinError::IndexOutOfRange { index: 0, len: 0 })
+        );
+        tx.push_input(TransactionInput::new(
+            OutPoint::new(dummy_txid(1), 0),
+            Script::new(vec![]),
+            0,
+        ));
+        assert_eq!(
+            tx.remove_input(3),
+            Err(BitcoinError::IndexOutOfRange { index: 3, len: 1 })
+        );
+        assert_eq!(tx.inputs.len(), 1);
+    }
+
+    #[test]
+    fn test_check_sanity_passes_real_inputs() {
+        let tx = BitcoinTransaction::from_bytes_exact(&hex::decode(BLOCK_170_TX).unwrap()).unwrap();
+        assert_eq!(tx.check_sanity(), Ok(()));
+
+        // Coinbase scriptSig of mainnet block 702861.
+        let coinbase = Script::from_hex(
+            "038db90a0475a45561fabe6d6db43c2ece440513219decd96f67a31bf0191f9a5f2d6c952e5029005e\
+             3d30f562040000001e34c5f062696e616e63652f6672323134818226021704159799809b19f82f7807\
+             000000000000",
+        )
+        .unwrap();
+        let tx = BitcoinTransaction::new_with_outputs(
+            2,
+            vec![TransactionInput::new(
+                OutPoint::null(),
+                coinbase,
+                0xFFFFFFFF,
+            )],
+            tx.outputs,
+            0,
+        );
+        assert_eq!(tx.check_sanity(), Ok(()));
+    }
+
+    #[test]
+    fn test_check_sanity_failures() {
+        let input = |txid: u8, vout: u32| {
+            TransactionInput::new(
+                OutPoint::new(dummy_txid(txid), vout),
+                Script::new(vec![]),
+                0,
+            )
+        };
+        let output = TransactionOutput::new(Amount::from_sat(1_000), Script::new(vec![0x51]));
+        let tx = |inputs: Vec<TransactionInput>| {
+            BitcoinTransaction::new_with_outputs(2, inputs, vec![output.clone()], 0)
+        };
+        assert_eq!(tx(vec![]).check_sanity(), Err(TxSanityError::NoInputs));
+        assert_eq!(
+            BitcoinTransaction::new(2, vec![input(1, 0)], 0).check_sanity(),
+            Err(TxSanityError::NoOutputs)
+        );
+
+        assert_eq!(
+            tx(vec![input(1, 0), input(1, 1), input(2, 0), input(1, 1)]).check_sanity(),
+            Err(TxSanityError::DuplicateInput { index: 3, first: 1 })
+        );
+
+        for size in [1, 101] {
+            let coinbase =
+                TransactionInput::new(OutPoint::null(), Script::new(vec![0x51; size]), 0);
+            assert_eq!(
+                tx(vec![coinbase]).check_sanity(),
+                Err(TxSanityError::CoinbaseScriptSize { size })
+            );
+        }
+
+        let null = TransactionInput::new(OutPoint::null(), Script::new(vec![0x51, 0x51]), 0);
+        assert_eq!(
+            tx(vec![input(1, 0), null]).check_sanity(),
+            Err(TxSanityError::NullPrevout { index: 1 })
+        );
+
+        let mut too_much = tx(vec![input(1, 0)]);
+        too_much.outputs[0].value = Amount::from_sat(Amount::MAX_MONEY.to_sat() + 1);
+        assert_eq!(
+            too_much.check_sanity(),
+            Err(TxSanityError::OutputValueTooLarge { index: 0 })
+        );
+        too_much.outputs[0].value = Amount::MAX_MONEY;
+        too_much.push_output(output.clone());
+        assert_eq!(
+            too_much.check_sanity(),
+            Err(TxSanityError::OutputSumTooLarge { index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_lazy_inputs_match_eager_parser() {
+        let inputs = (0..400u32)
+            .map(|i| {
+                let script_sig = Script::new(vec![0xAB; (i % 7) as usize * 40]);
+                TransactionInput::new(OutPoint::new(dummy_txid(i as u8), i), script_sig, i)
+            })
+            .collect();
+        let tx = BitcoinTransaction::new(2, inputs, 0x1234);
+        let bytes = tx.to_bytes();
+
+        let before = alloc_counter::allocations();
+        let mut iter = TxDecoder::inputs(&bytes).unwrap();
+        let mut count = 0;
+        let mut found = None;
+        for (i, input) in iter.by_ref().enumerate() {
+            let input = input.unwrap();
+            count += 1;
+            if input.vout() == 321 {
+                found = Some((i, input.script_sig_bytes().len()));
+            }
+        }
+        assert_eq!(alloc_counter::allocations(), before);
+        assert_eq!(count, 400);
+        assert_eq!(found, Some((321, 240)));
+        assert_eq!(iter.remainder(), [0x00, 0x34, 0x12, 0x00, 0x00]);
+
+        let lazy: Vec<TransactionInput> = TxDecoder::inputs(&bytes)
+            .unwrap()
+            .map(|input| input.unwrap().to_input())
+            .collect();
+        assert_eq!(lazy, tx.inputs);
+        let first = TxDecoder::inputs(&bytes).unwrap().next().unwrap().unwrap();
+        assert_eq!(first.previous_output(), tx.inputs[0].previous_output);
+        assert_eq!(first.sequence(), tx.inputs[0].sequence);
+        assert_eq!(first.txid_bytes(), &tx.inputs[0].previous_output.txid.0);
+    }
+
+    #[test]
+    fn test_lazy_inputs_truncated() {
+        let input = TransactionInput::new(
+            OutPoint::new(dummy_txid(1), 0),
+            Script::new(vec![0x51; 10]),
+            0,
+        );
+        let tx = BitcoinTransaction::new(2, vec![input.clone(), input.clone(), input], 0);
+        let bytes = tx.to_bytes();
+        // Cut inside the third input's scriptSig.
+        let truncated = &bytes[..4 + 1 + 51 + 51 + 40];
+        let mut iter = TxDecoder::inputs(truncated).unwrap();
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().unwrap().is_ok());
+        assert_eq!(iter.next().unwrap(), Err(BitcoinError::InsufficientBytes));
+        assert!(iter.next().is_none());
+        assert_eq!(
+            BitcoinTransaction::from_bytes(truncated).unwrap_err(),
+            BitcoinError::InsufficientBytes
+        );
+
+        // A count the buffer cannot hold is rejected up front, as by the eager parser.
+        let mut bytes = bytes.clone();
+        bytes[4] = 0xFC;
+        assert_eq!(
+            TxDecoder::inputs(&bytes).unwrap_err(),
+            BitcoinTransaction::from_bytes(&bytes).unwrap_err()
+        );
+        assert_eq!(
+            TxDecoder::inputs(&[0x02, 0x00]).unwrap_err(),
+            BitcoinError::InsufficientBytes
+        );
+    }
+
+    #[test]
+    fn test_opt_in_rbf() {
+        let input = |vout: u32, sequence: u32| {
+            TransactionInput::new(
+                OutPoint::new(dummy_txid(1), vout),
+                Script::new(vec![]),
+                sequence,
+            )
+        };
+        let mut tx =
+            BitcoinTransaction::new(2, vec![input(0, 0xFFFFFFFF), input(1, 0xFFFFFFFF)], 0);
+        assert!(!tx.signals_rbf());
+        tx.opt_in_rbf();
+        assert!(tx.signals_rbf());
+        assert!(
+            tx.inputs
+                .iter()
+                .all(|input| input.sequence == Sequence::ENABLE_RBF_NO_LOCKTIME)
+        );
+
+        // A 144-block BIP68 lock already signals and must keep its exact value.
+        let mut tx = BitcoinTransaction::new(2, vec![input(0, 144), input(1, 0xFFFFFFFE)], 0);
+        assert!(tx.signals_rbf());
+        tx.opt_in_rbf();
+        assert_eq!(tx.inputs[0].sequence, Sequence(144));
+        assert_eq!(
+            tx.inputs[0].sequence.relative_lock_time(),
+            Some(RelativeLockTime::Blocks(144))
+        );
+        assert_eq!(tx.inputs[1].sequence, Sequence::ENABLE_RBF_NO_LOCKTIME);
+
+        let tx = BitcoinTransaction::new(2, vec![input(0, 0xFFFFFFFE)], 0);
+        assert!(!tx.signals_rbf());
+    }
+
+    #[test]
+    fn test_tx_input_serde_golden_json() {
+        let input = TransactionInput::new(
+            OutPoint::new(dummy_txid(0xAB), 3),
+            Script::new(vec![0x51, 0x52]),
+            0xFFFFFFFD,
+        );
+        // Like Core, and `to_rpc_json`, the txid is in display order.
+        let txid = format!("ab{}", "00".repeat(31));
+        let json = serde_json::to_string(&input).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                "{{\"txid\":\"{txid}\",\"vout\":3,\"scriptSig\":{{\"hex\":\"5152\"}},\"sequence\":4294967293}}"
+            )
+        );
+        let tx = BitcoinTransaction::new(2, vec![input.clone()], 0);
+        assert_eq!(tx.to_rpc_json(Network::Mainnet)["vin"][0]["txid"], txid);
+        assert_eq!(
+            serde_json::from_str::<TransactionInput>(&json).unwrap(),
+            input
+        );
+
+        let mut with_witness = input.clone();
+        with_witness.witness.push([0xDE, 0xAD]);
+        with_witness.witness.push([]);
+        let json = serde_json::to_string(&with_witness).unwrap();
+        assert!(json.ends_with(",\"sequence\":4294967293,\"txinwitness\":[\"dead\",\"\"]}"));
+        assert_eq!(
+            serde_json::from_str::<TransactionInput>(&json).unwrap(),
+            with_witness
+        );
+
+        // Extra Core fields such as `asm` are ignored; bad hex and txid lengths are not.
+        let core = format!(
+            "{{\"txid\":\"{txid}\",\"vout\":3,\"scriptSig\":{{\"asm\":\"1 2\",\"hex\":\"5152\"}},\"sequence\":4294967293}}"
+        );
+        assert_eq!(
+            serde_json::from_str::<TransactionInput>(&core).unwrap(),
+            input
+        );
+        let bad_hex = core.replace("5152", "51g2");
+        assert!(serde_json::from_str::<TransactionInput>(&bad_hex).is_err());
+        let short_txid = core.replacen("ab00", "", 1);
+        assert!(serde_json::from_str::<TransactionInput>(&short_txid).is_err());
+    }
+
+    #[test]
+    fn test_tx_input_serde_bincode() {
+        let input = TransactionInput::new(
+            OutPoint::new(dummy_txid(1), 7),
+            Script::new(vec![0x6A; 20]),
+            0xFFFFFFFF,
+        );
+        let encoded = bincode::serialize(&input).unwrap();
+        assert_eq!(&encoded[8..], input.to_bytes().as_slice());
+        assert_eq!(
+            bincode::deserialize::<TransactionInput>(&encoded).unwrap(),
+            input
+        );
+
+        let mut with_witness = input.clone();
+        with_witness.witness.push([0x01; 3]);
+        let encoded = bincode::serialize(&with_witness).unwrap();
+        assert_eq!(encoded.len(), 8 + input.to_bytes().len() + 5);
+        assert_eq!(
+            bincode::deserialize::<TransactionInput>(&encoded).unwrap(),
+            with_witness
+        );
+
+        // The scriptSig length claims a byte more than is present.
+        let mut bad = input.to_bytes();
+        bad[36] = 21;
+        let encoded = bincode::serialize(&bad).unwrap();
+        assert!(bincode::deserialize::<TransactionInput>(&encoded).is_err());
+    }
+
+    #[test]
+    fn test_input_standardness() {
+        let input = |script_sig: Script| {
+            TransactionInput::new(OutPoint::new(dummy_txid(1), 0), script_sig, 0xFFFFFFFF)
+        };
+        // A single PUSHDATA2 of 1647 bytes makes a 1650-byte scriptSig.
+        let at_limit = ScriptBuilder::new().push_slice(&[0x01; 1647]).into_script();
+        assert_eq!(at_limit.len(), 1650);
+        assert_eq!(input(at_limit).is_standard(), Ok(()));
+        let over = ScriptBuilder::new().push_slice(&[0x01; 1648]).into_script();
+        assert_eq!(
+            input(over.clone()).is_standard(),
+            Err(StandardnessViolation::ScriptSigTooLarge { size: 1651 })
+        );
+        let not_push_only = Script::new(vec![0x51, 0x76]);
+        assert_eq!(
+            input(not_push_only.clone()).is_standard(),
+            Err(StandardnessViolation::ScriptSigNotPushOnly)
+        );
+
+        let tx = BitcoinTransaction::new(
+            2,
+            vec![
+                input(Script::new(vec![0x51])),
+                input(over),
+                input(Script::new(vec![])),
+                input(not_push_only),
+            ],
+            0,
+        );
+        assert_eq!(
+            tx.check_input_standardness(),
+            Err(vec![
+                (1, StandardnessViolation::ScriptSigTooLarge { size: 1651 }),
+                (3, StandardnessViolation::ScriptSigNotPushOnly),
+            ])
+        );
+        tx.inputs[..1]
+            .iter()
+            .for_each(|input| assert!(input.is_standard().is_ok()));
+        let tx = BitcoinTransaction::new(2, vec![input(Script::new(vec![0x00]))], 0);
+        assert_eq!(tx.check_input_standardness(), Ok(()));
+    }
+
+    #[test]
+    fn test_tx_input_roundtrip() {
+        let outpoint = OutPoint::new(dummy_txid(1), 0);
+        let script = Script::new(vec![0x01, 0x02]);
+        let input = TransactionInput::new(outpoint.clone(), script.clone(), 0xFFFFFFFF);
+        let bytes = input.to_bytes();
+        let (parsed, consumed) = TransactionInput::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, input);
+        assert_eq!(consumed, bytes.len());
+    }
+
+    #[test]
+    fn test_tx_output_roundtrip() {
+        let script =
+            Script::from_hex("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac").unwrap();
+        let output = TransactionOutput::new(Amount::from_sat(0x0102030405), script.clone());
+        let bytes = output.to_bytes();
+        assert_eq!(bytes[..8], [0x05, 0x04, 0x03, 0x02, 0x01, 0x00, 0x00, 0x00]);
+        assert_eq!(bytes[8..], script.to_bytes()[..]);
+        assert_eq!(bytes.len(), output.serialized_size());
+        assert_eq!(
+            TransactionOutput::from_bytes(&bytes).unwrap(),
+            (output.clone(), bytes.len())
+        );
+        assert_eq!(
+            TransactionOutput::from_bytes(&bytes[..7]),
+            Err(BitcoinError::InsufficientBytes)
+        );
+        assert_eq!(
+            TransactionOutput::from_bytes_exact(&[&bytes[..], &[0x00]].concat()),
+            Err(BitcoinError::TrailingBytes { remaining: 1 })
+        );
+
+        assert_eq!(
+            output.to_string(),
+            "4328719365 sat to 76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac"
+        );
+        let json = serde_json::to_string(&output).unwrap();
+        assert_eq!(
+            json,
+            r#"{"value":4328719365,"scriptPubKey":"76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<TransactionOutput>(&json).unwrap(),
+            output
+        );
+        let encoded = bincode::serialize(&output).unwrap();
+        assert_eq!(
+            bincode::deserialize::<TransactionOutput>(&encoded).unwrap(),
+            output
+        );
+    }
+
+    #[test]
+    fn test_output_dust_thresholds() {
+        let cases = [
+            ("76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac", 546),
+            ("a9144733f37cf4db86fbc2efed2500b4f4e49f31202387", 540),
+            ("0014751e76e8199196d454941c45d1b3a323f1433bd6", 294),
+            (
+                "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+                330,
+            ),
+            ("6a0b68656c6c6f20776f726c64", 0),
+        ];
+        for (script, threshold) in cases {
+            let script = Script::from_hex(script).unwrap();
+            assert_eq!(
+                TransactionOutput::min_non_dust(&script, FeeRate::DUST_RATE),
+                Amount::from_sat(threshold)
+            );
+            let at = TransactionOutput::new(Amount::from_sat(threshold), script.clone());
+            assert!(!at.is_dust(FeeRate::DUST_RATE));
+            if threshold > 0 {
+                let below = TransactionOutput::new(Amount::from_sat(threshold - 1), script);
+                assert!(below.is_dust(FeeRate::DUST_RATE));
+            }
+        }
+
+        let op_return = TransactionOutput::new(Amount::ZERO, Script::new(vec![0x6a]));
+        assert!(op_return.is_unspendable());
+        assert!(!op_return.is_dust(FeeRate::DUST_RATE));
+        let oversized = TransactionOutput::new(
+            Amount::ZERO,
+            Script::new(vec![0x51; Script::MAX_SCRIPT_SIZE + 1]),
+        );
+        assert!(oversized.is_unspendable());
+        assert!(!oversized.is_dust(FeeRate::DUST_RATE));
+
+        // The threshold scales with the rate: 182 vbytes for a P2PKH output at 1 sat/vB.
+        let p2pkh = Script::from_hex(cases[0].0).unwrap();
+        assert_eq!(
+            TransactionOutput::min_non_dust(&p2pkh, FeeRate::from_sat_per_vb(1).unwrap()),
+            Amount::from_sat(182)
+        );
+        assert_eq!(FeeRate::from_sat_per_vb(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_utxo_set_replays_chain() {
+        let p2wpkh = Script::new_p2wpkh(&Hash160([0x11; 20]));
+        let funding = BitcoinTransaction::new_with_outputs(
+            2,
+            vec![TransactionInput::new(
+                OutPoint::new(dummy_txid(1), 0),
+                Script::new(vec![]),
+                0xFFFFFFFF,
+            )],
+            vec![
+                TransactionOutput::new(Amount::from_sat(60_000), p2wpkh.clone()),
+                TransactionOutput::new(Amount::from_sat(40_000), p2wpkh.clone()),
+            ],
+            0,
+        );
+        let mut utxos = UtxoSet::new();
+        utxos.insert_tx(&funding);
+        let first = OutPoint::from_txid(funding.txid(), 0);
+        let second = OutPoint::from_txid(funding.txid(), 1);
+        assert_eq!(utxos.len(), 2);
+        assert!(utxos.contains(&first) && utxos.contains(&second));
+        assert_eq!(utxos.get(&second), Some(&funding.outputs[1]));
+        assert_eq!(utxos.total_value(), Ok(Amount::from_sat(100_000)));
+
+        // The second transaction spends the first's output 0.
+        let spend = BitcoinTransaction::new_with_outputs(
+            2,
+            vec![TransactionInput::new(
+                first.clone(),
+                Script::new(vec![]),
+                0xFFFFFFFF,
+            )],
+            vec![TransactionOutput::new(
+                Amount::from_sat(59_000),
+                p2wpkh.clone(),
+            )],
+            0,
+        );
+        assert_eq!(utxos.apply_tx(&spend), Ok(()));
+        assert!(!utxos.contains(&first));
+        assert_eq!(
+            utxos.get(&OutPoint::from_txid(spend.txid(), 0)),
+            Some(&spend.outputs[0])
+        );
+        assert_eq!(utxos.total_value(), Ok(Amount::from_sat(99_000)));
+
+        // Replaying it is a double spend, and leaves the set as it was.
+        let before = utxos.clone();
+        assert_eq!(
+            utxos.apply_tx(&spend),
+            Err(BitcoinError::MissingPrevout(first.clone()))
+        );
+        assert_eq!(utxos, before);
+
+        // Listing an unspent outpoint twice is a double spend within the transaction.
+        let mut double_spend = spend.clone();
+        double_spend.inputs[0].previous_output = second.clone();
+        double_spend.inputs.push(double_spend.inputs[0].clone());
+        assert_eq!(
+            utxos.apply_tx(&double_spend),
+            Err(BitcoinError::DuplicateInput { index: 1, first: 0 })
+        );
+        assert_eq!(utxos, before);
+
+        assert_eq!(utxos.spend(&first), None);
+        assert_eq!(utxos.spend(&second), Some(funding.outputs[1].clone()));
+        assert_eq!(utxos.len(), 1);
+    }
+
+    #[test]
+    fn test_fee_rate_rounds_up() {
+        let weight = Weight::from_wu(703);
+        // 703 WU is 175.75 vbytes; every partial satoshi goes to the relayer.
+        assert_eq!(
+            FeeRate::BROADCAST_MIN.fee_for_weight(weight),
+            Some(Amount::from_sat(176))
+        );
+        assert_eq!(
+            FeeRate::DUST_RATE.fee_for_weight(weight),
+            Some(Amount::from_sat(528))
+        );
+        assert_eq!(
+            FeeRate::from_sat_per_kwu(1).fee_for_weight(weight),
+            Some(Amount::ONE_SAT)
+        );
+        assert_eq!(
+            FeeRate::from_sat_per_kwu(1000).fee_for_weight(weight),
+            Some(Amount::from_sat(703))
+        );
+        assert_eq!(FeeRate::ZERO.fee_for_weight(weight), Some(Amount::ZERO));
+        assert_eq!(
+            FeeRate::BROADCAST_MIN.fee_for_vsize(176),
+            Some(Amount::from_sat(176))
+        );
+        assert_eq!(
+            FeeRate::from_sat_per_kwu(3125).fee_for_vsize(3),
+            Some(Amount::from_sat(38))
+        );
+
+        assert_eq!(
+            FeeRate::from_sat_per_kwu(2).fee_for_weight(Weight::from_wu(u64::MAX)),
+            None
+        );
+        assert_eq!(FeeRate::BROADCAST_MIN.fee_for_vsize(u64::MAX / 2), None);
+    }
+
+    #[test]
+    fn test_fee_rate_units_and_display() {
+        assert_eq!(FeeRate::from_sat_per_vb(1), Some(FeeRate::BROADCAST_MIN));
+        assert_eq!(FeeRate::from_sat_per_vb(3), Some(FeeRate::DUST_RATE));
+        assert_eq!(FeeRate::DUST_RATE.to_sat_per_kwu(), 750);
+        assert!(FeeRate::BROADCAST_MIN < FeeRate::DUST_RATE);
+        assert!(FeeRate::from_sat_per_kwu(251) > FeeRate::BROADCAST_MIN);
+
+        assert_eq!(FeeRate::from_sat_per_kwu(3125).to_string(), "12.5 sat/vB");
+        assert_eq!(FeeRate::BROADCAST_MIN.to_string(), "1 sat/vB");
+        assert_eq!(FeeRate::from_sat_per_kwu(1).to_string(), "0.004 sat/vB");
+        assert_eq!(FeeRate::from_sat_per_kwu(255).to_string(), "1.02 sat/vB");
+        assert_eq!(FeeRate::ZERO.to_string(), "0 sat/vB");
+    }
+
+    #[test]
+    fn test_amount_from_btc() {
+        assert_eq!(Amount::from_btc(0.00000001), Ok(Amount::ONE_SAT));
+        assert_eq!(Amount::from_btc(1.0), Ok(Amount::ONE_BTC));
+        assert_eq!(Amount::from_btc(0.1), Ok(Amount::from_sat(10_000_000)));
+        assert_eq!(Amount::from_btc(0.0), Ok(Amount::ZERO));
+        assert_eq!(Amount::from_btc(21_000_000.0), Ok(Amount::MAX_MONEY));
+        assert_eq!(
+            Amount::from_btc(20_999_999.99999999),
+            Ok(Amount::from_sat(2_099_999_999_999_999))
+        );
+        for bad in [-0.00000001, f64::NAN, f64::INFINITY, 0.000000015, 1e-9] {
+            assert_eq!(Amount::from_btc(bad), Err(BitcoinError::InvalidAmount));
+        }
+        assert_eq!(
+            Amount::from_btc(21_000_001.0),
+            Err(BitcoinError::ValueTooLarge {
+                value: 2_100_000_100_000_000,
+                max: 2_100_000_000_000_000
+            })
+        );
+
+        assert_eq!(Amount::from_sat(150_000_000).to_btc(), 1.5);
+        assert_eq!(Amount::ONE_SAT.to_btc(), 0.00000001);
+        assert_eq!(Amount::MAX_MONEY.to_sat(), 2_100_000_000_000_000);
+        assert!(Amount::MAX_MONEY.is_valid());
+        assert!(!Amount::from_sat(Amount::MAX_MONEY.to_sat() + 1).is_valid());
+    }
+
+    #[test]
+    fn test_amount_from_str_in() {
+        use Denomination::*;
+        let ok = |s: &str, denomination, sats: u64| {
+            assert_eq!(
+                Amount::from_str_in(s, denomination),
+                Ok(Amount::from_sat(sats)),
+                "{s} {denomination}"
+            );
+        };
+        ok("0", Bitcoin, 0);
+        ok("0.0", Bitcoin, 0);
+        ok("000", Satoshi, 0);
+        ok(".5", Bitcoin, 50_000_000);
+        ok("5.", Bitcoin, 500_000_000);
+        ok("0.015", Bitcoin, 1_500_000);
+        ok("0.00000001", Bitcoin, 1);
+        // Zeros past the precision carry no value.
+        ok("0.000000010000", Bitcoin, 1);
+        ok("1500", Satoshi, 1_500);
+        ok("1500.000", Satoshi, 1_500);
+        ok("12.3", MilliBitcoin, 1_230_000);
+        ok("0.00001", MilliBitcoin, 1);
+        ok("1.5", MicroBitcoin, 150);
+        ok("21000000", Bitcoin, 2_100_000_000_000_000);
+        ok("0021000000.00000000", Bitcoin, 2_100_000_000_000_000);
+
+        let err = |s: &str, denomination, error| {
+            assert_eq!(
+                Amount::from_str_in(s, denomination),
+                Err(error),
+                "{s} {denomination}"
+            );
+        };
+        err("0.000000001", Bitcoin, ParseAmountError::TooPrecise);
+        err("0.000001", MilliBitcoin, ParseAmountError::TooPrecise);
+        err("0.5", Satoshi, ParseAmountError::TooPrecise);
+        err("-1", Bitcoin, ParseAmountError::Negative);
+        err("-0", Satoshi, ParseAmountError::Negative);
+        err("21000000.00000001", Bitcoin, ParseAmountError::TooLarge);
+        err("2100000000000001", Satoshi, ParseAmountError::TooLarge);
+        err("18446744073709551616", Satoshi, ParseAmountError::TooLarge);
+        err("99999999999999999999", Bitcoin, ParseAmountError::TooLarge);
+        for invalid in ["", ".", "+1", "1.2.3", "1,5", " 1", "1e8", "0x10", "1 "] {
+            err(invalid, Bitcoin, ParseAmountError::InvalidFormat);
+        }
+    }
+
+    #[test]
+    fn test_amount_from_str_and_display_in() {
+        assert_eq!("0.015 BTC".parse(), Ok(Amount::from_sat(1_500_000)));
+        assert_eq!("1500 sat".parse(), Ok(Amount::from_sat(1_500)));
+        assert_eq!("1500 sats".parse(), Ok(Amount::from_sat(1_500)));
+        assert_eq!("12.3 mBTC".parse(), Ok(Amount::from_sat(1_230_000)));
+        assert_eq!(".5 BTC".parse(), Ok(Amount::from_sat(50_000_000)));
+        assert_eq!("21000000 BTC".parse(), Ok(Amount::MAX_MONEY));
+        assert_eq!(
+            "21000000.00000001 BTC".parse::<Amount>(),
+            Err(ParseAmountError::TooLarge)
+        );
+        assert_eq!(
+            "1500".parse::<Amount>(),
+            Err(ParseAmountError::MissingDenomination)
+        );
+        assert_eq!(
+            "1500 btc".parse::<Amount>(),
+            Err(ParseAmountError::UnknownDenomination)
+        );
+        assert_eq!(
+            "1500  sat".parse::<Amount>(),
+            Err(ParseAmountError::UnknownDenomination)
+        );
+        assert_eq!("-1 BTC".parse::<Amount>(), Err(ParseAmountError::Negative));
+
+        let amount = Amount::from_sat(1_500_000);
+        assert_eq!(amount.to_string(), "1500000 sat");
+        assert_eq!(
+            amount.display_in(Denomination::Bitcoin).to_string(),
+            "0.015 BTC"
+        );
+        assert_eq!(
+            amount.display_in(Denomination::MilliBitcoin).to_string(),
+            "15 mBTC"
+        );
+        assert_eq!(
+            amount.display_in(Denomination::MicroBitcoin).to_string(),
+            "15000 uBTC"
+        );
+        assert_eq!(
+            Amount::ONE_SAT
+                .display_in(Denomination::Bitcoin)
+                .to_string(),
+            "0.00000001 BTC"
+        );
+        assert_eq!(
+            Amount::ONE_SAT
+                .display_in(Denomination::MicroBitcoin)
+                .to_string(),
+            "0.01 uBTC"
+        );
+        assert_eq!(
+            Amount::ZERO.display_in(Denomination::Bitcoin).to_string(),
+            "0 BTC"
+        );
+        assert_eq!(
+            Amount::MAX_MONEY
+                .display_in(Denomination::Bitcoin)
+                .to_string(),
+            "21000000 BTC"
+        );
+
+        for sats in [0, 1, 10, 1_230_000, 123_456_789, 2_100_000_000_000_000] {
+            let amount = Amount::from_sat(sats);
+            for denomination in [
+                Denomination::Bitcoin,
+                Denomination::MilliBitcoin,
+                Denomination::MicroBitcoin,
+                Denomination::Satoshi,
+            ] {
+                let s = amount.display_in(denomination).to_string();
+                assert_eq!(s.parse(), Ok(amount), "{s}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_amount_checked_math() {
+        let one = Amount::ONE_BTC;
+        assert_eq!(one.checked_add(one), Ok(Amount::from_sat(200_000_000)));
+        assert_eq!(
+            one.checked_sub(Amount::ONE_SAT),
+            Ok(Amount::from_sat(99_999_999))
+        );
+        assert_eq!(
+            Amount::ONE_SAT.checked_sub(one),
+            Err(BitcoinError::IntegerOverflow)
+        );
+        assert_eq!(
+            Amount::from_sat(u64::MAX).checked_add(Amount::ONE_SAT),
+            Err(BitcoinError::IntegerOverflow)
+        );
+        assert_eq!(
+            Amount::MAX_MONEY.checked_add(Amount::ONE_SAT),
+            Err(BitcoinError::ValueTooLarge {
+                value: 2_100_000_000_000_001,
+                max: 2_100_000_000_000_000
+            })
+        );
+        assert!(Amount::from_sat(u64::MAX).checked_sub(one).is_err());
+
+        assert_eq!(Amount::ONE_BTC.to_string(), "100000000 sat");
+        assert_eq!(
+            serde_json::to_string(&Amount::from_sat(546)).unwrap(),
+            "546"
+        );
+        assert_eq!(
+            serde_json::from_str::<Amount>("546").unwrap(),
+            Amount::from_sat(546)
+        );
+        assert!(serde_json::from_str::<Amount>("-1").is_err());
+    }
+
+    #[test]
+    fn test_bitcoin_tx_roundtrip() {
+        let inputs = vec![TransactionInput::new(
+            OutPoint::new(dummy_txid(1), 0),
+            Script::new(vec![0x01, 0x02]),
+            0xFFFFFFFF,
+        )];
+        let tx = BitcoinTransaction::new(2, inputs.clone(), 1000);
+        let bytes = tx.to_bytes();
+        let (parsed, consumed) = BitcoinTransaction::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, tx);
+        assert_eq!(consumed, bytes.len());
+    }
+
+    #[test]
+    fn test_bitcoin_tx_rejects_huge_input_count() {
+        let mut bytes = 1u32.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&[0xFF; 9]);
+        assert_eq!(
+            BitcoinTransaction::from_bytes(&bytes),
+            Err(BitcoinError::ValueTooLarge {
+                value: u64::MAX,
+                max: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_returns_remainder() {
+        let trailer = [0xDE, 0xAD];
+
+        let mut bytes = CompactSize::new(300).to_bytes();
+        bytes.extend_from_slice(&trailer);
+        let (cs, rest) = CompactSize::parse(&bytes).unwrap();
+        assert_eq!(cs.value, 300);
+        assert_eq!(rest, &trailer);
+
+        let outpoint = OutPoint::new(dummy_txid(9), 4);
+        let mut bytes = outpoint.to_bytes();
+        bytes.extend_from_slice(&trailer);
+        assert_eq!(OutPoint::parse(&bytes).unwrap(), (outpoint, &trailer[..]));
+
+        let script = Script::new(vec![0x51, 0x52]);
+        let mut bytes = script.to_bytes();
+        bytes.extend_from_slice(&trailer);
+        assert_eq!(
+            Script::parse(&bytes).unwrap(),
+            (script.clone(), &trailer[..])
+        );
+
+        let input = TransactionInput::new(OutPoint::new(dummy_txid(1), 0), script, 7);
+        let mut bytes = input.to_bytes();
+        bytes.extend_from_slice(&trailer);
+        assert_eq!(
+            TransactionInput::parse(&bytes).unwrap(),
+            (input.clone(), &trailer[..])
+        );
+
+        let tx = BitcoinTransaction::new(2, vec![input.clone(), input], 500);
+        let mut bytes = tx.to_bytes();
+        bytes.extend_from_slice(&trailer);
+        assert_eq!(
+            BitcoinTransaction::parse(&bytes).unwrap(),
+            (tx.clone(), &trailer[..])
+        );
+        assert_eq!(
+            BitcoinTransaction::from_bytes(&bytes).unwrap(),
+            (tx, bytes.len() - trailer.len())
+        );
+    }
+
+    #[test]
+    fn test_bitcoin_tx_txid() {
+        let tx = BitcoinTransaction::new(
+            1,
+            vec![TransactionInput::new(
+                OutPoint::new(dummy_txid(1), 0),
+                Script::new(vec![0x01, 0x02]),
+                0xFFFFFFFF,
+            )],
+            0,
+        );
+        assert_eq!(
+            hex::encode(tx.to_bytes()),
+            "01000000010000000000000000000000000000000000000000000000000000000000000001\
+             00000000020102ffffffff0000000000"
+        );
+        assert_eq!(
+            tx.txid().to_string(),
+            "4b06d94d6ec7a454d2afc3f25e48130045bf1e6fca54eb84e83bca87f84a4aab"
+        );
+    }
+
+    #[test]
+    fn test_real_legacy_tx_roundtrip() {
+        let bytes = hex::decode(BLOCK_170_TX).unwrap();
+        let tx = BitcoinTransaction::from_bytes_exact(&bytes).unwrap();
+        assert_eq!(tx.to_bytes(), bytes);
+        assert_eq!(
+            tx.txid().to_string(),
+            "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16"
+        );
+        assert_eq!(tx.inputs.len(), 1);
+        assert_eq!(
+            tx.outputs.iter().map(|o| o.value).collect::<Vec<_>>(),
+            [
+                Amount::from_sat(1_000_000_000),
+                Amount::from_sat(4_000_000_000)
+            ]
+        );
+        // Both outputs are bare P2PK, which has no address.
+        assert_eq!(tx.output_addresses(Network::Mainnet), [None, None]);
+        let display = tx.to_string();
+        assert!(display.contains("Output 1:\n      Value: 10.00000000 BTC\n"));
+        assert!(display.contains("Value: 40.00000000 BTC"));
+        assert!(display.contains("ScriptPubKey (67 bytes): 410411db93e1"));
+        assert_eq!(
+            BitcoinTransaction::from_bytes(&bytes[..bytes.len() - 5]),
+            Err(BitcoinError::InsufficientBytes)
+        );
+    }
+
+    #[test]
+    fn test_output_addresses() {
+        // Transaction 15 of mainnet block 702861: an OP_RETURN, two P2SH and one P2PKH output.
+        let bytes = hex::decode(
+            "0100000001a869636c5235ccec6aec560e10b5587edbb6c830ed8e0eefe653496a0605edfd03000000\
+             6b483045022100fdfcd0396306059e6a9c041580eea889a8a4fa2c0fd917bfa7def1aee70c670502\
+             201fee178a9d7b3fa0ab78fe1c3b0668da14ea34d9ce311eb35a54fd9e767636d501210229701969\
+             946ca7ac28d36dc19df6995e0921005a85afb9683b8e0a19857bc2c9fdffffff0400000000000000\
+             00536a4c5058325babe8531a47eb2cd4410efe00620e4972e67b87386ded8ab117b22fde2b943efd\
+             65a714ee0a7f72d9afa1d13f43c12bc188a6249704c00b519f1c984f9bb9657d000ab98c0035000a\
+             b189004500400d03000000000017a914db42b3e5517eb9f8f50d3d10e368c96d4f42594387400d03\
+             000000000017a914459556788403d60ecdd554e5b484761fce8b45b0871a276808000000001976a9\
+             14e93c2d0a87c2fd559255b8e08da7c114d431add688ac00000000",
+        )
+        .unwrap();
+        let tx = BitcoinTransaction::from_bytes_exact(&bytes).unwrap();
+        assert_eq!(tx.to_bytes(), bytes);
+        assert_eq!(
+            tx.txid().to_string(),
+            "ebcdc8788b5a5b85256944aa16b038dc2981e069372cc8509e2f3ac8f0937783"
+        );
+        let addresses: Vec<Option<String>> = tx
+            .output_addresses(Network::Mainnet)
+            .iter()
+            .map(|address| address.as_ref().map(Address::to_string))
+            .collect();
+        assert_eq!(
+            addresses,
+            [
+                None,
+                Some("3MgMp97aWxDsFBsEyZEdYmGGd2ba7rcQWk".to_string()),
+                Some("382wSx6HqUv76tWH7FgFs8PFWgpiWFPWBM".to_string()),
+                Some("1NGEXo4oKR563AoC2owwsb4YzGAJzUSndN".to_string()),
+            ]
+        );
+        assert_eq!(tx.outputs[3].value, Amount::from_sat(141_043_482));
+    }
+
+    #[test]
+    fn test_real_segwit_tx_roundtrip() {
+        let bytes = hex::decode(BLOCK_702861_P2WPKH_TX).unwrap();
+        let tx = BitcoinTransaction::from_bytes_exact(&bytes).unwrap();
+        assert!(tx.has_witness());
+        assert_eq!(tx.to_bytes(), bytes);
+        assert_eq!(tx.to_bytes_legacy().len(), 125);
+        assert_eq!(
+            tx.txid().to_string(),
+            "7bf717689b9033eafb2f3272719989b304bb7db616c2bfb5ded2e1b76d50a4f0"
+        );
+        assert_eq!(
+            tx.wtxid().to_string(),
+            "16280b1cc1ed358983b12745b1a90a9eb1e9bf060f8c7d5ea1f2ebc58be9f3cc"
+        );
+        assert_eq!(
+            tx.inputs[0]
+                .witness
+                .p2wpkh_pubkey()
+                .map(hex::encode)
+                .as_deref(),
+            Some("02dfaba46d2417eee4661d45a6ab44f15cf2c77377045c678c926142b6b611ab9e")
+        );
+        assert_eq!(tx.outputs.len(), 2);
+        assert_eq!(tx.lock_time, 702_860);
+
+        let lazy: Vec<_> = TxDecoder::inputs(&bytes)
+            .unwrap()
+            .map(|input| input.unwrap().to_input())
+            .collect();
+        assert_eq!(lazy.len(), 1);
+        assert_eq!(lazy[0].previous_output, tx.inputs[0].previous_output);
+
+        // The same entry point keeps legacy transactions legacy.
+        let legacy = hex::decode(BLOCK_170_TX).unwrap();
+        let tx = BitcoinTransaction::from_bytes_exact(&legacy).unwrap();
+        assert!(!tx.has_witness());
+        assert_eq!(tx.to_bytes(), legacy);
+        assert_eq!(tx.to_bytes_legacy(), legacy);
+        assert_eq!(tx.wtxid().as_bytes(), tx.txid().as_bytes());
+    }
+
+    #[test]
+    fn test_segwit_serialization_layout() {
+        let input =
+            |n: u8| TransactionInput::new(OutPoint::new(dummy_txid(n), 0), Script::new(vec![]), 0);
+        let mut tx = BitcoinTransaction::new_with_outputs(
+            2,
+            vec![input(1), input(2)],
+            vec![TransactionOutput::new(
+                Amount::from_sat(1),
+                Script::new(vec![0x51]),
+            )],
+            0,
+        );
+        let legacy = tx.to_bytes();
+        tx.inputs[1].witness.push([0xAB, 0xCD]);
+        let bytes = tx.to_bytes();
+        assert_eq!(bytes[4..6], [0x00, 0x01]);
+        assert_eq!(bytes[6..legacy.len() + 2 - 4], legacy[4..legacy.len() - 4]);
+        // An empty witness for the first input, then one two-byte element for the second.
+        assert_eq!(
+            bytes[legacy.len() + 2 - 4..],
+            [0x00, 0x01, 0x02, 0xAB, 0xCD, 0x00, 0x00, 0x00, 0x00]
+        );
+        assert_eq!(BitcoinTransaction::from_bytes_exact(&bytes).unwrap(), tx);
+        assert_eq!(tx.to_bytes_legacy(), legacy);
+
+        // The marker and flag with nothing but empty witnesses.
+        let mut superfluous = legacy[..4].to_vec();
+        superfluous.extend_from_slice(&[0x00, 0x01]);
+        superfluous.extend_from_slice(&legacy[4..legacy.len() - 4]);
+        superfluous.extend_from_slice(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(
+            BitcoinTransaction::from_bytes(&superfluous),
+            Err(BitcoinError::SuperfluousWitnessRecord)
+        );
+
+        let mut unknown_flag = bytes.clone();
+        unknown_flag[5] = 0x02;
+        assert_eq!(
+            BitcoinTransaction::from_bytes(&unknown_flag),
+            Err(BitcoinError::UnsupportedSegwitFlag(0x02))
+        );
+        assert_eq!(
+            BitcoinTransaction::from_bytes(&bytes[..bytes.len() - 6]),
+            Err(BitcoinError::InsufficientBytes)
+        );
+
+        // No inputs and no outputs is still read as legacy.
+        let empty = BitcoinTransaction::new(2, vec![], 0);
+        assert_eq!(
+            BitcoinTransaction::from_bytes_exact(&empty.to_bytes()).unwrap(),
+            empty
+        );
+    }
+
+    #[test]
+    fn test_tx_size_and_weight() {
+        // Core reports weight 609 and vsize 153 for this transaction.
+        let bytes = hex::decode(BLOCK_702861_P2WPKH_TX).unwrap();
+        let tx = BitcoinTransaction::from_bytes_exact(&bytes).unwrap();
+        assert_eq!(tx.total_size(), 234);
+        assert_eq!(tx.total_size(), bytes.len());
+        assert_eq!(tx.base_size(), 125);
+        assert_eq!(tx.base_size(), tx.to_bytes_legacy().len());
+        assert_eq!(tx.weight(), Weight::from_wu(609));
+        assert_eq!(tx.vsize(), 153);
+
+        let legacy = hex::decode(BLOCK_170_TX).unwrap();
+        let tx = BitcoinTransaction::from_bytes_exact(&legacy).unwrap();
+        assert_eq!(tx.total_size(), legacy.len());
+        assert_eq!(tx.base_size(), legacy.len());
+        assert_eq!(tx.weight().to_wu(), 4 * legacy.len() as u64);
+        assert_eq!(tx.vsize(), legacy.len() as u64);
+
+        // Empty witnesses still cost a byte each once any input has one.
+        let input =
+            |n: u8| TransactionInput::new(OutPoint::new(dummy_txid(n), 0), Script::new(vec![]), 0);
+        let mut tx = BitcoinTransaction::new(2, vec![input(1), input(2), input(3)], 0);
+        tx.inputs[1].witness.push([0xAB; 300]);
+        assert_eq!(tx.total_size(), tx.to_bytes().len());
+        assert_eq!(tx.base_size(), tx.to_bytes_legacy().len());
+        assert_eq!(
+            tx.weight().to_wu(),
+            4 * tx.base_size() as u64 + 2 + 1 + 304 + 1
+        );
+
+        assert_eq!(Weight::from_wu(609).to_vbytes_ceil(), 153);
+        assert_eq!(Weight::from_wu(612).to_vbytes_ceil(), 153);
+        assert_eq!(Weight::ZERO.to_vbytes_ceil(), 0);
+    }
+
+    #[test]
+    fn test_tx_fee_from_prevouts() {
+        use std::collections::HashMap;
+
+        let first = OutPoint::new(dummy_txid(1), 0);
+        let second = OutPoint::new(dummy_txid(2), 1);
+        let p2wpkh = Script::new_p2wpkh(&Hash160([0x11; 20]));
+        let utxos: HashMap<OutPoint, TransactionOutput> = [
+            (
+                first.clone(),
+                TransactionOutput::new(Amount::from_sat(60_000), p2wpkh.clone()),
+            ),
+            (
+                second.clone(),
+                TransactionOutput::new(Amount::from_sat(40_000), p2wpkh.clone()),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let lookup = |outpoint: &OutPoint| utxos.get(outpoint);
+
+        // Listed in the opposite order to the map's insertion: the lookup doesn't care.
+        let mut tx = BitcoinTransaction::new_with_outputs(
+            2,
+            vec![
+                TransactionInput::new(second, Script::new(vec![]), 0xFFFFFFFF),
+                TransactionInput::new(first, Script::new(vec![]), 0xFFFFFFFF),
+            ],
+            vec![TransactionOutput::new(
+                Amount::from_sat(90_000),
+                p2wpkh.clone(),
+            )],
+            0,
+        );
+        assert_eq!(tx.fee(lookup), Ok(Amount::from_sat(10_000)));
+        assert_eq!(tx.weight(), Weight::from_wu(492));
+        // 10000 sat over 492 WU is 20325.2 sat/kwu.
+        let rate = tx.fee_rate(lookup).unwrap();
+        assert_eq!(rate, FeeRate::from_sat_per_kwu(20_325));
+        assert!(rate.fee_for_weight(tx.weight()).unwrap() <= Amount::from_sat(10_000));
+
+        tx.outputs[0].value = Amount::from_sat(100_000);
+        assert_eq!(tx.fee(lookup), Ok(Amount::ZERO));
+        tx.push_output(TransactionOutput::new(Amount::ONE_SAT, p2wpkh.clone()));
+        assert_eq!(tx.fee(lookup), Err(BitcoinError::NegativeFee));
+        assert_eq!(tx.fee_rate(lookup), Err(BitcoinError::NegativeFee));
+        tx.outputs[1].value = Amount::MAX_MONEY;
+        assert_eq!(
+            tx.fee(lookup),
+            Err(BitcoinError::OutputSumTooLarge { index: 1 })
+        );
+
+        let unknown = OutPoint::new(dummy_txid(3), 0);
+        tx.push_input(TransactionInput::new(
+            unknown.clone(),
+            Script::new(vec![]),
+            0xFFFFFFFF,
+        ));
+        assert_eq!(tx.fee(lookup), Err(BitcoinError::MissingPrevout(unknown)));
+    }
+
+    #[test]
+    fn test_total_output_value() {
+        let output =
+            |sats: u64| TransactionOutput::new(Amount::from_sat(sats), Script::new(vec![]));
+        let tx = |outputs: Vec<TransactionOutput>| {
+            BitcoinTransaction::new_with_outputs(2, vec![], outputs, 0)
+        };
+        assert_eq!(tx(vec![]).total_output_value(), Ok(Amount::ZERO));
+        assert_eq!(
+            tx(vec![output(1_000), output(2_000)]).total_output_value(),
+            Ok(Amount::from_sat(3_000))
+        );
+        assert_eq!(
+            tx(vec![output(1 << 63), output(1 << 63)]).total_output_value(),
+            Err(BitcoinError::OutputSumTooLarge { index: 1 })
+        );
+        assert_eq!(
+            tx(vec![output(1), output(2_100_000_000_000_001)]).total_output_value(),
+            Err(BitcoinError::OutputValueTooLarge { index: 1 })
+        );
+        assert_eq!(
+            tx(vec![output(2_100_000_000_000_000), output(0), output(1)]).total_output_value(),
+            Err(BitcoinError::OutputSumTooLarge { index: 2 })
+        );
+        assert_eq!(
+            tx(vec![output(2_100_000_000_000_000)]).total_output_value(),
+            Ok(Amount::MAX_MONEY)
+        );
+    }
+
+    #[test]
+    fn test_sort_bip69_reference_vectors() {
+        // BIP69 test vector 1, the inputs and outputs of txid 0a6a357e…e0ed0c4c3 in
+        // sorted order, with txids as displayed.
+        let inputs = [
+            (
+                "0e53ec5dfb2cb8a71fec32dc9a634a35b7e24799295ddd5278217822e0b31f57",
+                0,
+            ),
+            (
+                "26aa6e6d8b9e49bb0630aac301db6757c02e3619feb4ee0eea81eb1672947024",
+                1,
+            ),
+            (
+                "28e0fdd185542f2c6ea19030b0796051e7772b6026dd5ddccd7a2f93b73e6fc2",
+                0,
+            ),
+            (
+                "381de9b9ae1a94d9c17f6a08ef9d341a5ce29e2e60c36a52d333ff6203e58d5d",
+                1,
+            ),
+            (
+                "3b8b2f8efceb60ba78ca8bba206a137f14cb5ea4035e761ee204302d46b98de2",
+                0,
+            ),
+            (
+                "402b2c02411720bf409eff60d05adad684f135838962823f3614cc657dd7bc0a",
+                1,
+            ),
+            (
+                "54ffff182965ed0957dba1239c27164ace5a73c9b62a660c74b7b7f15ff61e7a",
+                1,
+            ),
+            (
+                "643e5f4e66373a57251fb173151e838ccd27d279aca882997e005016bb53d5aa",
+                0,
+            ),
+            (
+                "6c1d56f31b2de4bfc6aaea28396b333102b1f600da9c6d6149e96ca43f1102b1",
+                1,
+            ),
+            (
+                "7a1de137cbafb5c70405455c49c5104ca3057a1f1243e6563bb9245c9c88c191",
+                0,
+            ),
+            (
+                "7d037ceb2ee0dc03e82f17be7935d238b35d1deabf953a892a4507bfbeeb3ba4",
+                1,
+            ),
+            (
+                "a5e899dddb28776ea9ddac0a502316d53a4a3fca607c72f66c470e0412e34086",
+                0,
+            ),
+            (
+                "b4112b8f900a7ca0c8b0e7c4dfad35c6be5f6be46b3458974988e1cdb2fa61b8",
+                0,
+            ),
+            (
+                "bafd65e3c7f3f9fdfdc1ddb026131b278c3be1af90a4a6ffa78c4658f9ec0c85",
+                0,
+            ),
+            (
+                "de0411a1e97484a2804ff1dbde260ac19de841bebad1880c782941aca883b4e9",
+                1,
+            ),
+            (
+                "f0a130a84912d03c1d284974f563c5949ac13f8342b8112edff52971599e6a45",
+                0,
+            ),
+            (
+                "f320832a9d2e2452af63154bc687493484a0e7745ebd3aaf9ca19eb80834ad60",
+                0,
+            ),
+        ];
+        let outputs = [
+            (
+                400_057_456,
+                "76a9144a5fba237213a062f6f57978f796390bdcf8d01588ac",
+            ),
+            (
+                40_000_000_000,
+                "76a9145be32612930b8323add2212a4ec03c1562084f8488ac",
+            ),
+        ];
+        let sorted = BitcoinTransaction::new_with_outputs(
+            1,
+            inputs
+                .iter()
+                .map(|&(txid, vout)| {
+                    TransactionInput::new(
+                        OutPoint::new(Txid::from_hex(txid).unwrap().0, vout),
+                        Script::new(vec![]),
+                        0xFFFFFFFF,
+                    )
+                })
+                .collect(),
+            outputs
+                .iter()
+                .map(|&(sats, script)| {
+                    TransactionOutput::new(
+                        Amount::from_sat(sats),
+                        Script::from_hex(script).unwrap(),
+                    )
+                })
+                .collect(),
+            0,
+        );
+        assert!(sorted.is_bip69_sorted());
+
+        let mut shuffled = sorted.clone();
+        shuffled.inputs.reverse();
+        shuffled.inputs.swap(3, 11);
+        shuffled.outputs.reverse();
+        assert!(!shuffled.is_bip69_sorted());
+        shuffled.sort_bip69();
+        assert_eq!(shuffled, sorted);
+
+        // Serialized byte order would put the input ending in ...0a (index 5) first.
+        let mut internal = sorted.clone();
+        internal
+            .inputs
+            .sort_by(|a, b| a.previous_output.cmp(&b.previous_output));
+        assert_eq!(internal.inputs[0], sorted.inputs[5]);
+        assert!(!internal.is_bip69_sorted());
+
+        // BIP69 test vector 2, txid 28204cad…cd3069a5f: two outputs of one transaction, and
+        // outputs sorted by amount before script.
+        let prev =
+            Txid::from_hex("35288d269cee1941eaebb2ea85e32b42cdb2b04284a56d8b14dcc3f5c65d6055")
+                .unwrap();
+        let mut tx = BitcoinTransaction::new_with_outputs(
+            1,
+            vec![
+                TransactionInput::new(OutPoint::new(prev.0, 1), Script::new(vec![]), 0xFFFFFFFF),
+                TransactionInput::new(OutPoint::new(prev.0, 0), Script::new(vec![]), 0xFFFFFFFF),
+            ],
+            vec![
+                TransactionOutput::new(
+                    Amount::from_sat(2_400_000_000),
+                    Script::from_hex(
+                        "41044a656f065871a353f216ca26cef8dde2f03e8c16202d2e8ad769f02032cb86a5eb5e56842e92e19141d60a01928f8dd2c875a390f67c1f6c94cfc617c0ea45afac",
+                    )
+                    .unwrap(),
+                ),
+                TransactionOutput::new(
+                    Amount::from_sat(100_000_000),
+                    Script::from_hex(
+                        "41046a0765b5865641ce08dd39690aade26dfbf5511430ca428a3089261361cef170e3929a68aee3d8d4848b0c5111b0a37b82b86ad559fd2a745b44d8e8d9dfdc0cac",
+                    )
+                    .unwrap(),
+                ),
+            ],
+            0,
+        );
+        tx.sort_bip69();
+        assert!(tx.is_bip69_sorted());
+        assert_eq!(tx.inputs[0].previous_output.vout, 0);
+        assert_eq!(tx.inputs[1].previous_output.vout, 1);
+        assert_eq!(tx.outputs[0].value, Amount::from_sat(100_000_000));
+        assert_eq!(tx.outputs[1].value, Amount::from_sat(2_400_000_000));
+
+        // Equal amounts fall back to the scriptPubKey bytes.
+        let output = |script: Vec<u8>| TransactionOutput::new(Amount::ONE_SAT, Script::new(script));
+        let mut tx = BitcoinTransaction::new_with_outputs(
+            1,
+            vec![],
+            vec![
+                output(vec![0x52]),
+                output(vec![0x51, 0x51]),
+                output(vec![0x51]),
+            ],
+            0,
+        );
+        tx.sort_bip69();
+        let scripts: Vec<_> = tx
+            .outputs
+            .iter()
+            .map(|o| o.script_pubkey.to_hex())
+            .collect();
+        assert_eq!(scripts, ["51", "5151", "52"]);
+    }
+
+    #[test]
+    fn test_select_coins_largest_first() {
+        let p2wpkh = Script::new_p2wpkh(&Hash160([0x11; 20]));
+        let utxo = |n: u8, sats: u64, script: &Script| {
+            (
+                OutPoint::new(dummy_txid(n), 0),
+                TransactionOutput::new(Amount::from_sat(sats), script.clone()),
+            )
+        };
+        let utxos = [
+            utxo(1, 10_000, &p2wpkh),
+            utxo(2, 50_000, &p2wpkh),
+            utxo(3, 30_000, &p2wpkh),
+        ];
+        let rate = FeeRate::BROADCAST_MIN;
+        let select = |target: u64| {
+            select_coins(
+                &utxos,
+                Amount::from_sat(target),
+                rate,
+                SelectionStrategy::LargestFirst,
+            )
+        };
+
+        // Two P2WPKH inputs: 40 + 2 * 272 + 2 = 586 WU, and 710 WU with a change output,
+        // so 178 sat at 1 sat/vB.
+        assert_eq!(
+            select(60_000),
+            Ok(Selection {
+                outpoints: vec![utxos[1].0.clone(), utxos[2].0.clone()],
+                input_value: Amount::from_sat(80_000),
+                fee: Amount::from_sat(178),
+                change: Amount::from_sat(19_822),
+            })
+        );
+        // 122 sat of change would be dust, so it goes to the fee instead.
+        assert_eq!(
+            select(79_700),
+            Ok(Selection {
+                outpoints: vec![utxos[1].0.clone(), utxos[2].0.clone()],
+                input_value: Amount::from_sat(80_000),
+                fee: Amount::from_sat(300),
+                change: Amount::ZERO,
+            })
+        );
+        // One input is enough when it covers the fee too.
+        assert_eq!(select(40_000).unwrap().outpoints, [utxos[1].0.clone()]);
+
+        assert_eq!(
+            select(90_001),
+            Err(SelectionError::InsufficientFunds {
+                available: Amount::from_sat(90_000),
+                target: Amount::from_sat(90_001),
+            })
+        );
+        assert_eq!(
+            select(90_000),
+            Err(SelectionError::CannotCoverFee {
+                available: Amount::from_sat(90_000),
+                target: Amount::from_sat(90_000),
+            })
+        );
+    }
+
+    #[test]
+    fn test_select_coins_skips_unusable_utxos() {
+        let p2pkh = Script::new_p2pkh(&Hash160([0x22; 20]));
+        let p2wpkh = Script::new_p2wpkh(&Hash160([0x11; 20]));
+        let p2sh = Script::new_p2sh(&Hash160([0x33; 20]));
+        let utxo = |n: u8, sats: u64, script: &Script| {
+            (
+                OutPoint::new(dummy_txid(n), 0),
+                TransactionOutput::new(Amount::from_sat(sats), script.clone()),
+            )
+        };
+        let rate = FeeRate::BROADCAST_MIN;
+
+        // A P2SH output's spend size is unknown, so it doesn't count as available.
+        let utxos = [utxo(1, 100_000, &p2sh), utxo(2, 10_000, &p2wpkh)];
+        assert_eq!(
+            select_coins(
+                &utxos,
+                Amount::from_sat(50_000),
+                rate,
+                SelectionStrategy::LargestFirst
+            ),
+            Err(SelectionError::InsufficientFunds {
+                available: Amount::from_sat(10_000),
+                target: Amount::from_sat(50_000),
+            })
+        );
+
+        // 50 sat of P2PKH costs 148 sat to spend.
+        let utxos = [utxo(1, 50, &p2pkh)];
+        assert_eq!(
+            select_coins(
+                &utxos,
+                Amount::from_sat(10),
+                rate,
+                SelectionStrategy::LargestFirst
+            ),
+            Err(SelectionError::CannotCoverFee {
+                available: Amount::from_sat(50),
+                target: Amount::from_sat(10),
+            })
+        );
+
+        // A legacy input in a segwit transaction still takes an empty witness:
+        // 40 + 592 + 272 + 2 + 1 = 907 WU without change.
+        let utxos = [utxo(1, 20_000, &p2pkh), utxo(2, 10_000, &p2wpkh)];
+        let selection = select_coins(
+            &utxos,
+            Amount::from_sat(29_700),
+            rate,
+            SelectionStrategy::LargestFirst,
+        )
+        .unwrap();
+        assert_eq!(selection.outpoints.len(), 2);
+        assert_eq!(selection.fee, Amount::from_sat(300));
+        assert!(
+            FeeRate::BROADCAST_MIN
+                .fee_for_weight(Weight::from_wu(907))
+                .unwrap()
+                <= selection.fee
+        );
+    }
+
+    #[test]
+    fn test_select_coins_single_random_draw() {
+        let p2tr = Script::from_hex(TAPROOT_SCRIPT_PATHS[0].0).unwrap();
+        let utxos: Vec<_> = (1..=10u8)
+            .map(|n| {
+                (
+                    OutPoint::new(dummy_txid(n), 0),
+                    TransactionOutput::new(Amount::from_sat(n as u64 * 10_000), p2tr.clone()),
+                )
+            })
+            .collect();
+        let draw = |seed: u64| {
+            select_coins(
+                &utxos,
+                Amount::from_sat(100_000),
+                FeeRate::from_sat_per_vb(2).unwrap(),
+                SelectionStrategy::SingleRandomDraw { seed },
+            )
+            .unwrap()
+        };
+        let selection = draw(7);
+        assert_eq!(draw(7), selection);
+        assert_eq!(
+            selection.input_value,
+            Amount::from_sat(100_000 + selection.fee.to_sat() + selection.change.to_sat())
+        );
+        // Two P2TR key-path inputs and change: 40 + 2 * 230 + 2 + 124 = 626 WU at 2 sat/vB.
+        assert_eq!(selection.fee, Amount::from_sat(313));
+        let chosen = |selection: &Selection| -> Vec<u8> {
+            selection.outpoints.iter().map(|o| o.txid.0[31]).collect()
+        };
+        assert_eq!(chosen(&selection), [9, 2]);
+        assert_eq!(chosen(&draw(0)), [7, 4]);
+    }
+
+    #[test]
+    fn test_tx_rpc_json_matches_core() {
+        let expected: serde_json::Value = serde_json::from_str(BLOCK_702861_P2WPKH_TX_RPC).unwrap();
+        let tx =
+            BitcoinTransaction::from_bytes_exact(&hex::decode(BLOCK_702861_P2WPKH_TX).unwrap())
+                .unwrap();
+        assert_eq!(tx.to_rpc_json(Network::Mainnet), expected);
+
+        // Core's asm: numbers for small pushes, and the sighash named in scriptSigs only.
+        let tx = BitcoinTransaction::from_bytes_exact(&hex::decode(BLOCK_170_TX).unwrap()).unwrap();
+        let json = tx.to_rpc_json(Network::Mainnet);
+        assert_eq!(
+            json["vin"][0]["scriptSig"]["asm"],
+            format!("{}[ALL]", &BLOCK_170_SIG[..BLOCK_170_SIG.len() - 2])
+        );
+        assert!(json["vin"][0].get("txinwitness").is_none());
+        assert_eq!(json["vout"][1]["value"], 40.0);
+        assert_eq!(json["vout"][1]["scriptPubKey"]["type"], "pubkey");
+        assert!(json["vout"][1]["scriptPubKey"].get("address").is_none());
+        assert_eq!(
+            json["vout"][1]["scriptPubKey"]["desc"],
+            "pk(0411db93e1dcdb8a016b49840f8c53bc1eb68a382e97b1482ecad7b148a6909a5cb2e0eaddfb84ccf97\
+             44464f82e160bfa9b8b64f9d4c03f999b8643f656b412a3)#u7qfa49l"
+        );
+        assert_eq!(json["hash"], json["txid"]);
+
+        // The two-byte push ff80 is the non-minimal CScriptNum -255.
+        let op_return = Script::new(vec![
+            0x6a, 0x4f, 0x60, 0x02, 0xff, 0x80, 0x05, 1, 2, 3, 4, 5,
+        ]);
+        let coinbase = BitcoinTransaction::new_with_outputs(
+            1,
+            vec![TransactionInput::new(
+                OutPoint::null(),
+                Script::new(vec![0x03, 0x8d, 0xb9, 0x0a, 0x51]),
+                0xFFFFFFFF,
+            )],
+            vec![TransactionOutput::new(Amount::ZERO, op_return)],
+            0,
+        );
+        let json = coinbase.to_rpc_json(Network::Mainnet);
+        assert_eq!(
+            json["vin"][0],
+            serde_json::json!({"coinbase": "038db90a51", "sequence": 4294967295u32})
+        );
+        assert_eq!(
+            json["vout"][0]["scriptPubKey"]["asm"],
+            "OP_RETURN -1 16 -255 0102030405"
+        );
+        assert_eq!(json["vout"][0]["scriptPubKey"]["type"], "nulldata");
+        assert_eq!(
+            json["vout"][0]["scriptPubKey"]["desc"],
+            "raw(6a4f6002ff80050102030405)#epusnkjv"
+        );
+    }
+
+    #[test]
+    fn test_tx_rpc_json_descriptors() {
+        let desc = |script: Script| {
+            let tx = BitcoinTransaction::new_with_outputs(
+                2,
+                vec![],
+                vec![TransactionOutput::new(Amount::ZERO, script)],
+                0,
+            );
+            tx.to_rpc_json(Network::Mainnet)["vout"][0]["scriptPubKey"]["desc"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        // BIP380's example checksum.
+        assert_eq!(
+            desc(Script::new(vec![0xde, 0xad, 0xbe, 0xef])),
+            "raw(deadbeef)#89f8spxm"
+        );
+        let first = [[0x02].as_slice(), &[0x11; 32]].concat();
+        let second = [[0x03].as_slice(), &[0x22; 32]].concat();
+        let multisig = ScriptBuilder::new()
+            .push_opcode(opcodes::OP_1)
+            .push_slice(&first)
+            .push_slice(&second)
+            .push_opcode(opcodes::OP_2)
+            .push_opcode(opcodes::OP_CHECKMULTISIG)
+            .into_script();
+        assert_eq!(
+            desc(multisig),
+            format!(
+                "multi(1,02{},03{})#47newhng",
+                "11".repeat(32),
+                "22".repeat(32)
+            )
+        );
+        assert_eq!(
+            desc(Script::new_p2tr(&XOnlyPublicKey([0x33; 32]))),
+            format!("rawtr({})#rrahfrvk", "33".repeat(32))
+        );
+        // Hybrid keys have no descriptor, so the script is given raw.
+        let hybrid = [[0x06].as_slice(), &[0x44; 64]].concat();
+        let p2pk = ScriptBuilder::new()
+            .push_slice(&hybrid)
+            .push_opcode(opcodes::OP_CHECKSIG)
+            .into_script();
+        assert_eq!(p2pk.classify(), ScriptType::P2PK);
+        assert_eq!(
+            desc(p2pk.clone()),
+            format!("raw({})#v0xdf4ps", p2pk.to_hex())
+        );
+    }
+
+    #[test]
+    fn test_output_standardness() {
+        let p2pkh = Script::new_p2pkh(&Hash160([0x62; 20]));
+        let output = |sats: u64, script: &Script| {
+            TransactionOutput::new(Amount::from_sat(sats), script.clone())
+        };
+        assert_eq!(output(546, &p2pkh).is_standard(), Ok(()));
+        assert_eq!(
+            output(545, &p2pkh).is_standard(),
+            Err(StandardnessViolation::Dust {
+                value: Amount::from_sat(545),
+                threshold: Amount::from_sat(546),
+            })
+        );
+
+        // 80 bytes of data fit in an 83-byte script; 81 do not.
+        let op_return = Script::new_op_return(&[0xAB; 80]).unwrap();
+        assert_eq!(
+            op_return.len(),
+            TransactionOutput::MAX_STANDARD_OP_RETURN_SIZE
+        );
+        assert_eq!(output(0, &op_return).is_standard(), Ok(()));
+        let oversized = ScriptBuilder::new()
+            .push_opcode(opcodes::OP_RETURN)
+            .push_slice(&[0xAB; 81])
+            .into_script();
+        assert_eq!(
+            output(0, &oversized).is_standard(),
+            Err(StandardnessViolation::DataCarrierTooLarge { size: 84 })
+        );
+        assert!(output(0, &oversized).is_unspendable());
+
+        let key = "02dfaba46d2417eee4661d45a6ab44f15cf2c77377045c678c926142b6b611ab9e";
+        let multisig = |required: usize, total: usize| {
+            let keys = vec![key; total].join(" ");
+            Script::from_asm_str(&format!("OP_{required} {keys} OP_{total} OP_CHECKMULTISIG"))
+                .unwrap()
+        };
+        assert_eq!(output(1_000, &multisig(2, 3)).is_standard(), Ok(()));
+        assert_eq!(
+            output(1_000, &multisig(1, 4)).is_standard(),
+            Err(StandardnessViolation::BareMultisigTooLarge { keys: 4 })
+        );
+        assert_eq!(
+            output(1_000, &Script::new(vec![0x51])).is_standard(),
+            Err(StandardnessViolation::NonStandardScript)
+        );
+
+        let input = |script_sig: Script| {
+            TransactionInput::new(OutPoint::new(dummy_txid(1), 0), script_sig, 0xFFFFFFFF)
+        };
+        let mut tx = BitcoinTransaction::new_with_outputs(
+            2,
+            vec![input(Script::new(vec![]))],
+            vec![
+                output(546, &p2pkh),
+                output(545, &p2pkh),
+                output(0, &oversized),
+            ],
+            0,
+        );
+        assert_eq!(
+            tx.check_output_standardness(),
+            Err(vec![
+                (
+                    1,
+                    StandardnessViolation::Dust {
+                        value: Amount::from_sat(545),
+                        threshold: Amount::from_sat(546),
+                    }
+                ),
+                (2, StandardnessViolation::DataCarrierTooLarge { size: 84 }),
+            ])
+        );
+        assert!(!tx.is_standard());
+        tx.outputs.truncate(1);
+        assert!(tx.is_standard());
+        tx.inputs[0].script_sig = Script::new(vec![0x51, 0x76]);
+        assert!(!tx.is_standard());
+    }
+
+    #[test]
+    fn test_likely_change_outputs() {
+        let p2wpkh = |n: u8| Script::new_p2wpkh(&Hash160([n; 20]));
+        let p2tr = Script::from_hex(TAPROOT_SCRIPT_PATHS[0].0).unwrap();
+        let input = TransactionInput::new(OutPoint::new(dummy_txid(1), 0), Script::new(vec![]), 0);
+        let tx = |outputs: Vec<(u64, Script)>| {
+            BitcoinTransaction::new_with_outputs(
+                2,
+                vec![input.clone()],
+                outputs
+                    .into_iter()
+                    .map(|(sats, script)| TransactionOutput::new(Amount::from_sat(sats), script))
+                    .collect(),
+                0,
+            )
+        };
+
+        // Address reuse: the second output pays back to the input's own script.
+        let reuse = tx(vec![(50_000, p2wpkh(2)), (12_345, p2wpkh(1))]);
+        assert_eq!(reuse.likely_change_outputs(&[p2wpkh(1)]), [1]);
+        // Script type: only the taproot output matches the taproot inputs; both are round.
+        let script_type = tx(vec![(100_000, p2wpkh(2)), (250_000, p2tr.clone())]);
+        assert_eq!(
+            script_type.likely_change_outputs(&[p2tr.clone(), p2tr.clone()]),
+            [1]
+        );
+        // Round amounts: same types everywhere, so only the odd amount stands out.
+        let round = tx(vec![(1_000_000, p2wpkh(2)), (873_421, p2wpkh(3))]);
+        assert_eq!(round.likely_change_outputs(&[p2wpkh(1)]), [1]);
+        assert_eq!(round.likely_change_outputs(&[]), [1]);
+
+        // Nothing to go on.
+        let ambiguous = tx(vec![(1_000_000, p2wpkh(2)), (2_000_000, p2wpkh(3))]);
+        assert!(ambiguous.likely_change_outputs(&[p2wpkh(1)]).is_empty());
+        let op_return = Script::new_op_return(b"hi").unwrap();
+        assert!(
+            tx(vec![(0, op_return.clone())])
+                .likely_change_outputs(&[])
+                .is_empty()
+        );
+        assert!(
+            tx(vec![(0, op_return), (12_345, p2wpkh(1))])
+                .likely_change_outputs(&[p2wpkh(1)])
+                .is_empty()
+        );
+        assert!(tx(vec![]).likely_change_outputs(&[p2wpkh(1)]).is_empty());
+        let coinbase = BitcoinTransaction::new_with_outputs(
+            1,
+            vec![TransactionInput::new(
+                OutPoint::null(),
+                Script::new(vec![0x51, 0x51]),
+                0,
+            )],
+            reuse.outputs.clone(),
+            0,
+        );
+        assert!(coinbase.likely_change_outputs(&[p2wpkh(1)]).is_empty());
+    }
+
+    #[test]
+    fn test_legacy_sighash_core_vectors() {
+        // From Bitcoin Core's sighash.json: transaction, script code, input index, hash type
+        // (an arbitrary i32, committed to in full) and the hash as displayed. Two of each
+        // base type, with and without ANYONECANPAY.
+        let vectors: [(&str, &str, usize, i32, &str); 12] = [
+            (
+                "d3b7421e011f4de0f1cea9ba7458bf3486bee722519efab711a963fa8c100970cf7488b7bb0200000003525352dcd61b300148be5d05000000000000000000",
+                "535251536aac536a",
+                0,
+                -1960128125,
+                "29aa6d2d752d3310eba20442770ad345b7f6a35f96161ede5f07b33e92053e2a",
+            ),
+            (
+                "ff5400dd02fec5beb9a396e1cbedc82bedae09ed44bae60ba9bef2ff375a6858212478844b03000000025253ffffffff01e46c203577a79d1172db715e9cc6316b9cfc59b5e5e4d9199fef201c6f9f0f000000000900ab6552656a5165acffffffff02e8ce62040000000002515312ce3e00000000000251513f119316",
+                "",
+                0,
+                1541581667,
+                "1e0da47eedbbb381b0e0debbb76e128d042e02e65b11125e17fd127305fc65cd",
+            ),
+            (
+                "9ff618e60136f8e6bb7eabaaac7d6e2535f5fba95854be6d2726f986eaa9537cb283c701ff02000000026a65ffffffff012d1c0905000000000865ab00ac6a516a652f9ad240",
+                "51515253635351ac",
+                0,
+                1571304387,
+                "659cd3203095d4a8672646add7d77831a1926fc5b66128801979939383695a79",
+            ),
+            (
+                "c33028b301d5093e1e8397270d75a0b009b2a6509a01861061ab022ca122a6ba935b8513320200000000ffffffff013bcf5a0500000000015200000000",
+                "",
+                0,
+                -513413204,
+                "6b1459536f51482f5dbf42d7e561896557461e1e3b6bf67871e2b51faae2832c",
+            ),
+            (
+                "97be4f7702dc20b087a1fdd533c7de762a3f2867a8f439bddf0dcec9a374dfd0276f9c55cc0300000000cdfb1dbe6582499569127bda6ca4aaff02c132dc73e15dcd91d73da77e92a32a13d1a0ba0200000002ab51ffffffff048cfbe202000000000900516351515363ac535128ce0100000000076aac5365ab6aabc84e8302000000000863536a53ab6a6552f051230500000000066aac535153510848d813",
+                "ac51",
+                0,
+                229541474,
+                "e5da9a416ea883be1f8b8b2d178463633f19de3fa82ae25d44ffb531e35bdbc8",
+            ),
+            (
+                "2f7353dd02e395b0a4d16da0f7472db618857cd3de5b9e2789232952a9b154d249102245fd030000000151617fd88f103280b85b0a198198e438e7cab1a4c92ba58409709997cc7a65a619eb9eec3c0200000003636aabffffffff0397481c0200000000045300636a0dc97803000000000009d389030000000003ac6a53134007bb",
+                "0000536552526a",
+                0,
+                -1912746174,
+                "30c4cd4bd6b291f7e9489cc4b4440a083f93a7664ea1f93e77a9597dab8ded9c",
+            ),
+            (
+                "f2b539a401e4e8402869d5e1502dbc3156dbce93583f516a4947b333260d5af1a34810c6a00200000003525363ffffffff01d305e2000000000005acab535200a265fe77",
+                "",
+                0,
+                -1435650456,
+                "41617b27321a830c712638dbb156dae23d4ef181c7a06728ccbf3153ec53d7dd",
+            ),
+            (
+                "148e68480196eb52529af8e83e14127cbfdbd4a174e60a86ac2d86eac9665f46f4447cf7aa01000000045200ac538f8f871401cf240c0300000000065252ab52656a5266cf61",
+                "",
+                0,
+                -344314825,
+                "eacc47c5a53734d6ae3aedbc6a7c0a75a1565310851b29ef0342dc4745ceb607",
+            ),
+            (
+                "32fa0b0804e6ea101e137665a041cc2350b794e59bf42d9b09088b01cde806ec1bbea077df0200000008515153650000006506a11c55904258fa418e57b88b12724b81153260d3f4c9f080439789a391ab147aabb0fa0000000007000052ac51ab510986f2a15c0d5e05d20dc876dd2dafa435276d53da7b47c393f20900e55f163b97ce0b800000000008ab526a520065636a8087df7d4d9c985fb42308fb09dce704650719140aa6050e8955fa5d2ea46b464a333f870000000009636300636a6565006affffffff01994a0d040000000002536500000000",
+                "516563530065",
+                2,
+                -163068286,
+                "f58637277d2bc42e18358dc55f7e87e7043f5e33f4ce1fc974e715ef0d3d1c2a",
+            ),
+            (
+                "e3cdbfb4014d90ae6a4401e85f7ac717adc2c035858bf6ff48979dd399d155bce1f150daea0300000002ac51a67a0d39017f6c71040000000005535200535200000000",
+                "",
+                0,
+                -1899950911,
+                "c1c7df8206e661d593f6455db1d61a364a249407f88e99ecad05346e495b38d7",
+            ),
+            (
+                "6f62138301436f33a00b84a26a0457ccbfc0f82403288b9cbae39986b34357cb2ff9b889b302000000045253655335a7ff6701bac9960400000000086552ab656352635200000000",
+                "6aac51",
+                0,
+                1444414211,
+                "502a2435fd02898d2ff3ab08a3c19078414b32ec9b73d64a944834efc9dae10c",
+            ),
+            (
+                "b3cad3a7041c2c17d90a2cd994f6c37307753fa3635e9ef05ab8b1ff121ca11239a0902e700300000009ab635300006aac5163ffffffffcec91722c7468156dce4664f3c783afef147f0e6f80739c83b5f09d5a09a57040200000004516a6552ffffffff969d1c6daf8ef53a70b7cdf1b4102fb3240055a8eaeaed2489617cd84cfd56cf020000000352ab53ffffffff46598b6579494a77b593681c33422a99559b9993d77ca2fa97833508b0c169f80200000009655300655365516351ffffffff04d7ddf800000000000853536a65ac6351ab09f3420300000000056aab65abac33589d04000000000952656a65655151acac944d6f0400000000006a8004ba",
+                "005165",
+                1,
+                1035865506,
+                "fe1dc9e8554deecf8f50c417c670b839cc9d650722ebaaf36572418756075d58",
+            ),
+        ];
+        for (tx, script_code, input_index, hash_type, expected) in vectors {
+            let tx = BitcoinTransaction::from_bytes_exact(&hex::decode(tx).unwrap()).unwrap();
+            let script_code = Script::from_hex(script_code).unwrap();
+            let sighash = tx
+                .legacy_sighash_raw(input_index, &script_code, hash_type as u32)
+                .unwrap();
+            let mut displayed = sighash.0;
+            displayed.reverse();
+            assert_eq!(hex::encode(displayed), expected);
+        }
+    }
+
+    #[test]
+    fn test_legacy_sighash_types_and_edge_cases() {
+        let mut tx = BitcoinTransaction::new_with_outputs(
+            1,
+            vec![
+                TransactionInput::new(OutPoint::new([1; 32], 0), Script::new(vec![0x51]), 1),
+                TransactionInput::new(OutPoint::new([2; 32], 1), Script::new(vec![]), 2),
+            ],
+            vec![TransactionOutput::new(
+                Amount::from_sat(1000),
+                Script::new_p2wpkh(&Hash160([3; 20])),
+            )],
+            0,
+        );
+        let script_code = Script::new_p2pkh(&Hash160([4; 20]));
+        for (sighash_type, raw) in [
+            (SighashType::All, 0x01),
+            (SighashType::None, 0x02),
+            (SighashType::Single, 0x03),
+            (SighashType::AllPlusAnyoneCanPay, 0x81),
+            (SighashType::NonePlusAnyoneCanPay, 0x82),
+            (SighashType::SinglePlusAnyoneCanPay, 0x83),
+        ] {
+            assert_eq!(
+                tx.legacy_sighash(0, &script_code, sighash_type),
+                tx.legacy_sighash_raw(0, &script_code, raw)
+            );
+        }
+
+        // scriptSigs are not signed, and ANYONECANPAY leaves out the other inputs entirely.
+        let all = tx
+            .legacy_sighash(0, &script_code, SighashType::All)
+            .unwrap();
+        let acp = tx
+            .legacy_sighash(0, &script_code, SighashType::AllPlusAnyoneCanPay)
+            .unwrap();
+        tx.inputs[0].script_sig = Script::new(vec![0x52]);
+        assert_eq!(
+            tx.legacy_sighash(0, &script_code, SighashType::All)
+                .unwrap(),
+            all
+        );
+        tx.inputs[1].previous_output.vout = 2;
+        assert_ne!(
+            tx.legacy_sighash(0, &script_code, SighashType::All)
+                .unwrap(),
+            all
+        );
+        assert_eq!(
+            tx.legacy_sighash(0, &script_code, SighashType::AllPlusAnyoneCanPay)
+                .unwrap(),
+            acp
+        );
+
+        // OP_CODESEPARATORs are removed from the script code, but not push data equal to 0xab.
+        let with_separators = Script::new([&[0xab][..], script_code.as_bytes(), &[0xab]].concat());
+        assert_eq!(
+            tx.legacy_sighash(0, &with_separators, SighashType::All)
+                .unwrap(),
+            tx.legacy_sighash(0, &script_code, SighashType::All)
+                .unwrap()
+        );
+        let pushed = Script::new(vec![0x01, 0xab]);
+        assert_ne!(
+            tx.legacy_sighash(0, &pushed, SighashType::All).unwrap(),
+            tx.legacy_sighash(0, &Script::new(vec![0x01]), SighashType::All)
+                .unwrap()
+        );
+
+        // SIGHASH_SINGLE without a matching output signs the constant 1.
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        assert_eq!(
+            tx.legacy_sighash(1, &script_code, SighashType::Single),
+            Ok(Hash256(one))
+        );
+        assert_eq!(
+            tx.legacy_sighash(2, &script_code, SighashType::All),
+            Err(BitcoinError::IndexOutOfRange { index: 2, len: 2 })
+        );
+    }
+
+    // The unsigned transactions of the BIP143 "Native P2WPKH" and "P2SH-P2WPKH" examples.
+    const BIP143_P2WPKH_TX: &str = "0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f00000000\
+     00eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000\
+     ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac909351\
+     0d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000";
+    const BIP143_P2SH_P2WPKH_TX: &str = "0100000001db6b1b20aa0fd7b23880be2ecbd4a98130974cf4748fb66092ac4d3ceb1a547701000000\
+     00feffffff02b8b4eb0b000000001976a914a457b684d7f0d539a46a45bbc043f35b59d0d96388ac0008\
+     af2f000000001976a914fd270b1ee6abcaea97fea7ad0402e8bd8ad6d77c88ac92040000";
+
+    #[test]
+    fn test_segwit_v0_sighash_bip143_p2wpkh() {
+        // BIP143 "Native P2WPKH": the second input spends 6 BTC from a P2WPKH output.
+        let tx =
+            BitcoinTransaction::from_bytes_exact(&hex::decode(BIP143_P2WPKH_TX).unwrap()).unwrap();
+        let hashes = tx.segwit_v0_hashes();
+        assert_eq!(
+            hashes.hash_prevouts.to_hex_internal(),
+            "96b827c8483d4e9b96712b6713a7b68d6e8003a781feba36c31143470b4efd37"
+        );
+        assert_eq!(
+            hashes.hash_sequence.to_hex_internal(),
+            "52b0a642eea2fb7ae638c36f6252b6750293dbe574a806984b8e4d8548339a3b"
+        );
+        assert_eq!(
+            hashes.hash_outputs.to_hex_internal(),
+            "863ef3e1a92afbfdb97f31ad0fc7683ee943e9abcf2501590ff8f6551f47e5e5"
+        );
+
+        let script_code =
+            Script::from_hex("76a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a188ac").unwrap();
+        let sighash = tx
+            .segwit_v0_sighash(
+                1,
+                &script_code,
+                Amount::from_sat(600_000_000),
+                SighashType::All,
+            )
+            .unwrap();
+        assert_eq!(
+            sighash.to_hex_internal(),
+            "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670"
+        );
+        assert_eq!(
+            tx.segwit_v0_sighash(2, &script_code, Amount::ZERO, SighashType::All),
+            Err(BitcoinError::IndexOutOfRange { index: 2, len: 2 })
+        );
+    }
+
+    #[test]
+    fn test_segwit_v0_sighash_bip143_p2sh_p2wpkh() {
+        // BIP143 "P2SH-P2WPKH": the only input spends 10 BTC.
+        let tx = BitcoinTransaction::from_bytes_exact(&hex::decode(BIP143_P2SH_P2WPKH_TX).unwrap())
+            .unwrap();
+        let hashes = tx.segwit_v0_hashes();
+        assert_eq!(
+            hashes.hash_prevouts.to_hex_internal(),
+            "b0287b4a252ac05af83d2dcef00ba313af78a3e9c329afa216eb3aa2a7b4613a"
+        );
+        assert_eq!(
+            hashes.hash_sequence.to_hex_internal(),
+            "18606b350cd8bf565266bc352f0caddcf01e8fa789dd8a15386327cf8cabe198"
+        );
+        assert_eq!(
+            hashes.hash_outputs.to_hex_internal(),
+            "de984f44532e2173ca0d64314fcefe6d30da6f8cf27bafa706da61df8a226c83"
+        );
+
+        let script_code =
+            Script::from_hex("76a91479091972186c449eb1ded22b78e40d009bdf008988ac").unwrap();
+        let sighash = tx
+            .segwit_v0_sighash(
+                0,
+                &script_code,
+                Amount::from_sat(1_000_000_000),
+                SighashType::All,
+            )
+            .unwrap();
+        assert_eq!(
+            sighash.to_hex_internal(),
+            "64f3b0f4dd2bb3aa1ce8566d220cc74dda9df97d8490cc81d89d735c92e59fb6"
+        );
+    }
+
+    #[test]
+    fn test_segwit_v0_sighash_types() {
+        // One P2WPKH input signed with each sighash type by Bitcoin Core, which prints the
+        // hashes byte-reversed.
+        let tx = BitcoinTransaction::from_bytes_exact(
+            &hex::decode(
+                "0200000001cf309ee0839b8aaa3fbc84f8bd32e9c6357e99b49bf6a3af90308c68e762f1d701000000\
+                 00feffffff0288528c61000000001600146e8d9e07c543a309dcdeba8b50a14a991a658c5be0aebb00\
+                 00000000160014698d8419804a5d5994704d47947889ff7620c004db000000",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let script_code =
+            Script::from_hex("76a91462744660c6b5133ddeaacbc57d2dc2d7b14d0b0688ac").unwrap();
+        for (sighash_type, expected) in [
+            (
+                SighashType::All,
+                "0a1bc2758dbb5b3a56646f8cafbf63f410cc62b77a482f8b87552683300a7711",
+            ),
+            (
+                SighashType::None,
+                "3e275ac8b084f79f756dcd535bffb615cc94a685eefa244d9031eaf22e4cec12",
+            ),
+            (
+                SighashType::Single,
+                "191a08165ffacc3ea55753b225f323c35fd00d9cc0268081a4a501921fc6ec14",
+            ),
+            (
+                SighashType::AllPlusAnyoneCanPay,
+                "4b6b612530f94470bbbdef18f57f2990d56b239f41b8728b9a49dc8121de4559",
+            ),
+            (
+                SighashType::NonePlusAnyoneCanPay,
+                "a7e916d3acd4bb97a21e6793828279aeab02162adf8099ea4f309af81f3d5adb",
+            ),
+            (
+                SighashType::SinglePlusAnyoneCanPay,
+                "d9276e2a48648ddb53a4aaa58314fc2b8067c13013e1913ffb67e0988ce82c78",
+            ),
+        ] {
+            let sighash = tx
+                .segwit_v0_sighash(
+                    0,
+                    &script_code,
+                    Amount::from_sat(1_648_888_940),
+                    sighash_type,
+                )
+                .unwrap();
+            let mut displayed = sighash.0;
+            displayed.reverse();
+            assert_eq!(hex::encode(displayed), expected, "{sighash_type}");
+        }
+    }
+    #[test]
+    fn test_segwit_v0_sighash_undefined_types_commit_to_sequences() {
+        let mut tx = BitcoinTransaction::new(
+            2,
+            vec![
+                TransactionInput::new(
+                    OutPoint::from_txid(Txid([0x11; 32]), 0),
+                    Script::new(vec![]),
+                    0xFFFFFFFD,
+                ),
+                TransactionInput::new(
+                    OutPoint::from_txid(Txid([0x22; 32]), 1),
+                    Script::new(vec![]),
+                    0xFFFFFFFD,
+                ),
+            ],
+            0,
+        );
+        let script_code =
+            Script::from_hex("76a91462744660c6b5133ddeaacbc57d2dc2d7b14d0b0688ac").unwrap();
+        let value = Amount::from_sat(50_000);
+        let digests = |tx: &BitcoinTransaction| {
+            [0x00, 0x01, 0x02, 0x04, 0x1f].map(|sighash_type| {
+                tx.segwit_v0_sighash_raw(0, &script_code, value, sighash_type)
+                    .unwrap()
+            })
+        };
+        let before = digests(&tx);
+        // Only hashSequence covers the other input's sequence.
+        tx.inputs[1].sequence = Sequence(0);
+        let after = digests(&tx);
+        // Undefined base types sign like ALL, hashSequence included; NONE zeroes it.
+        assert_ne!(before[0], after[0]);
+        assert_ne!(before[1], after[1]);
+        assert_eq!(before[2], after[2]);
+        assert_ne!(before[3], after[3]);
+        assert_ne!(before[4], after[4]);
+    }
+
+    #[test]
+    fn test_taproot_sighash_vectors() {
+        // From Bitcoin Core's functional tests: transaction, the outputs it spends as a
+        // CompactSize-prefixed list, input index, type, annex, tapscript and the hash.
+        type Vector = (
+            &'static str,
+            &'static str,
+            usize,
+            TapSighashType,
+            Option<&'static str>,
+            Option<&'static str>,
+            &'static str,
+        );
+        let vectors: [Vector; 10] = [
+            (
+                "020000000164eb050a5e3da0c2a65e4786f26d753b7bc69691fabccafb11f7acef36641f1846010000003101b2b404392a22000000000017a9147f2bde86fe78bf68a0544a4f290e12f0b7e0a08c87580200000000000017a91425d11723074ecfb96a0a83c3956bfaf362ae0c908758020000000000001600147e20f938993641de67bb0cdd71682aa34c4d29ad5802000000000000160014c64984dc8761acfa99418bd6bedc79b9287d652d72000000",
+                "01365724000000000023542156b39dab4f8f3508e0432cfb41fab110170acaa2d4c42539cb90a4dc7c093bc500",
+                0,
+                TapSighashType::Default,
+                None,
+                None,
+                "33ca0ebfb4a945eeee9569fc0f5040221275f88690b7f8592ada88ce3bdf6703",
+            ),
+            (
+                "0200000002fff49be59befe7566050737910f6ccdc5e749c7f8860ddc140386463d88c5ad0f3000000002cf68eb4a3d67f9d4c079249f7e4f27b8854815cb1ed13842d4fbf395f9e217fd605ee24090100000065235d9203f458520000000000160014b6d48333bb13b4c644e57c43a9a26df3a44b785e58020000000000001976a914eea9461a9e1e3f765d3af3e726162e0229fe3eb688ac58020000000000001976a9143a8869c9f2b5ea1d4ff3aeeb6a8fb2fffb1ad5fe88ac0ad7125c",
+                "02591f220000000000225120f25ad35583ea31998d968871d7de1abd2a52f6fe4178b54ea158274806ff4ece48fb310000000000225120f25ad35583ea31998d968871d7de1abd2a52f6fe4178b54ea158274806ff4ece",
+                1,
+                TapSighashType::All,
+                None,
+                None,
+                "626ab955d58c9a8a600a0c580549d06dc7da4e802eb2a531f62a588e430967a8",
+            ),
+            (
+                "0200000001350005f65aa830ced2079df348e2d8c2bdb4f10e2dde6a161d8a07b40d1ad87dae000000001611d0d603d9dc0e000000000017a914459b6d7d6bbb4d8837b4bf7e9a4556f952da2f5c8758020000000000001976a9141dd70e1299ffc2d5b51f6f87de9dfe9398c33cbb88ac58020000000000001976a9141dd70e1299ffc2d5b51f6f87de9dfe9398c33cbb88aca71c1f4f",
+                "01c4811000000000002251201bf9297d0a2968ae6693aadd0fa514717afefd218087a239afb7418e2d22e65c",
+                0,
+                TapSighashType::AllPlusAnyoneCanPay,
+                None,
+                None,
+                "dfa9437f9c9a1d1f9af271f79f2f5482f287cdb0d2e03fa92c8a9b216cc6061c",
+            ),
+            (
+                "020000000185bed1a6da2bffbd60ec681a1bfb71c5111d6395b99b3f8b2bf90167111bcb18f5010000007c83ace802ded24a00000000001600142c4698f9f7a773866879755aa78c516fb332af8e5802000000000000160014d38639dfbac4259323b98a472405db0c461b31fa61073747",
+                "0144c84d0000000000225120e3f2107989c88e67296ab2faca930efa2e3a5bd3ff0904835a11c9e807458621",
+                0,
+                TapSighashType::None,
+                None,
+                None,
+                "3129de36a5d05fff97ffca31eb75fcccbbbc27b3147a7a36a9e4b45d8b625067",
+            ),
+            (
+                "eb93dbb901028c8515589dac980b6e7f8e4088b77ed866ca0d6d210a7218b6fd0f6b22dd6d7300000000eb4740a9047efc0e0000000000160014913da2128d8fcf292b3691db0e187414aa1783825802000000000000160014913da2128d8fcf292b3691db0e187414aa178382580200000000000017a9143dd27f01c6f7ef9bb9159937b17f17065ed01a0c875802000000000000160014d7630e19df70ada9905ede1722b800c0005f246641000000",
+                "013fed110000000000225120eb536ae8c33580290630fc495046e998086a64f8f33b93b07967d9029b265c55",
+                0,
+                TapSighashType::NonePlusAnyoneCanPay,
+                None,
+                None,
+                "2441e8b0e063a2083ee790f14f2045022f07258ddde5ee01de543c9e789d80ae",
+            ),
+            (
+                "02000000017836b409a5fed32211407e44b971591f2032053f14701fb5b3a30c0ff382f2cc9c0100000061ac55f60288fb5600000000001976a9144ea02f6f182b082fb6ce47e36bbde390b6a41b5088ac58020000000000001976a9144ea02f6f182b082fb6ce47e36bbde390b6a41b5088ace4000000",
+                "01efa558000000000022512007071ea3dc7e331b0687d0193d1e6d6ed10e645ef36f10ef8831d5e522ac9e80",
+                0,
+                TapSighashType::Single,
+                None,
+                None,
+                "30239345177cadd0e3ea413d49803580abb6cb27971b481b7788a78d35117a88",
+            ),
+            (
+                "0100000001aa6deae89d5e0aaca58714fc76ef6f3c8284224888089232d4e663843ed3ab3eae010000008b6657a60450cb4c0000000000160014a3d42b5413ef0c0701c4702f3cd7d4df222c147058020000000000001976a91430b4ed8723a4ee8992aa2c8814cfe5c3ad0ab9d988ac5802000000000000160014365b1166a6ed0a5e8e9dff17a6d00bbb43454bc758020000000000001976a914bc98c51a84fe7fad5dc380eb8b39586eff47241688ac4f313247",
+                "0107af4e00000000002251202c36d243dfc06cb56a248e62df27ecba7417307511a81ae61aa41c597a929c69",
+                0,
+                TapSighashType::SinglePlusAnyoneCanPay,
+                None,
+                None,
+                "bf9c83f26c6dd16449e4921f813f551c4218e86f2ec906ca8611175b41b566df",
+            ),
+            (
+                "0200000001df8123752e8f37d132c4e9f1ff7e4f9b986ade9211267e9ebd5fd22a5e718dec6d01000000ce4023b903cb7b23000000000017a914a18b36ea7a094db2f4940fc09edf154e86de7bd787580200000000000017a914afd0d512a2c5c2b40e25669e9cc460303c325b8b87580200000000000017a914a18b36ea7a094db2f4940fc09edf154e86de7bd787f6020000",
+                "01ea49260000000000225120ab5e9800806bf18cb246edcf5fe63441208fe955a4b5a35bbff65f5db622a010",
+                0,
+                TapSighashType::SinglePlusAnyoneCanPay,
+                Some(
+                    "507b979802e62d397acb29f56743a791894b99372872fc5af06a4f6e8d242d0615cda53062bb20e6ec79756fe39183f0c128adfe85559a8fa042b042c018aa8010143799e44f0893c40e1e",
+                ),
+                None,
+                "3b003000add359a364a156e73e02846782a59d0d95ca8c4638aaad99f2ef915c",
+            ),
+            (
+                "020000000189fc651483f9296b906455dd939813bf086b1bbe7c77635e157c8e14ae29062195010000004445b5c7044561320000000000160014331414dbdada7fb578f700f38fb69995fc9b5ab958020000000000001976a914268db0a8104cc6d8afd91233cc8b3d1ace8ac3ef88ac580200000000000017a914ec00dcb368d6a693e11986d265f659d2f59e8be2875802000000000000160014c715799a49a0bae3956df9c17cb4440a673ac0df6f010000",
+                "011bec34000000000022512028055142ea437db73382e991861446040b61dd2185c4891d7daf6893d79f7182",
+                0,
+                TapSighashType::All,
+                None,
+                Some("20cc4e1107aea1d170c5ff5b6817e1303010049724fb3caa7941792ea9d29b3e2bacab"),
+                "d66de5274a60400c7b08c86ba6b7f198f40660079edf53aca89d2a9501317f2e",
+            ),
+            (
+                "020000000132fb72cb8fba496755f027a9743e2d698c831fdb8304e4d1a346ac92cbf51acba50100000026bdc7df044aad34000000000017a9144fa2554ed6174586854fa3bc01de58dcf33567d0875802000000000000160014950367e1e62cdf240b35b883fc2f5e39f0eb9ab95802000000000000160014950367e1e62cdf240b35b883fc2f5e39f0eb9ab958020000000000001600141b31217d48ccc8760dcc0710fade5866d628e733a02d5122",
+                "011458360000000000225120a7baec3fb9f84614e3899fcc010c638f80f13539344120e1f4d8b68a9a011a13",
+                0,
+                TapSighashType::All,
+                Some(
+                    "50a6272b470e1460e3332ade7bb14b81671c564fb6245761bd5bd531394b28860e0b3808ab229fb51791fb6ae6fa82d915b2efb8f6df83ae1f5ab3db13e30928875e2a22b749d89358de481f19286cd4caa792ce27f9559082d227a731c5486882cc707f83da361c51b7aadd9a0cf68fe7480c410fa137b454482d9a1ebf0f96d760b4d61426fc109c6e8e99a508372c45caa7b000a41f8251305da3f206c1849985ba03f3d9592832b4053afbd23ab25d0465df0bc25a36c223aacf8e04ec736a418c72dc319e4da3e972e349713ca600965e7c665f2090d5a70e241ac164115a1f5639f28b1773327715ca307ace64a2de7f0e3df70a2ffee3857689f909c0dad46d8a20fa373a4cc6eed6d4c9806bf146f0d76baae1",
+                ),
+                Some(
+                    "7520ab9160dd8299dc1367659be3e8f66781fe440d52940c7f8d314a89b9f2698d406ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6eadac",
+                ),
+                "a0042aa434f9a75904b64043f2a283f8b4c143c7f4f7f49a6cbe5b9f745f4c15",
+            ),
+        ];
+        for (tx, prevouts, input_index, sighash_type, annex, tapscript, expected) in vectors {
+            let tx = BitcoinTransaction::from_bytes_exact(&hex::decode(tx).unwrap()).unwrap();
+            let prevouts = hex::decode(prevouts).unwrap();
+            let (count, used) = CompactSize::from_bytes(&prevouts).unwrap();
+            let mut rest = &prevouts[used..];
+            let prevouts: Vec<_> = (0..count.value)
+                .map(|_| {
+                    let (output, used) = TransactionOutput::from_bytes(rest).unwrap();
+                    rest = &rest[used..];
+                    output
+                })
+                .collect();
+            let annex = annex.map(|annex| hex::decode(annex).unwrap());
+            let leaf_hash = tapscript.map(|script| {
+                Script::from_hex(script)
+                    .unwrap()
+                    .tap_leaf_hash(TAPROOT_LEAF_TAPSCRIPT)
+            });
+            let sighash = tx
+                .taproot_sighash(
+                    input_index,
+                    &prevouts,
+                    sighash_type,
+                    leaf_hash,
+                    annex.as_deref(),
+                )
+                .unwrap();
+            assert_eq!(sighash.to_string(), expected, "{sighash_type:?}");
+        }
+    }
+
+    #[test]
+    fn test_taproot_sighash_errors() {
+        let tx = BitcoinTransaction::new(
+            2,
+            vec![TransactionInput::new(
+                OutPoint::new([1; 32], 0),
+                Script::new(vec![]),
+                0xffffffff,
+            )],
+            0,
+        );
+        let prevout = TransactionOutput::new(
+            Amount::from_sat(1000),
+            Script::new_p2tr(&XOnlyPublicKey([2; 32])),
+        );
+        let prevouts = [prevout.clone()];
+        assert_eq!(
+            tx.taproot_sighash(0, &[], TapSighashType::All, None, None),
+            Err(BitcoinError::PrevoutsLengthMismatch {
+                inputs: 1,
+                prevouts: 0
+            })
+        );
+        assert_eq!(
+            tx.taproot_sighash(
+                0,
+                &[prevout.clone(), prevout],
+                TapSighashType::All,
+                None,
+                None
+            ),
+            Err(BitcoinError::PrevoutsLengthMismatch {
+                inputs: 1,
+                prevouts: 2
+            })
+        );
+        assert_eq!(
+            tx.taproot_sighash(1, &prevouts, TapSighashType::All, None, None),
+            Err(BitcoinError::IndexOutOfRange { index: 1, len: 1 })
+        );
+        assert_eq!(
+            tx.taproot_sighash(0, &prevouts, TapSighashType::Single, None, None),
+            Err(BitcoinError::SighashSingleWithoutOutput { index: 0 })
+        );
+        assert_eq!(
+            tx.taproot_sighash(0, &prevouts, TapSighashType::All, None, Some(&[0x51])),
+            Err(BitcoinError::InvalidAnnex)
+        );
+
+        // Default signs the same fields as All, but commits to a different type byte.
+        assert_ne!(
+            tx.taproot_sighash(0, &prevouts, TapSighashType::Default, None, None),
+            tx.taproot_sighash(0, &prevouts, TapSighashType::All, None, None)
+        );
+    }
+
+    #[test]
+    fn test_sighash_cache_matches_uncached() {
+        let inputs = (0..5u8)
+            .map(|i| {
+                TransactionInput::new(
+                    OutPoint::new([i + 1; 32], u32::from(i)),
+                    Script::new(vec![]),
+                    0xffff_fffd - u32::from(i),
+                )
+            })
+            .collect();
+        let outputs = (0..3u8)
+            .map(|i| {
+                TransactionOutput::new(
+                    Amount::from_sat(10_000 * (u64::from(i) + 1)),
+                    Script::new_p2wpkh(&Hash160([i; 20])),
+                )
+            })
+            .collect();
+        let tx = BitcoinTransaction::new_with_outputs(2, inputs, outputs, 800_000);
+        let prevouts: Vec<_> = (0..5u8)
+            .map(|i| {
+                TransactionOutput::new(
+                    Amount::from_sat(50_000 + u64::from(i)),
+                    Script::new_p2tr(&XOnlyPublicKey([i; 32])),
+                )
+            })
+            .collect();
+        let script_code = Script::new_p2pkh(&Hash160([9; 20]));
+
+        let mut cache = SighashCache::new(&tx);
+        for input_index in 0..5 {
+            for byte in [0x01, 0x02, 0x03, 0x81, 0x82, 0x83] {
+                let sighash_type = SighashType::from_u8(byte).unwrap();
+                let value = Amount::from_sat(1000);
+                assert_eq!(
+                    cache.legacy_sighash(input_index, &script_code, sighash_type),
+                    tx.legacy_sighash(input_index, &script_code, sighash_type)
+                );
+                assert_eq!(
+                    cache.segwit_v0_sighash(input_index, &script_code, value, sighash_type),
+                    tx.segwit_v0_sighash(input_index, &script_code, value, sighash_type)
+                );
+            }
+            for byte in [0x00, 0x01, 0x02, 0x03, 0x81, 0x82, 0x83] {
+                let sighash_type = TapSighashType::from_u8(byte).unwrap();
+                let leaf_hash = Some(Hash256([7; 32]));
+                assert_eq!(
+                    cache.taproot_sighash(input_index, &prevouts, sighash_type, None, None),
+                    tx.taproot_sighash(input_index, &prevouts, sighash_type, None, None)
+                );
+                assert_eq!(
+                    cache.taproot_sighash(input_index, &prevouts, sighash_type, leaf_hash, None),
+                    tx.taproot_sighash(input_index, &prevouts, sighash_type, leaf_hash, None)
+                );
+            }
+        }
+        assert_eq!(*cache.segwit_v0_hashes(), tx.segwit_v0_hashes());
+        assert_eq!(
+            *cache.taproot_hashes(&prevouts).unwrap(),
+            tx.taproot_hashes(&prevouts).unwrap()
+        );
+        assert_eq!(
+            cache.taproot_sighash(0, &prevouts[1..], TapSighashType::All, None, None),
+            Err(BitcoinError::PrevoutsLengthMismatch {
+                inputs: 5,
+                prevouts: 4
+            })
+        );
+
+        // Once cached, the per-transaction hashes are not recomputed: each further hash
+        // allocates the same amount whichever input it is for, and less than without
+        // the cache, which serializes every input and output again.
+        let allocations = |f: &mut dyn FnMut()| {
+            let before = alloc_counter::allocations();
+            f();
+            alloc_counter::allocations() - before
+        };
+        let value = Amount::from_sat(1000);
+        let cached: Vec<_> = (0..5)
+            .map(|input_index| {
+                allocations(&mut || {
+                    cache
+                        .segwit_v0_sighash(input_index, &script_code, value, SighashType::All)
+                        .unwrap();
+                })
+            })
+            .collect();
+        let uncached = allocations(&mut || {
+            tx.segwit_v0_sighash(0, &script_code, value, SighashType::All)
+                .unwrap();
+        });
+        assert!(cached.iter().all(|&n| n == cached[0]));
+        assert!(cached[0] < uncached, "{cached:?} vs {uncached}");
+
+        let cached: Vec<_> = (0..5)
+            .map(|input_index| {
+                allocations(&mut || {
+                    cache
+                        .taproot_sighash(
+                            input_index,
+                            &prevouts,
+                            TapSighashType::Default,
+                            None,
+                            None,
+                        )
+                        .unwrap();
+                })
+            })
+            .collect();
+        let uncached = allocations(&mut || {
+            tx.taproot_sighash(0, &prevouts, TapSighashType::Default, None, None)
+                .unwrap();
+        });
+        assert!(cached.iter().all(|&n| n == cached[0]));
+        assert!(cached[0] < uncached, "{cached:?} vs {uncached}");
+    }
+
+    #[test]
+    fn test_p2wpkh_script_code_and_signature_hash() {
+        // The BIP143 examples again, deriving the script codes from the spent programs.
+        let script_pubkey =
+            Script::from_hex("00141d0f172a0ecb48aee1be1f2687d2963ae33f71a1").unwrap();
+        assert_eq!(
+            script_pubkey.p2wpkh_script_code().unwrap().to_hex(),
+            "76a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a188ac"
+        );
+        let tx =
+            BitcoinTransaction::from_bytes_exact(&hex::decode(BIP143_P2WPKH_TX).unwrap()).unwrap();
+        let mut cache = SighashCache::new(&tx);
+        let sighash = cache
+            .p2wpkh_signature_hash(
+                1,
+                &script_pubkey,
+                Amount::from_sat(600_000_000),
+                SighashType::All,
+            )
+            .unwrap();
+        assert_eq!(
+            sighash.to_hex_internal(),
+            "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670"
+        );
+
+        let redeem_script =
+            Script::from_hex("001479091972186c449eb1ded22b78e40d009bdf0089").unwrap();
+        let tx = BitcoinTransaction::from_bytes_exact(&hex::decode(BIP143_P2SH_P2WPKH_TX).unwrap())
+            .unwrap();
+        let mut cache = SighashCache::new(&tx);
+        let sighash = cache
+            .p2sh_p2wpkh_signature_hash(
+                0,
+                &redeem_script,
+                Amount::from_sat(1_000_000_000),
+                SighashType::All,
+            )
+            .unwrap();
+        assert_eq!(
+            sighash.to_hex_internal(),
+            "64f3b0f4dd2bb3aa1ce8566d220cc74dda9df97d8490cc81d89d735c92e59fb6"
+        );
+
+        // Passing the P2PKH script code or the P2SH scriptPubKey instead is caught.
+        let p2pkh = script_pubkey.p2wpkh_script_code().unwrap();
+        let p2sh = Script::new_p2sh(&hash160(&redeem_script));
+        for script in [&p2pkh, &p2sh, &Script::new_p2wsh(&Hash256([1; 32]))] {
+            assert_eq!(script.p2wpkh_script_code(), None);
+            assert_eq!(
+                cache.p2sh_p2wpkh_signature_hash(0, script, Amount::ZERO, SighashType::All),
+                Err(BitcoinError::UnsupportedScript)
+            );
+        }
+    }
+
+    /// `(script_pubkey, amount, input_index, flags)` for one `ScriptVerifier::verify` call.
+    type VerifyCall = (Vec<u8>, u64, usize, u32);
+
+    /// Accepts only spends of `accepted`, recording each call it sees.
+    struct MockVerifier {
+        accepted: Script,
+        calls: std::cell::RefCell<Vec<VerifyCall>>,
+    }
+
+    impl ScriptVerifier for MockVerifier {
+        fn verify(
+            &self,
+            script_pubkey: &[u8],
+            amount: u64,
+            tx_bytes: &[u8],
+            input_index: usize,
+            flags: u32,
+        ) -> Result<(), VerifyError> {
+            let (tx, _) = BitcoinTransaction::from_bytes(tx_bytes).unwrap();
+            assert!(input_index < tx.inputs.len());
+            self.calls
+                .borrow_mut()
+                .push((script_pubkey.to_vec(), amount, input_index, flags));
+            if script_pubkey == self.accepted.as_bytes() {
+                Ok(())
+            } else {
+                Err(VerifyError::ScriptFailed { index: input_index })
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_with_script_verifier() {
+        let accepted = Script::new_p2wsh(&Hash256([0x11; 32]));
+        let rejected = Script::new_p2sh(&Hash160([0x22; 20]));
+        let tx = BitcoinTransaction::new_with_outputs(
+            2,
+            (0..2)
+                .map(|vout| {
+                    TransactionInput::new(
+                        OutPoint::from_txid(Txid([0x33; 32]), vout),
+                        Script::new(vec![]),
+                        0xFFFFFFFF,
+                    )
+                })
+                .collect(),
+            vec![TransactionOutput::new(
+                Amount::from_sat(1_000),
+                accepted.clone(),
+            )],
+            0,
+        );
+        let verifier = MockVerifier {
+            accepted: accepted.clone(),
+            calls: Default::default(),
+        };
+        let prevouts = [
+            TransactionOutput::new(Amount::from_sat(600), accepted.clone()),
+            TransactionOutput::new(Amount::from_sat(700), accepted.clone()),
+        ];
+        assert_eq!(tx.verify_with(&verifier, &prevouts), Ok(()));
+        assert_eq!(
+            *verifier.calls.borrow(),
+            vec![
+                (accepted.as_bytes().to_vec(), 600, 0, verify::VERIFY_ALL),
+                (accepted.as_bytes().to_vec(), 700, 1, verify::VERIFY_ALL),
+            ]
+        );
+
+        // The first rejected input ends the run.
+        verifier.calls.borrow_mut().clear();
+        let prevouts = [
+            TransactionOutput::new(Amount::from_sat(600), rejected.clone()),
+            TransactionOutput::new(Amount::from_sat(700), rejected),
+        ];
+        assert_eq!(
+            tx.verify_with(&verifier, &prevouts),
+            Err(VerifyError::ScriptFailed { index: 0 })
+        );
+        assert_eq!(verifier.calls.borrow().len(), 1);
+        assert_eq!(
+            tx.verify_with(&verifier, &prevouts[..1]),
+            Err(VerifyError::MissingPrevouts)
+        );
+    }
+
+    #[test]
+    fn test_null_outpoint() {
+        assert!(Txid::ZERO.is_zero());
+        assert_eq!(Txid::all_zeros(), Txid::ZERO);
+        assert!(!Txid(dummy_txid(1)).is_zero());
+
+        let null = OutPoint::null();
+        assert!(null.is_null());
+        assert_eq!(null.to_bytes(), [[0u8; 32].as_slice(), &[0xFF; 4]].concat());
+        assert!(!OutPoint::new([0u8; 32], 0).is_null());
+        assert!(!OutPoint::new(dummy_txid(1), u32::MAX).is_null());
+    }
+
+    #[test]
+    fn test_bitcoin_tx_is_coinbase() {
+        // Input of the genesis block coinbase transaction.
+        let genesis_input = hex::decode(
+            "0000000000000000000000000000000000000000000000000000000000000000ffffffff\
+             4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c\
+             6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e\
+             6b73ffffffff",
+        )
+        .unwrap();
+        let (input, consumed) = TransactionInput::from_bytes(&genesis_input).unwrap();
+        assert_eq!(consumed, genesis_input.len());
+        assert!(input.previous_output.is_null());
+
+        let coinbase = BitcoinTransaction::new(1, vec![input.clone()], 0);
+        assert!(coinbase.is_coinbase());
+
+        let spend = TransactionInput::new(
+            OutPoint::new(dummy_txid(1), 0),
+            Script::new(vec![]),
+            0xFFFFFFFF,
+        );
+        assert!(!BitcoinTransaction::new(1, vec![input, spend.clone()], 0).is_coinbase());
+        assert!(!BitcoinTransaction::new(1, vec![spend], 0).is_coinbase());
+        assert!(!BitcoinTransaction::new(1, vec![], 0).is_coinbase());
+    }
+
+    #[test]
+    fn test_bitcoin_tx_wtxid_without_witness() {
+        let tx = BitcoinTransaction::new(
+            1,
+            vec![TransactionInput::new(
+                OutPoint::new(dummy_txid(1), 0),
+                Script::new(vec![0x01, 0x02]),
+                0xFFFFFFFF,
+            )],
+            0,
+        );
+        let wtxid = tx.wtxid();
+        assert_eq!(wtxid.as_bytes(), tx.txid().as_bytes());
+        assert_eq!(wtxid.to_string(), tx.txid().to_string());
+        assert_eq!(wtxid.to_string().parse::<Wtxid>().unwrap(), wtxid);
+        assert_eq!(
+            serde_json::to_string(&wtxid).unwrap(),
+            serde_json::to_string(&tx.txid()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_exact_rejects_trailing_bytes() {
+        let tx = BitcoinTransaction::new(
+            2,
+            vec![TransactionInput::new(
+                OutPoint::new(dummy_txid(1), 0),
+                Script::new(vec![0x51]),
+                0xFFFFFFFF,
+            )],
+            0,
+        );
+        let bytes = tx.to_bytes();
+        assert_eq!(BitcoinTransaction::from_bytes_exact(&bytes).unwrap(), tx);
+        assert_eq!(BitcoinTransaction::try_from(bytes.as_slice()).unwrap(), tx);
+
+        let mut padded = bytes.clone();
+        padded.push(0x00);
+        assert_eq!(
+            BitcoinTransaction::from_bytes_exact(&padded),
+            Err(BitcoinError::TrailingBytes { remaining: 1 })
+        );
+        assert_eq!(
+            BitcoinTransaction::try_from(padded.as_slice()),
+            Err(BitcoinError::TrailingBytes { remaining: 1 })
+        );
+
+        let extra = [0xAA, 0xBB];
+        let input = &tx.inputs[0];
+        let with_extra = |mut bytes: Vec<u8>| {
+            bytes.extend_from_slice(&extra);
+            bytes
+        };
+        assert_eq!(
+            TransactionInput::from_bytes_exact(&with_extra(input.to_bytes())),
+            Err(BitcoinError::TrailingBytes { remaining: 2 })
+        );
+        assert_eq!(
+            OutPoint::from_bytes_exact(&with_extra(input.previous_output.to_bytes())),
+            Err(BitcoinError::TrailingBytes { remaining: 2 })
+        );
+        assert_eq!(
+            Script::from_bytes_exact(&with_extra(input.script_sig.to_bytes())),
+            Err(BitcoinError::TrailingBytes { remaining: 2 })
+        );
+        assert_eq!(
+            CompactSize::from_bytes_exact(&with_extra(vec![0x05])),
+            Err(BitcoinError::TrailingBytes { remaining: 2 })
+        );
+
+        assert_eq!(
+            TransactionInput::try_from(input.to_bytes().as_slice()).unwrap(),
+            *input
+        );
+        assert_eq!(CompactSize::try_from(&[0x05u8][..]).unwrap().value, 5);
+    }
+
+    #[test]
+    fn test_bitcoin_tx_json_serialization() {
+        let input = TransactionInput::new(
+            OutPoint::new(dummy_txid(0xAB), 3),
+            Script::new(vec![0xDE, 0xAD, 0xBE, 0xEF]),
+            0xABCDEF01,
+        );
+        let tx = BitcoinTransaction::new(1, vec![input], 999);
+
+        let json = serde_json::to_string_pretty(&tx).unwrap();
+        let parsed: BitcoinTransaction = serde_json::from_str(&json).unwrap();
         assert_eq!(tx, parsed);
 
         assert!(json.contains("\"version\": 1"));
@@ -109,5 +6189,682 @@ mod tests {
         assert!(output.contains("Version: 1"));
         assert!(output.contains("Lock Time: 0"));
         assert!(output.contains("Vout: 7"));
+        assert!(output.contains(&format!("Txid: cd{}", "00".repeat(31))));
+        assert!(output.contains("ScriptSig (3 bytes): 010203"));
+        assert!(output.contains("Sequence: 4294967295 (final)"));
+    }
+
+    #[test]
+    fn test_lock_time_and_sequence_descriptions() {
+        let mut tx = BitcoinTransaction::new(2, vec![], 0);
+        assert_eq!(tx.lock_time_description(), "0 (none)");
+        tx.lock_time = 850_000;
+        assert_eq!(tx.lock_time_description(), "850000 (block height)");
+        tx.lock_time = 1_718_000_000;
+        assert_eq!(
+            tx.lock_time_description(),
+            "1718000000 (UNIX time ≈ 2024-06-10T06:13:20Z)"
+        );
+        assert!(
+            format!("{tx}").contains("Lock Time: 1718000000 (UNIX time ≈ 2024-06-10T06:13:20Z)")
+        );
+        tx.lock_time = 500_000_000;
+        assert_eq!(
+            tx.lock_time_description(),
+            "500000000 (UNIX time ≈ 1985-11-05T00:53:20Z)"
+        );
+        tx.lock_time = u32::MAX;
+        assert_eq!(
+            tx.lock_time_description(),
+            "4294967295 (UNIX time ≈ 2106-02-07T06:28:15Z)"
+        );
+
+        assert_eq!(
+            Sequence::ENABLE_RBF_NO_LOCKTIME.description(2),
+            "4294967293 (RBF)"
+        );
+        assert_eq!(
+            Sequence::ENABLE_LOCKTIME_NO_RBF.description(2),
+            "4294967294 (no RBF)"
+        );
+        assert_eq!(
+            Sequence(10).description(2),
+            "10 (RBF, relative lock: 10 blocks)"
+        );
+        assert_eq!(
+            Sequence((1 << 22) | 2).description(2),
+            "4194306 (RBF, relative lock: 1024 seconds)"
+        );
+        // BIP68 does not apply before version 2.
+        assert_eq!(Sequence(10).description(1), "10 (RBF)");
+        let input = TransactionInput::new(
+            OutPoint::from_txid(Txid([0x11; 32]), 0),
+            Script::new(vec![]),
+            10,
+        );
+        let mut tx = BitcoinTransaction::new(1, vec![input], 0);
+        assert!(format!("{tx}").contains("Sequence: 10 (RBF)\n"));
+        tx.version = 2;
+        assert!(format!("{tx}").contains("Sequence: 10 (RBF, relative lock: 10 blocks)"));
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod property_tests {
+    use super::*;
+    use arbitrary::{Arbitrary, Unstructured};
+
+    // Deterministic xorshift so failures are reproducible without extra dependencies.
+    fn random_buffers(count: usize, max_len: usize) -> Vec<Vec<u8>> {
+        let mut state = 0x2545F4914F6CDD1Du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        (0..count)
+            .map(|_| {
+                let len = (next() as usize) % max_len;
+                (0..len).map(|_| next() as u8).collect()
+            })
+            .collect()
+    }
+
+    fn check_roundtrip<'a, T>(u: &mut Unstructured<'a>)
+    where
+        T: Arbitrary<'a> + Encodable + PartialEq + std::fmt::Debug,
+    {
+        if let Ok(value) = T::arbitrary(u) {
+            let bytes = value.encode();
+            let (decoded, consumed) = T::decode(&bytes).unwrap();
+            assert_eq!(decoded, value);
+            assert_eq!(consumed, bytes.len());
+        }
+    }
+
+    trait Encodable: Sized {
+        fn encode(&self) -> Vec<u8>;
+        fn decode(bytes: &[u8]) -> Result<(Self, usize), BitcoinError>;
+    }
+
+    macro_rules! impl_encodable {
+        ($($t:ty),*) => {
+            $(
+                impl Encodable for $t {
+                    fn encode(&self) -> Vec<u8> {
+                        self.to_bytes()
+                    }
+
+                    fn decode(bytes: &[u8]) -> Result<(Self, usize), BitcoinError> {
+                        <$t>::from_bytes(bytes)
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_encodable!(
+        CompactSize,
+        OutPoint,
+        Script,
+        Witness,
+        TransactionInput,
+        TransactionOutput,
+        BitcoinTransaction
+    );
+
+    #[test]
+    fn prop_encode_decode_roundtrip() {
+        for buffer in random_buffers(500, 4096) {
+            let mut u = Unstructured::new(&buffer);
+            check_roundtrip::<CompactSize>(&mut u);
+            check_roundtrip::<OutPoint>(&mut u);
+            check_roundtrip::<Script>(&mut u);
+            check_roundtrip::<Witness>(&mut u);
+            check_roundtrip::<TransactionInput>(&mut u);
+            check_roundtrip::<TransactionOutput>(&mut u);
+            check_roundtrip::<BitcoinTransaction>(&mut u);
+        }
+    }
+
+    #[test]
+    fn prop_decoding_random_bytes_never_panics() {
+        for buffer in random_buffers(2000, 512) {
+            let _ = CompactSize::from_bytes(&buffer);
+            let _ = OutPoint::from_bytes(&buffer);
+            let _ = Script::from_bytes(&buffer);
+            let _ = Witness::from_bytes(&buffer);
+            let _ = TransactionInput::from_bytes(&buffer);
+            let _ = TransactionOutput::from_bytes(&buffer);
+            let _ = BitcoinTransaction::from_bytes(&buffer);
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+mod rand_fixtures {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_dummy_transaction_roundtrip() {
+        let tx = BitcoinTransaction::dummy(3);
+        assert_eq!(tx.inputs.len(), 3);
+        assert!(
+            tx.inputs
+                .iter()
+                .all(|input| input.sequence == Sequence::MAX)
+        );
+        assert!(tx.inputs.iter().all(|input| input.script_sig.is_empty()));
+
+        let bytes = tx.to_bytes();
+        let (parsed, consumed) = BitcoinTransaction::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, tx);
+        assert_eq!(consumed, bytes.len());
+    }
+
+    #[test]
+    fn test_seeded_fixtures_are_reproducible() {
+        let a = BitcoinTransaction::dummy_with_rng(&mut StdRng::seed_from_u64(7), 2);
+        let b = BitcoinTransaction::dummy_with_rng(&mut StdRng::seed_from_u64(7), 2);
+        assert_eq!(a, b);
+        assert_ne!(a.inputs[0].previous_output, a.inputs[1].previous_output);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_ne!(Txid::random(&mut rng), Txid::random(&mut rng));
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+mod secp256k1_tests {
+    use super::*;
+
+    // Legacy SIGHASH_ALL digest: every scriptSig blanked except the signed input, which
+    // carries the previous scriptPubKey, followed by the 4-byte sighash type.
+    fn legacy_sighash_all(tx: &BitcoinTransaction, index: usize, script_code: &Script) -> [u8; 32] {
+        let mut tx = tx.clone();
+        for (i, input) in tx.inputs.iter_mut().enumerate() {
+            input.script_sig = if i == index {
+                script_code.clone()
+            } else {
+                Script::new(vec![])
+            };
+        }
+        let mut bytes = tx.to_bytes();
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        sha256d(&bytes).0
+    }
+
+    #[test]
+    fn test_sign_and_verify_p2pkh_spend() {
+        let key =
+            PrivateKey::from_wif("KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617").unwrap();
+        let pubkey = key.public_key().unwrap();
+        assert!(pubkey.is_compressed());
+        let prev_script = Script::new_p2pkh_from_key(&pubkey);
+
+        let outpoint = OutPoint::from_txid(Txid([0x11; 32]), 0);
+        let mut tx = BitcoinTransaction::new(
+            2,
+            vec![TransactionInput::new(
+                outpoint,
+                Script::new(vec![]),
+                0xFFFFFFFF,
+            )],
+            0,
+        );
+        let digest = legacy_sighash_all(&tx, 0, &prev_script);
+        let sig = key.sign_ecdsa(&digest, SighashType::All).unwrap();
+        assert!(sig.is_low_s() && sig.is_strict_der());
+        tx.inputs[0].script_sig = ScriptBuilder::new()
+            .push_slice(&sig.to_bytes())
+            .push_slice(pubkey.as_bytes())
+            .into_script();
+
+        // Re-parse the signed transaction and check it the way a verifier would.
+        let (parsed, _) = BitcoinTransaction::from_bytes(&tx.to_bytes()).unwrap();
+        let pushes: Vec<&[u8]> = parsed.inputs[0]
+            .script_sig
+            .instructions()
+            .map(|instruction| match instruction.unwrap() {
+                Instruction::PushBytes(data) => data,
+                Instruction::Op(op) => panic!("unexpected {}", op),
+            })
+            .collect();
+        let sig = Signature::from_der_with_sighash(pushes[0]).unwrap();
+        let pubkey = PublicKey::from_slice(pushes[1]).unwrap();
+        assert_eq!(Script::new_p2pkh_from_key(&pubkey), prev_script);
+        assert_eq!(sig.sighash_type(), SighashType::All);
+        let digest = legacy_sighash_all(&parsed, 0, &prev_script);
+        assert_eq!(pubkey.verify_ecdsa(&digest, &sig), Ok(()));
+
+        let mut tampered = digest;
+        tampered[0] ^= 1;
+        assert_eq!(
+            pubkey.verify_ecdsa(&tampered, &sig),
+            Err(BitcoinError::InvalidSignature)
+        );
+    }
+
+    fn two_input_spend() -> BitcoinTransaction {
+        BitcoinTransaction::new_with_outputs(
+            2,
+            vec![
+                TransactionInput::new(
+                    OutPoint::from_txid(Txid([0x22; 32]), 1),
+                    Script::new(vec![]),
+                    0xFFFFFFFD,
+                ),
+                TransactionInput::new(
+                    OutPoint::from_txid(Txid([0x33; 32]), 0),
+                    Script::new(vec![]),
+                    0xFFFFFFFD,
+                ),
+            ],
+            vec![TransactionOutput::new(
+                Amount::from_sat(90_000),
+                Script::new_p2wpkh(&Hash160([0x44; 20])),
+            )],
+            0,
+        )
+    }
+
+    #[test]
+    fn test_sign_input_p2pkh_round_trip() {
+        let key =
+            PrivateKey::from_wif("KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617").unwrap();
+        let prevout = TransactionOutput::new(
+            Amount::from_sat(50_000),
+            Script::new_p2pkh_from_key(&key.public_key().unwrap()),
+        );
+        let mut tx = two_input_spend();
+        for sighash_type in [SighashType::All, SighashType::SinglePlusAnyoneCanPay] {
+            sign_input_p2pkh(&mut tx, 0, &key, sighash_type).unwrap();
+            let (parsed, _) = BitcoinTransaction::from_bytes(&tx.to_bytes()).unwrap();
+            assert_eq!(parsed.verify_input(0, &prevout), Ok(()));
+        }
+
+        // Another key's output, and a changed transaction.
+        let other = PrivateKey::new([0x55; 32], Network::Mainnet, true);
+        let other_prevout = TransactionOutput::new(
+            Amount::from_sat(50_000),
+            Script::new_p2pkh_from_key(&other.public_key().unwrap()),
+        );
+        assert_eq!(
+            tx.verify_input(0, &other_prevout),
+            Err(VerifyError::KeyMismatch)
+        );
+        tx.lock_time = 1;
+        assert_eq!(
+            tx.verify_input(0, &prevout),
+            Err(VerifyError::InvalidSignature)
+        );
+        assert_eq!(
+            sign_input_p2pkh(&mut tx, 2, &key, SighashType::All),
+            Err(BitcoinError::IndexOutOfRange { index: 2, len: 2 })
+        );
+    }
+
+    #[test]
+    fn test_sign_input_p2wpkh_round_trip() {
+        let key =
+            PrivateKey::from_wif("KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617").unwrap();
+        let value = Amount::from_sat(50_000);
+        let prevout = TransactionOutput::new(
+            value,
+            Script::new_p2wpkh_from_key(&key.public_key().unwrap()).unwrap(),
+        );
+        let mut tx = two_input_spend();
+        tx.inputs[1].script_sig = Script::new(vec![0x51]);
+        sign_input_p2wpkh(&mut tx, 1, &key, value, SighashType::All).unwrap();
+        assert!(tx.inputs[1].script_sig.is_empty());
+        assert_eq!(tx.inputs[1].witness.element_count(), 2);
+        let (parsed, _) = BitcoinTransaction::from_bytes(&tx.to_bytes()).unwrap();
+        assert_eq!(parsed.verify_input(1, &prevout), Ok(()));
+
+        // The signature commits to the spent value.
+        let wrong_value =
+            TransactionOutput::new(Amount::from_sat(50_001), prevout.script_pubkey.clone());
+        assert_eq!(
+            parsed.verify_input(1, &wrong_value),
+            Err(VerifyError::InvalidSignature)
+        );
+        // Input 0 has no witness, and P2WSH isn't handled here.
+        assert_eq!(
+            parsed.verify_input(0, &prevout),
+            Err(VerifyError::MalformedSpend)
+        );
+        let p2wsh = TransactionOutput::new(value, Script::new_p2wsh(&Hash256([2; 32])));
+        assert_eq!(
+            parsed.verify_input(1, &p2wsh),
+            Err(VerifyError::UnsupportedScriptType(ScriptType::P2WSH))
+        );
+
+        let uncompressed = PrivateKey::new([0x55; 32], Network::Mainnet, false);
+        assert_eq!(
+            sign_input_p2wpkh(&mut tx, 0, &uncompressed, value, SighashType::All),
+            Err(BitcoinError::UncompressedKey)
+        );
+    }
+    #[test]
+    fn test_verify_input_undefined_sighash_byte() {
+        let key =
+            PrivateKey::from_wif("KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617").unwrap();
+        let pubkey = key.public_key().unwrap();
+        let outpoint = OutPoint::from_txid(Txid([0x11; 32]), 0);
+        let mut tx = BitcoinTransaction::new(
+            2,
+            vec![TransactionInput::new(
+                outpoint,
+                Script::new(vec![]),
+                0xFFFFFFFF,
+            )],
+            0,
+        );
+        // 0x04 is no defined type: it signs like ALL, but the hash commits to 0x04.
+        let with_byte = |digest: Hash256, byte: u8| {
+            let mut bytes = key
+                .sign_ecdsa(&digest.0, SighashType::All)
+                .unwrap()
+                .der()
+                .to_vec();
+            bytes.push(byte);
+            bytes
+        };
+
+        let p2pkh =
+            TransactionOutput::new(Amount::from_sat(1_000), Script::new_p2pkh_from_key(&pubkey));
+        let digest = tx
+            .legacy_sighash_raw(0, &p2pkh.script_pubkey, 0x04)
+            .unwrap();
+        tx.inputs[0].script_sig = ScriptBuilder::new()
+            .push_slice(&with_byte(digest, 0x04))
+            .push_slice(pubkey.as_bytes())
+            .into_script();
+        assert_eq!(tx.verify_input(0, &p2pkh), Ok(()));
+        let digest = tx
+            .legacy_sighash(0, &p2pkh.script_pubkey, SighashType::All)
+            .unwrap();
+        tx.inputs[0].script_sig = ScriptBuilder::new()
+            .push_slice(&with_byte(digest, 0x04))
+            .push_slice(pubkey.as_bytes())
+            .into_script();
+        assert_eq!(
+            tx.verify_input(0, &p2pkh),
+            Err(VerifyError::InvalidSignature)
+        );
+
+        tx.inputs[0].script_sig = Script::new(vec![]);
+        let p2wpkh = TransactionOutput::new(
+            Amount::from_sat(1_000),
+            Script::new_p2wpkh_from_key(&pubkey).unwrap(),
+        );
+        let script_code = p2wpkh.script_pubkey.p2wpkh_script_code().unwrap();
+        let digest = tx
+            .segwit_v0_sighash_raw(0, &script_code, p2wpkh.value, 0x04)
+            .unwrap();
+        let mut witness = Witness::new();
+        witness.push(with_byte(digest, 0x04));
+        witness.push(pubkey.as_bytes());
+        tx.inputs[0].witness = witness;
+        assert_eq!(tx.verify_input(0, &p2wpkh), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_input_mainnet_spends() {
+        let p2wpkh_spend =
+            BitcoinTransaction::from_bytes_exact(&hex::decode(BLOCK_702861_P2WPKH_SPEND).unwrap())
+                .unwrap();
+        let p2wpkh_prevout = TransactionOutput::new(
+            Amount::from_sat(10_242_295),
+            Script::from_hex("00144b184302bcb8060c50dfc8fa311be9d99803d44c").unwrap(),
+        );
+        assert_eq!(p2wpkh_spend.verify_input(0, &p2wpkh_prevout), Ok(()));
+
+        let p2pkh_spend =
+            BitcoinTransaction::from_bytes_exact(&hex::decode(BLOCK_702861_P2PKH_SPEND).unwrap())
+                .unwrap();
+        assert_eq!(
+            p2pkh_spend.inputs[0].previous_output,
+            OutPoint::from_txid(p2wpkh_spend.txid(), 1)
+        );
+        assert_eq!(
+            p2pkh_spend.verify_input(0, &p2wpkh_spend.outputs[1]),
+            Ok(())
+        );
+        assert_eq!(
+            p2pkh_spend.verify_input(1, &p2wpkh_spend.outputs[1]),
+            Err(VerifyError::IndexOutOfRange { index: 1, len: 1 })
+        );
+
+        // A changed byte of r still decodes, but no longer verifies.
+        let mut corrupted = p2pkh_spend.clone();
+        let mut script_sig = corrupted.inputs[0].script_sig.as_bytes().to_vec();
+        script_sig[10] ^= 1;
+        corrupted.inputs[0].script_sig = Script::new(script_sig);
+        assert_eq!(
+            corrupted.verify_input(0, &p2wpkh_spend.outputs[1]),
+            Err(VerifyError::InvalidSignature)
+        );
+
+        // The same signature with S negated is valid to secp256k1, but not to policy.
+        let mut high_s = p2wpkh_spend.clone();
+        let mut witness = Witness::new();
+        witness.push(
+            hex::decode(
+                "304502203bc0516d57d56304b4784f2e985937755d38bef594788d363d80da532bf531050221\
+                 00b14610504a58ad9d2e69743687c0f7d7b296ee94b7bc40dcd44244598b88941b01",
+            )
+            .unwrap(),
+        );
+        witness.push(p2wpkh_spend.inputs[0].witness.nth(1).unwrap());
+        high_s.inputs[0].witness = witness;
+        assert_eq!(
+            high_s.verify_input(0, &p2wpkh_prevout),
+            Err(VerifyError::HighS)
+        );
+    }
+
+    #[test]
+    fn test_verify_input_p2tr_key_path() {
+        use secp256k1::{Keypair, Message, Scalar, Secp256k1};
+
+        // No mainnet taproot spend is at hand, so sign one the way a wallet would: with the
+        // internal key tweaked by an empty script tree.
+        let secp = Secp256k1::new();
+        let internal = Keypair::from_seckey_slice(&secp, &[0x42; 32]).unwrap();
+        let (internal_key, _) = internal.x_only_public_key();
+        let tweak = tagged_hash("TapTweak", &internal_key.serialize());
+        let tweaked = internal
+            .add_xonly_tweak(&secp, &Scalar::from_be_bytes(tweak).unwrap())
+            .unwrap();
+        let output_key = XOnlyPublicKey(tweaked.x_only_public_key().0.serialize());
+        let prevout =
+            TransactionOutput::new(Amount::from_sat(20_000), Script::new_p2tr(&output_key));
+
+        let mut tx = BitcoinTransaction::new_with_outputs(
+            2,
+            vec![TransactionInput::new(
+                OutPoint::from_txid(Txid([0x66; 32]), 0),
+                Script::new(vec![]),
+                0xFFFFFFFD,
+            )],
+            vec![TransactionOutput::new(
+                Amount::from_sat(19_000),
+                Script::new_p2wpkh(&Hash160([0x77; 20])),
+            )],
+            0,
+        );
+        let sign = |tx: &BitcoinTransaction, prevouts: &[TransactionOutput], sighash_type| {
+            let sighash = tx
+                .taproot_sighash(0, prevouts, sighash_type, None, None)
+                .unwrap();
+            let signature =
+                secp.sign_schnorr_no_aux_rand(&Message::from_digest(sighash.0), &tweaked);
+            let mut witness = Witness::new();
+            let mut bytes = signature.serialize().to_vec();
+            if sighash_type != TapSighashType::Default {
+                bytes.push(sighash_type.to_u8());
+            }
+            witness.push(bytes);
+            witness
+        };
+        for sighash_type in [
+            TapSighashType::Default,
+            TapSighashType::SinglePlusAnyoneCanPay,
+        ] {
+            tx.inputs[0].witness = sign(&tx, std::slice::from_ref(&prevout), sighash_type);
+            let (parsed, _) = BitcoinTransaction::from_bytes(&tx.to_bytes()).unwrap();
+            assert_eq!(parsed.verify_input(0, &prevout), Ok(()));
+        }
+
+        let mut corrupted = tx.clone();
+        corrupted.lock_time = 1;
+        assert_eq!(
+            corrupted.verify_input(0, &prevout),
+            Err(VerifyError::InvalidSignature)
+        );
+        let mut explicit_default = tx.clone();
+        let mut signature = tx.inputs[0].witness.nth(0).unwrap().to_vec();
+        signature[64] = 0x00;
+        explicit_default.inputs[0].witness = Witness::new();
+        explicit_default.inputs[0].witness.push(signature);
+        assert_eq!(
+            explicit_default.verify_input(0, &prevout),
+            Err(VerifyError::InvalidSignatureEncoding)
+        );
+        let mut script_path = tx.clone();
+        script_path.inputs[0].witness.push([0x51]);
+        assert_eq!(
+            script_path.verify_input(0, &prevout),
+            Err(VerifyError::UnsupportedScriptType(ScriptType::P2TR))
+        );
+
+        // With a second input, the signature commits to both spent outputs.
+        let other_prevout = TransactionOutput::new(
+            Amount::from_sat(5_000),
+            Script::new_p2wpkh(&Hash160([1; 20])),
+        );
+        tx.inputs.push(TransactionInput::new(
+            OutPoint::from_txid(Txid([0x88; 32]), 3),
+            Script::new(vec![]),
+            0xFFFFFFFD,
+        ));
+        let prevouts = [prevout.clone(), other_prevout];
+        tx.inputs[0].witness = sign(&tx, &prevouts, TapSighashType::All);
+        assert_eq!(
+            tx.verify_input(0, &prevout),
+            Err(VerifyError::MissingPrevouts)
+        );
+        assert_eq!(tx.verify_input_with_prevouts(0, &prevouts), Ok(()));
+        assert_eq!(
+            tx.verify_input_with_prevouts(0, &prevouts[..1]),
+            Err(VerifyError::MissingPrevouts)
+        );
+    }
+
+    #[test]
+    fn test_verify_schnorr_bip340_vector() {
+        // BIP340 test vector 0.
+        let key = XOnlyPublicKey::from_hex(
+            "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+        )
+        .unwrap();
+        let sig: [u8; 64] = hex::decode(
+            "e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca8215\
+             25f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0",
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+        assert_eq!(key.verify_schnorr(&[0u8; 32], &sig), Ok(()));
+        assert_eq!(
+            key.verify_schnorr(&[1u8; 32], &sig),
+            Err(BitcoinError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_verify_taproot_commitment() {
+        for (spk, script, control_block, _) in TAPROOT_SCRIPT_PATHS {
+            let output_key = Script::from_hex(spk).unwrap().p2tr_output_key().unwrap();
+            let script = Script::new(hex::decode(script).unwrap());
+            let mut bytes = hex::decode(control_block).unwrap();
+            let parsed = ControlBlock::from_slice(&bytes).unwrap();
+            assert!(parsed.verify_taproot_commitment(&output_key, &script));
+            assert!(!parsed.verify_taproot_commitment(&output_key, &Script::new(vec![0x51])));
+
+            bytes[0] ^= 1;
+            let wrong_parity = ControlBlock::from_slice(&bytes).unwrap();
+            assert!(!wrong_parity.verify_taproot_commitment(&output_key, &script));
+        }
+    }
+}
+
+#[cfg(feature = "strict")]
+mod strict_tests {
+    use super::*;
+
+    #[test]
+    fn test_strict_amount_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<Amount>("2100000000000000").unwrap(),
+            Amount::MAX_MONEY
+        );
+        assert!(serde_json::from_str::<Amount>("2100000000000001").is_err());
+
+        let json = r#"{"value":1000,"scriptPubKey":"51"}"#;
+        assert!(serde_json::from_str::<TransactionOutput>(json).is_ok());
+        let json = r#"{"value":18446744073709551615,"scriptPubKey":"51"}"#;
+        assert!(serde_json::from_str::<TransactionOutput>(json).is_err());
+    }
+}
+
+#[cfg(feature = "bitcoinconsensus")]
+mod bitcoinconsensus_tests {
+    use super::*;
+
+    #[test]
+    fn test_bitcoin_consensus_verifies_mainnet_spends() {
+        let p2wpkh_spend =
+            BitcoinTransaction::from_bytes_exact(&hex::decode(BLOCK_702861_P2WPKH_SPEND).unwrap())
+                .unwrap();
+        let p2wpkh_prevout = TransactionOutput::new(
+            Amount::from_sat(10_242_295),
+            Script::from_hex("00144b184302bcb8060c50dfc8fa311be9d99803d44c").unwrap(),
+        );
+        let p2pkh_spend =
+            BitcoinTransaction::from_bytes_exact(&hex::decode(BLOCK_702861_P2PKH_SPEND).unwrap())
+                .unwrap();
+        let verifier = BitcoinConsensus;
+        assert_eq!(
+            p2wpkh_spend.verify_with(&verifier, std::slice::from_ref(&p2wpkh_prevout)),
+            Ok(())
+        );
+        assert_eq!(
+            p2pkh_spend.verify_with(&verifier, &p2wpkh_spend.outputs[1..]),
+            Ok(())
+        );
+
+        // The segwit signature commits to the spent value.
+        let wrong_value =
+            TransactionOutput::new(Amount::from_sat(10_242_296), p2wpkh_prevout.script_pubkey);
+        assert_eq!(
+            p2wpkh_spend.verify_with(&verifier, &[wrong_value]),
+            Err(VerifyError::ScriptFailed { index: 0 })
+        );
+        // bitcoinconsensus_ERR_TX_INDEX.
+        assert_eq!(
+            verifier.verify(
+                p2wpkh_spend.outputs[1].script_pubkey.as_bytes(),
+                61_271,
+                &p2pkh_spend.to_bytes(),
+                1,
+                verify::VERIFY_ALL,
+            ),
+            Err(VerifyError::Library(1))
+        );
     }
 }